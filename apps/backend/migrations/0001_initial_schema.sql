@@ -1,43 +1,33 @@
--- Enable foreign key support
-PRAGMA foreign_keys = ON;
-
--- Drop existing tables if they exist
-DROP TABLE IF EXISTS balances;
-DROP TABLE IF EXISTS votes;
-DROP TABLE IF EXISTS positions;
-DROP TABLE IF EXISTS messages;
-DROP TABLE IF EXISTS investments;
-DROP TABLE IF EXISTS fund_wallets;
-DROP TABLE IF EXISTS fund_members;
-DROP TABLE IF EXISTS proposals;
-DROP TABLE IF EXISTS assets;
-DROP TABLE IF EXISTS funds;
+-- Baseline schema. Uses IF NOT EXISTS so databases created by the old
+-- initialize_database() bootstrap can be adopted without data loss.
 
 -- Create funds table
-CREATE TABLE funds (
+CREATE TABLE IF NOT EXISTS funds (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     name TEXT NOT NULL UNIQUE,
     executor_address TEXT NOT NULL,
     version INTEGER NOT NULL DEFAULT 0,
     status TEXT NOT NULL DEFAULT 'active',
+    description TEXT NOT NULL DEFAULT '',
     created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
     updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
 
 -- Create assets table
-CREATE TABLE assets (
+CREATE TABLE IF NOT EXISTS assets (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     symbol TEXT NOT NULL UNIQUE,
     name TEXT NOT NULL,
     decimals INTEGER NOT NULL,
     version INTEGER NOT NULL DEFAULT 0,
     address TEXT,
+    total_supply INTEGER NOT NULL DEFAULT 0,
     created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
     updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
 
 -- Create proposals table
-CREATE TABLE proposals (
+CREATE TABLE IF NOT EXISTS proposals (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     title TEXT NOT NULL,
     description TEXT NOT NULL,
@@ -52,7 +42,7 @@ CREATE TABLE proposals (
 );
 
 -- Create fund_members table
-CREATE TABLE fund_members (
+CREATE TABLE IF NOT EXISTS fund_members (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     fund_id INTEGER NOT NULL,
     member_address TEXT NOT NULL,
@@ -65,7 +55,7 @@ CREATE TABLE fund_members (
 );
 
 -- Create fund_wallets table
-CREATE TABLE fund_wallets (
+CREATE TABLE IF NOT EXISTS fund_wallets (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     fund_id INTEGER NOT NULL,
     wallet_address TEXT NOT NULL,
@@ -76,7 +66,7 @@ CREATE TABLE fund_wallets (
 );
 
 -- Create investments table
-CREATE TABLE investments (
+CREATE TABLE IF NOT EXISTS investments (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     fund_id INTEGER NOT NULL,
     asset_id INTEGER NOT NULL,
@@ -90,7 +80,7 @@ CREATE TABLE investments (
 );
 
 -- Create messages table
-CREATE TABLE messages (
+CREATE TABLE IF NOT EXISTS messages (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     fund_id INTEGER NOT NULL,
     sender_address TEXT NOT NULL,
@@ -101,7 +91,7 @@ CREATE TABLE messages (
 );
 
 -- Create positions table
-CREATE TABLE positions (
+CREATE TABLE IF NOT EXISTS positions (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     fund_id INTEGER NOT NULL,
     asset_id INTEGER NOT NULL,
@@ -115,7 +105,7 @@ CREATE TABLE positions (
 );
 
 -- Create votes table
-CREATE TABLE votes (
+CREATE TABLE IF NOT EXISTS votes (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     proposal_id INTEGER NOT NULL,
     voter_address TEXT NOT NULL,
@@ -127,7 +117,7 @@ CREATE TABLE votes (
 );
 
 -- Create balances table
-CREATE TABLE balances (
+CREATE TABLE IF NOT EXISTS balances (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     asset_id INTEGER NOT NULL,
     holder_address TEXT NOT NULL,
@@ -139,15 +129,15 @@ CREATE TABLE balances (
 );
 
 -- Create indices for better query performance
-CREATE INDEX idx_funds_version ON funds(version);
-CREATE INDEX idx_assets_version ON assets(version);
-CREATE INDEX idx_fund_members_status ON fund_members(status);
-CREATE INDEX idx_assets_address ON assets(address);
-CREATE INDEX idx_fund_members_fund_id ON fund_members(fund_id);
-CREATE INDEX idx_fund_wallets_fund_id ON fund_wallets(fund_id);
-CREATE INDEX idx_investments_fund_id ON investments(fund_id);
-CREATE INDEX idx_investments_asset_id ON investments(asset_id);
-CREATE INDEX idx_messages_fund_id ON messages(fund_id, id DESC);
-CREATE INDEX idx_positions_fund_id ON positions(fund_id);
-CREATE INDEX idx_votes_proposal_id ON votes(proposal_id);
-CREATE INDEX idx_balances_asset_id ON balances(asset_id); 
\ No newline at end of file
+CREATE INDEX IF NOT EXISTS idx_funds_version ON funds(version);
+CREATE INDEX IF NOT EXISTS idx_assets_version ON assets(version);
+CREATE INDEX IF NOT EXISTS idx_fund_members_status ON fund_members(status);
+CREATE INDEX IF NOT EXISTS idx_assets_address ON assets(address);
+CREATE INDEX IF NOT EXISTS idx_fund_members_fund_id ON fund_members(fund_id);
+CREATE INDEX IF NOT EXISTS idx_fund_wallets_fund_id ON fund_wallets(fund_id);
+CREATE INDEX IF NOT EXISTS idx_investments_fund_id ON investments(fund_id);
+CREATE INDEX IF NOT EXISTS idx_investments_asset_id ON investments(asset_id);
+CREATE INDEX IF NOT EXISTS idx_messages_fund_id ON messages(fund_id, id DESC);
+CREATE INDEX IF NOT EXISTS idx_positions_fund_id ON positions(fund_id);
+CREATE INDEX IF NOT EXISTS idx_votes_proposal_id ON votes(proposal_id);
+CREATE INDEX IF NOT EXISTS idx_balances_asset_id ON balances(asset_id);
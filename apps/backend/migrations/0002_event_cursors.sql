@@ -0,0 +1,8 @@
+-- Per-stream cursors for the event listener. next_sequence is the first
+-- sequence number that has not been applied yet.
+CREATE TABLE event_cursors (
+    stream TEXT PRIMARY KEY,
+    next_sequence INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
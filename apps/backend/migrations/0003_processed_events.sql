@@ -0,0 +1,14 @@
+-- Ledger of every on-chain event the listener has applied. The unique key
+-- makes re-application of a replayed or duplicated event a no-op.
+CREATE TABLE processed_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_address TEXT NOT NULL,
+    event_handle TEXT NOT NULL,
+    field TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    ledger_version INTEGER NOT NULL,
+    event_type TEXT NOT NULL,
+    data TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(account_address, event_handle, field, sequence_number)
+);
@@ -0,0 +1,16 @@
+-- Events the listener could not decode. Kept verbatim so they can be
+-- inspected and replayed once a matching schema ships.
+CREATE TABLE dead_letter_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_address TEXT NOT NULL,
+    event_handle TEXT NOT NULL,
+    field TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    ledger_version INTEGER NOT NULL,
+    event_type TEXT NOT NULL,
+    data TEXT NOT NULL,
+    schema_version INTEGER NOT NULL,
+    error TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(account_address, event_handle, field, sequence_number)
+);
@@ -0,0 +1,8 @@
+-- Votes indexed from governance VoteEvent / EmergencyVetoEvent carry the
+-- on-chain vote time and are flagged so they can be told apart from votes
+-- that only went through the REST API.
+ALTER TABLE votes ADD COLUMN voted_at DATETIME;
+ALTER TABLE votes ADD COLUMN is_veto BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE votes ADD COLUMN on_chain BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX idx_proposals_chain_id ON proposals(chain_id);
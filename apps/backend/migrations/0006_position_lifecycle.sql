@@ -0,0 +1,11 @@
+-- Positions indexed from the position module. `chain_position_id` links a
+-- row to its on-chain id; closed and liquidated positions keep their exit
+-- price, realized pnl and close time for history.
+ALTER TABLE positions ADD COLUMN chain_position_id INTEGER;
+ALTER TABLE positions ADD COLUMN status TEXT NOT NULL DEFAULT 'open';
+ALTER TABLE positions ADD COLUMN exit_price INTEGER;
+ALTER TABLE positions ADD COLUMN pnl INTEGER;
+ALTER TABLE positions ADD COLUMN closed_at DATETIME;
+
+CREATE UNIQUE INDEX idx_positions_chain_position_id ON positions(chain_position_id);
+CREATE INDEX idx_positions_fund_status ON positions(fund_id, status);
@@ -0,0 +1,16 @@
+-- Per-holder shares of on-chain positions, mirroring position::UserShare.
+-- Rows are keyed by the on-chain position id so allocations can be indexed
+-- even before the position itself has been attributed to a fund.
+CREATE TABLE position_shares (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chain_position_id INTEGER NOT NULL,
+    holder_address TEXT NOT NULL,
+    shares INTEGER NOT NULL,
+    entry_timestamp DATETIME NOT NULL,
+    last_updated DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(chain_position_id, holder_address)
+);
+
+CREATE INDEX idx_position_shares_holder ON position_shares(holder_address);
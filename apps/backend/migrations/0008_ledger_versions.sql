@@ -0,0 +1,265 @@
+-- Every row derived from chain data records the ledger version it was
+-- derived from. Changes that move a row to a newer version are journaled
+-- with the row's previous image so derived state above a version can be
+-- reverted when the node's ledger goes backwards. Rows written only
+-- through the REST API keep a NULL version and are never journaled.
+
+ALTER TABLE funds ADD COLUMN ledger_version INTEGER;
+ALTER TABLE assets ADD COLUMN ledger_version INTEGER;
+ALTER TABLE balances ADD COLUMN ledger_version INTEGER;
+ALTER TABLE fund_members ADD COLUMN ledger_version INTEGER;
+ALTER TABLE proposals ADD COLUMN ledger_version INTEGER;
+ALTER TABLE votes ADD COLUMN ledger_version INTEGER;
+ALTER TABLE positions ADD COLUMN ledger_version INTEGER;
+ALTER TABLE position_shares ADD COLUMN ledger_version INTEGER;
+ALTER TABLE event_cursors ADD COLUMN ledger_version INTEGER;
+
+CREATE TABLE ledger_journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ledger_version INTEGER NOT NULL,
+    table_name TEXT NOT NULL,
+    row_id INTEGER NOT NULL,
+    operation TEXT NOT NULL,
+    previous TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_ledger_journal_version ON ledger_journal(ledger_version);
+CREATE INDEX idx_processed_events_version ON processed_events(ledger_version);
+CREATE INDEX idx_dead_letter_events_version ON dead_letter_events(ledger_version);
+
+CREATE TRIGGER ledger_journal_funds_insert AFTER INSERT ON funds
+WHEN NEW.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'funds', NEW.rowid, 'insert', NULL);
+END;
+
+CREATE TRIGGER ledger_journal_funds_update AFTER UPDATE ON funds
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'funds', NEW.rowid, 'update', json_object(
+        'id', OLD.id,
+        'name', OLD.name,
+        'executor_address', OLD.executor_address,
+        'version', OLD.version,
+        'status', OLD.status,
+        'description', OLD.description,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
+
+CREATE TRIGGER ledger_journal_assets_insert AFTER INSERT ON assets
+WHEN NEW.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'assets', NEW.rowid, 'insert', NULL);
+END;
+
+CREATE TRIGGER ledger_journal_assets_update AFTER UPDATE ON assets
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'assets', NEW.rowid, 'update', json_object(
+        'id', OLD.id,
+        'symbol', OLD.symbol,
+        'name', OLD.name,
+        'decimals', OLD.decimals,
+        'version', OLD.version,
+        'address', OLD.address,
+        'total_supply', OLD.total_supply,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
+
+CREATE TRIGGER ledger_journal_balances_insert AFTER INSERT ON balances
+WHEN NEW.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'balances', NEW.rowid, 'insert', NULL);
+END;
+
+CREATE TRIGGER ledger_journal_balances_update AFTER UPDATE ON balances
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'balances', NEW.rowid, 'update', json_object(
+        'id', OLD.id,
+        'asset_id', OLD.asset_id,
+        'holder_address', OLD.holder_address,
+        'amount', OLD.amount,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
+
+CREATE TRIGGER ledger_journal_fund_members_insert AFTER INSERT ON fund_members
+WHEN NEW.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'fund_members', NEW.rowid, 'insert', NULL);
+END;
+
+CREATE TRIGGER ledger_journal_fund_members_update AFTER UPDATE ON fund_members
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'fund_members', NEW.rowid, 'update', json_object(
+        'id', OLD.id,
+        'fund_id', OLD.fund_id,
+        'member_address', OLD.member_address,
+        'share', OLD.share,
+        'status', OLD.status,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
+
+CREATE TRIGGER ledger_journal_proposals_insert AFTER INSERT ON proposals
+WHEN NEW.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'proposals', NEW.rowid, 'insert', NULL);
+END;
+
+CREATE TRIGGER ledger_journal_proposals_update AFTER UPDATE ON proposals
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'proposals', NEW.rowid, 'update', json_object(
+        'id', OLD.id,
+        'title', OLD.title,
+        'description', OLD.description,
+        'end_time', OLD.end_time,
+        'executed', OLD.executed,
+        'vetoed', OLD.vetoed,
+        'chain_id', OLD.chain_id,
+        'synced', OLD.synced,
+        'proposer_address', OLD.proposer_address,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
+
+CREATE TRIGGER ledger_journal_votes_insert AFTER INSERT ON votes
+WHEN NEW.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'votes', NEW.rowid, 'insert', NULL);
+END;
+
+CREATE TRIGGER ledger_journal_votes_update AFTER UPDATE ON votes
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'votes', NEW.rowid, 'update', json_object(
+        'id', OLD.id,
+        'proposal_id', OLD.proposal_id,
+        'voter_address', OLD.voter_address,
+        'vote_type', OLD.vote_type,
+        'voted_at', OLD.voted_at,
+        'is_veto', OLD.is_veto,
+        'on_chain', OLD.on_chain,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
+
+CREATE TRIGGER ledger_journal_positions_insert AFTER INSERT ON positions
+WHEN NEW.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'positions', NEW.rowid, 'insert', NULL);
+END;
+
+CREATE TRIGGER ledger_journal_positions_update AFTER UPDATE ON positions
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'positions', NEW.rowid, 'update', json_object(
+        'id', OLD.id,
+        'fund_id', OLD.fund_id,
+        'asset_id', OLD.asset_id,
+        'size', OLD.size,
+        'entry_price', OLD.entry_price,
+        'is_long', OLD.is_long,
+        'chain_position_id', OLD.chain_position_id,
+        'status', OLD.status,
+        'exit_price', OLD.exit_price,
+        'pnl', OLD.pnl,
+        'closed_at', OLD.closed_at,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
+
+CREATE TRIGGER ledger_journal_position_shares_insert AFTER INSERT ON position_shares
+WHEN NEW.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'position_shares', NEW.rowid, 'insert', NULL);
+END;
+
+CREATE TRIGGER ledger_journal_position_shares_update AFTER UPDATE ON position_shares
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'position_shares', NEW.rowid, 'update', json_object(
+        'id', OLD.id,
+        'chain_position_id', OLD.chain_position_id,
+        'holder_address', OLD.holder_address,
+        'shares', OLD.shares,
+        'entry_timestamp', OLD.entry_timestamp,
+        'last_updated', OLD.last_updated,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
+
+CREATE TRIGGER ledger_journal_event_cursors_insert AFTER INSERT ON event_cursors
+WHEN NEW.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'event_cursors', NEW.rowid, 'insert', NULL);
+END;
+
+CREATE TRIGGER ledger_journal_event_cursors_update AFTER UPDATE ON event_cursors
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'event_cursors', NEW.rowid, 'update', json_object(
+        'stream', OLD.stream,
+        'next_sequence', OLD.next_sequence,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
+
+-- Members are the only derived rows deleted by sync
+CREATE TRIGGER ledger_journal_fund_members_delete AFTER DELETE ON fund_members
+WHEN OLD.ledger_version IS NOT NULL
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (OLD.ledger_version, 'fund_members', OLD.rowid, 'delete', json_object(
+        'id', OLD.id,
+        'fund_id', OLD.fund_id,
+        'member_address', OLD.member_address,
+        'share', OLD.share,
+        'status', OLD.status,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
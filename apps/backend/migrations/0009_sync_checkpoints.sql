@@ -0,0 +1,21 @@
+-- Progress of the state synchronizer. `ledger_version` is the version the
+-- last successful pass read its resources at; `ledger_tip` is the node's
+-- latest version when that pass started.
+CREATE TABLE sync_checkpoints (
+    name TEXT PRIMARY KEY,
+    ledger_version INTEGER NOT NULL,
+    ledger_tip INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- The synchronizer reads each fund's asset::FundWallet resource at
+-- fund_wallets.wallet_address, which used to be filled with the wallet's
+-- actuator. The actuator gets its own column. The account holding the
+-- resource was never recorded, so existing wallets are left without one
+-- until their executor registers it.
+ALTER TABLE fund_wallets ADD COLUMN actuator_address TEXT NOT NULL DEFAULT '';
+
+UPDATE fund_wallets
+SET actuator_address = wallet_address,
+    wallet_address = '';
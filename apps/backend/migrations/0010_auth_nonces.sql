@@ -0,0 +1,11 @@
+-- Single-use login challenges. A wallet signs the message built from
+-- `nonce`; the row is consumed on the first login attempt that presents it.
+CREATE TABLE auth_nonces (
+    nonce TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    expires_at DATETIME NOT NULL,
+    used_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_auth_nonces_expires_at ON auth_nonces (expires_at);
@@ -0,0 +1,53 @@
+-- Ties proposals to the fund they govern and tracks where each is in its
+-- lifecycle. Proposals created before this migration belong to no fund.
+ALTER TABLE proposals ADD COLUMN fund_id INTEGER REFERENCES funds(id);
+ALTER TABLE proposals ADD COLUMN status TEXT NOT NULL DEFAULT 'active';
+
+UPDATE proposals
+SET status = CASE
+    WHEN vetoed THEN 'vetoed'
+    WHEN executed THEN 'executed'
+    ELSE 'active'
+END;
+
+CREATE INDEX idx_proposals_fund_status ON proposals(fund_id, status);
+
+-- Proposal ids are only unique within the GovernanceData they were created
+-- in, so a chain link records that address as well as the id. Links made
+-- before this migration were matched by local id alone, including those
+-- the REST API made up. Active proposals are unlinked so their creation
+-- event relinks them by description; finished ones keep their id, and the
+-- address is filled in when their creation event is seen.
+ALTER TABLE proposals ADD COLUMN chain_address TEXT;
+
+UPDATE proposals
+SET synced = false, chain_id = 0
+WHERE synced AND status = 'active';
+
+CREATE UNIQUE INDEX idx_proposals_chain ON proposals(chain_address, chain_id) WHERE synced;
+
+-- The row image now includes the new columns
+DROP TRIGGER ledger_journal_proposals_update;
+
+CREATE TRIGGER ledger_journal_proposals_update AFTER UPDATE ON proposals
+WHEN NEW.ledger_version > COALESCE(OLD.ledger_version, -1)
+BEGIN
+    INSERT INTO ledger_journal (ledger_version, table_name, row_id, operation, previous)
+    VALUES (NEW.ledger_version, 'proposals', NEW.rowid, 'update', json_object(
+        'id', OLD.id,
+        'fund_id', OLD.fund_id,
+        'title', OLD.title,
+        'description', OLD.description,
+        'end_time', OLD.end_time,
+        'status', OLD.status,
+        'executed', OLD.executed,
+        'vetoed', OLD.vetoed,
+        'chain_id', OLD.chain_id,
+        'chain_address', OLD.chain_address,
+        'synced', OLD.synced,
+        'proposer_address', OLD.proposer_address,
+        'ledger_version', OLD.ledger_version,
+        'created_at', OLD.created_at,
+        'updated_at', OLD.updated_at
+    ));
+END;
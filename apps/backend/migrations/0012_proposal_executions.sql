@@ -0,0 +1,19 @@
+-- Transactions the backend submitted to execute passed proposals, one row
+-- per attempt. The proposal itself only becomes executed once the
+-- `ProposalExecutedEvent` is synced.
+CREATE TABLE proposal_executions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    proposal_id INTEGER NOT NULL REFERENCES proposals(id),
+    attempt INTEGER NOT NULL,
+    sender_address TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    txn_hash TEXT,
+    gas_used INTEGER,
+    vm_status TEXT,
+    error TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (proposal_id, attempt)
+);
+
+CREATE INDEX idx_proposal_executions_status ON proposal_executions (status);
@@ -0,0 +1,16 @@
+-- Transactions signed by users' wallets and relayed through the API.
+-- `function` is the entry function called, as `module::function`.
+CREATE TABLE submitted_transactions (
+    hash TEXT PRIMARY KEY,
+    sender_address TEXT NOT NULL,
+    sequence_number INTEGER NOT NULL,
+    function TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    version INTEGER,
+    gas_used INTEGER,
+    vm_status TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_submitted_transactions_sender ON submitted_transactions (sender_address);
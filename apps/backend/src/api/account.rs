@@ -1,4 +1,4 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
 use serde::{Serialize};
 use aptos_sdk::types::account_address::AccountAddress;
 use crate::{
@@ -34,84 +34,50 @@ impl<'a> AccountClient<'a> {
         Self { client }
     }
 
-    pub async fn get_balance(&self, address: AccountAddress) -> Result<u64> {
-        self.client.get_account_balance(address).await
-    }
-
-    pub async fn get_sequence_number(&self, address: AccountAddress) -> Result<u64> {
-        self.client.get_sequence_number(address).await
-    }
-}
-
-pub async fn get_balance(
+    pub async fn get_balance(
     client: web::Data<Client>,
     address: web::Path<String>,
-) -> impl Responder {
-    let address = match AccountAddress::from_hex_literal(&address) {
-        Ok(addr) => addr,
-        Err(_) => return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Invalid address format"
-        })),
-    };
+) -> Result<HttpResponse> {
+    let address = parse_address(&address)?;
+    let balance = client.get_account_balance(address).await?;
 
-    match client.get_account_balance(address).await {
-        Ok(balance) => HttpResponse::Ok().json(BalanceResponse {
-            address: format!("0x{}", address),
-            balance,
-        }),
-        Err(AppError::NotFound(_)) => HttpResponse::NotFound().json(serde_json::json!({
-            "error": "Account not found"
-        })),
-        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-            "error": e.to_string()
-        })),
-    }
+    Ok(HttpResponse::Ok().json(BalanceResponse {
+        address: format!("0x{}", address),
+        balance,
+    }))
 }
 
 pub async fn get_modules(
     client: web::Data<Client>,
     address: web::Path<String>,
-) -> impl Responder {
+) -> Result<HttpResponse> {
     if address.as_str() == "0x1" {
-        match client.get_core_account_modules().await {
-            Ok(modules) => HttpResponse::Ok().json(ModulesResponse {
-                address: "0x1".to_string(),
-                modules,
-            }),
-            Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": e.to_string()
-            })),
-        }
+        let modules = client.get_core_account_modules().await?;
+        Ok(HttpResponse::Ok().json(ModulesResponse {
+            address: "0x1".to_string(),
+            modules,
+        }))
     } else {
-        let _address = match AccountAddress::from_hex_literal(&address) {
-            Ok(addr) => addr,
-            Err(_) => return HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "Invalid address format"
-            })),
-        };
+        let _address = parse_address(&address)?;
 
         // TODO: Implement get_account_modules for non-core addresses
-        HttpResponse::NotImplemented().json(serde_json::json!({
-            "error": "Getting modules for non-core addresses is not yet implemented"
-        }))
+        Err(AppError::NotImplemented("Getting modules for non-core addresses"))
     }
 }
 
 pub async fn get_resources(
     _client: web::Data<Client>,
     address: web::Path<String>,
-) -> impl Responder {
-    let _address = match AccountAddress::from_hex_literal(&address) {
-        Ok(addr) => addr,
-        Err(_) => return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Invalid address format"
-        })),
-    };
+) -> Result<HttpResponse> {
+    let _address = parse_address(&address)?;
 
     // TODO: Implement get_account_resources
-    HttpResponse::NotImplemented().json(serde_json::json!({
-        "error": "Getting account resources is not yet implemented"
-    }))
+    Err(AppError::NotImplemented("Getting account resources"))
+}
+
+fn parse_address(address: &str) -> Result<AccountAddress> {
+    AccountAddress::from_hex_literal(address)
+        .map_err(|_| AppError::invalid_input("Invalid address format"))
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -1,34 +1,161 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 use tokio::time::{sleep, Duration};
 use crate::{
     AppState,
-    db::operations,
-    error::Result,
+    config::SyncConfig,
+    db::{
+        operations,
+        schema::{POSITION_CLOSED, POSITION_LIQUIDATED},
+        types::DbDateTime,
+    },
+    error::{AppError, Result},
+    move_events::{self, DecodedEvent, EntryCall, PositionEffect, WindfallEvent},
+    sync::ledger,
 };
-use aptos_sdk::types::account_address::AccountAddress;
-use log::{info, error};
+use aptos_sdk::{
+    rest_client::aptos_api_types::VersionedEvent,
+    types::account_address::AccountAddress,
+};
+use sqlx::SqliteConnection;
+use log::{info, error, warn};
+
+const EVENT_PAGE_SIZE: u16 = 100;
+/// `PROPOSAL_DURATION` in `governance.move`.
+const PROPOSAL_DURATION_MICROS: u64 = 86_400_000_000;
+/// Stream whose cursor counts the proposals created on chain.
+const PROPOSAL_CREATED_STREAM: &str = "governance_proposal_created_events";
+
+/// An on-chain event handle the listener follows. `name` keys the
+/// persisted cursor in `event_cursors`; the handle lives in `resource`,
+/// published by `module` at its configured address.
+struct EventStream {
+    name: &'static str,
+    module: &'static str,
+    resource: &'static str,
+    field: &'static str,
+}
+
+const STREAMS: &[EventStream] = &[
+    EventStream {
+        name: "asset_transfer_events",
+        module: "asset",
+        resource: "AssetEvents",
+        field: "transfer_events",
+    },
+    EventStream {
+        name: "asset_mint_events",
+        module: "asset",
+        resource: "AssetEvents",
+        field: "mint_events",
+    },
+    EventStream {
+        name: "asset_burn_events",
+        module: "asset",
+        resource: "AssetEvents",
+        field: "burn_events",
+    },
+    // Ahead of governance, so votes are checked against membership
+    // changes the same pass picked up
+    EventStream {
+        name: "fund_member_update_events",
+        module: "asset",
+        resource: "FundEvents",
+        field: "member_update_events",
+    },
+    EventStream {
+        name: PROPOSAL_CREATED_STREAM,
+        module: "governance",
+        resource: "GovernanceEvents",
+        field: "proposal_created_events",
+    },
+    EventStream {
+        name: "governance_vote_events",
+        module: "governance",
+        resource: "GovernanceEvents",
+        field: "vote_events",
+    },
+    EventStream {
+        name: "governance_proposal_executed_events",
+        module: "governance",
+        resource: "GovernanceEvents",
+        field: "proposal_executed_events",
+    },
+    EventStream {
+        name: "governance_emergency_veto_events",
+        module: "governance",
+        resource: "GovernanceEvents",
+        field: "emergency_veto_events",
+    },
+    EventStream {
+        name: "position_opened_events",
+        module: "position",
+        resource: "PositionEvents",
+        field: "position_opened_events",
+    },
+    EventStream {
+        name: "position_closed_events",
+        module: "position",
+        resource: "PositionEvents",
+        field: "position_closed_events",
+    },
+    EventStream {
+        name: "position_share_allocation_events",
+        module: "position",
+        resource: "PositionEvents",
+        field: "share_allocation_events",
+    },
+    EventStream {
+        name: "position_share_transfer_events",
+        module: "position",
+        resource: "PositionEvents",
+        field: "share_transfer_events",
+    },
+    EventStream {
+        name: "registry_registration_events",
+        module: "registry",
+        resource: "RegistryEvents",
+        field: "registration_events",
+    },
+    EventStream {
+        name: "registry_deactivation_events",
+        module: "registry",
+        resource: "RegistryEvents",
+        field: "deactivation_events",
+    },
+];
+
+/// Event resources the listener reads, as `(module, struct)`.
+pub fn event_resources() -> Vec<(&'static str, &'static str)> {
+    STREAMS.iter().map(|stream| (stream.module, stream.resource)).collect()
+}
+
+/// What became of an event handed to `apply_event`.
+enum Applied {
+    Done,
+    /// Depends on a proposal whose creation event is still ahead of the
+    /// listener; the stream stops here until it is indexed.
+    Deferred,
+    /// Can never be applied; kept as a dead letter with the reason.
+    Rejected(String),
+}
 
 pub struct EventListener {
     state: Arc<AppState>,
-    last_processed_sequence: u64,
+    config: SyncConfig,
 }
 
 impl EventListener {
-    pub fn new(state: Arc<AppState>) -> Self {
-        Self {
-            state,
-            last_processed_sequence: 0,
-        }
+    pub fn new(state: Arc<AppState>, config: SyncConfig) -> Self {
+        Self { state, config }
     }
 
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting event listener service...");
-        
+
         loop {
             match self.process_events().await {
                 Ok(_) => {
-                    self.last_processed_sequence = self.last_processed_sequence.saturating_add(100);
-                    sleep(Duration::from_secs(1)).await;
+                    sleep(self.config.event_interval).await;
                 }
                 Err(e) => {
                     error!("Error processing events: {}", e);
@@ -39,133 +166,420 @@ impl EventListener {
     }
 
     async fn process_events(&mut self) -> Result<()> {
-        // Process Asset events
-        self.process_asset_events().await?;
-        // Process Governance events
-        self.process_governance_events().await?;
-        // Process Registry events
-        self.process_registry_events().await?;
+        let ledger = ledger::check_ledger(&self.state, &self.config).await?;
+
+        for stream in STREAMS {
+            self.process_stream(stream, ledger.confirmed).await?;
+        }
 
         Ok(())
     }
 
-    async fn process_asset_events(&mut self) -> Result<()> {
-        let events = self.state.client.get_account_events(
-            AccountAddress::from_hex_literal("0x1")?,
-            "0x1::windfall::asset::AssetEvents",
-            "transfer_events",
-            Some(self.last_processed_sequence),
-            Some(100),
-        ).await?;
+    fn events_address(&self, stream: &EventStream) -> AccountAddress {
+        self.state.client.modules().module_address(stream.module)
+    }
 
-        for event in events {
-            if let Ok(transfer_event) = serde_json::from_value::<AssetTransferEvent>(event) {
-                // Update balances in database
-                operations::update_balances(
-                    &self.state.db,
-                    &transfer_event.symbol,
-                    &transfer_event.from,
-                    &transfer_event.to,
-                    transfer_event.amount,
-                ).await?;
-            }
-        }
+    /// Where `GovernanceData` lives; proposal ids are only unique within it.
+    fn governance_address(&self) -> String {
+        self.state.client.modules().module_address("governance").to_hex_literal()
+    }
 
-        Ok(())
+    fn event_handle(&self, stream: &EventStream) -> String {
+        self.state.client.modules().type_name(stream.module, stream.resource)
     }
 
-    async fn process_governance_events(&mut self) -> Result<()> {
-        let events = self.state.client.get_account_events(
-            AccountAddress::from_hex_literal("0x1")?,
-            "0x1::windfall::governance::GovernanceEvents",
-            "proposal_events",
-            Some(self.last_processed_sequence),
-            Some(100),
-        ).await?;
+    /// Fetches the next page of a stream starting at its persisted cursor.
+    async fn fetch_events(&self, stream: &EventStream) -> Result<Vec<VersionedEvent>> {
+        let start = operations::get_event_cursor(&self.state.db, stream.name).await?;
+
+        self.state.client.get_account_events(
+            self.events_address(stream),
+            &self.event_handle(stream),
+            stream.field,
+            Some(start),
+            Some(EVENT_PAGE_SIZE),
+        ).await
+    }
+
+    /// Records the event in the processed-events ledger. Returns false when
+    /// it was applied before, in which case the caller must skip it.
+    async fn claim_event(
+        &self,
+        conn: &mut SqliteConnection,
+        stream: &EventStream,
+        event: &VersionedEvent,
+    ) -> Result<bool> {
+        operations::record_processed_event(
+            conn,
+            &self.events_address(stream).to_hex_literal(),
+            &self.event_handle(stream),
+            stream.field,
+            event,
+        ).await
+    }
+
+    async fn process_stream(&self, stream: &EventStream, confirmed_version: u64) -> Result<()> {
+        let events = self.fetch_events(stream).await?;
 
         for event in events {
-            if let Ok(proposal_event) = serde_json::from_value::<ProposalEvent>(event) {
-                match proposal_event.event_type.as_str() {
-                    "created" => {
-                        operations::sync_proposal_creation(
-                            &self.state.db,
-                            proposal_event.proposal_id,
-                            &proposal_event.proposer,
-                        ).await?;
-                    },
-                    "executed" => {
-                        operations::sync_proposal_execution(
-                            &self.state.db,
-                            proposal_event.proposal_id,
-                        ).await?;
-                    },
-                    "vetoed" => {
-                        operations::sync_proposal_veto(
-                            &self.state.db,
-                            proposal_event.proposal_id,
+            // Later events in the stream are newer still; they are picked
+            // up once they reach the confirmation depth
+            if event.version.0 > confirmed_version {
+                break;
+            }
+
+            let sequence_number = event.sequence_number.0;
+            // The cursor moves in the same transaction as the state change,
+            // so a crash can neither skip nor re-apply this event
+            let mut tx = self.state.db.begin().await?;
+
+            // Replays and overlapping pages are skipped by the ledger check
+            let first_seen = self.claim_event(&mut tx, stream, &event).await?;
+            if first_seen {
+                let applied = match move_events::decode_event(&event) {
+                    Ok(decoded) => self.apply_event(&mut tx, &decoded).await?,
+                    Err(e) => Applied::Rejected(e.to_string()),
+                };
+
+                match applied {
+                    Applied::Done => {}
+                    // Rolled back with the claim, so the next pass retries it
+                    Applied::Deferred => {
+                        info!("Deferring {} event {} until its proposal is indexed", stream.name, sequence_number);
+                        return Ok(());
+                    }
+                    Applied::Rejected(reason) => {
+                        warn!("Dead-lettering {} event {}: {}", stream.name, sequence_number, reason);
+                        operations::record_dead_letter_event(
+                            &mut *tx,
+                            &self.events_address(stream).to_hex_literal(),
+                            &self.event_handle(stream),
+                            stream.field,
+                            &event,
+                            &reason,
                         ).await?;
-                    },
-                    _ => {}
+                    }
                 }
             }
+
+            operations::advance_event_cursor(
+                &mut *tx,
+                stream.name,
+                sequence_number + 1,
+                event.version.0,
+            ).await?;
+            tx.commit().await?;
         }
 
         Ok(())
     }
 
-    async fn process_registry_events(&mut self) -> Result<()> {
-        let events = self.state.client.get_account_events(
-            AccountAddress::from_hex_literal("0x1")?,
-            "0x1::windfall::registry::RegistryEvents",
-            "member_events",
-            Some(self.last_processed_sequence),
-            Some(100),
-        ).await?;
+    /// Fetches the entry function call of the transaction that emitted an
+    /// event, for events whose meaning depends on how they were emitted.
+    async fn entry_call(&self, decoded: &DecodedEvent) -> Result<Option<EntryCall>> {
+        let txn = self.state.client.get_transaction_by_version(decoded.version).await?;
+        Ok(move_events::entry_call(&txn))
+    }
 
-        for event in events {
-            if let Ok(member_event) = serde_json::from_value::<MemberEvent>(event) {
-                match member_event.event_type.as_str() {
-                    "added" => {
-                        operations::sync_member_addition(
-                            &self.state.db,
-                            member_event.fund_id,
-                            &member_event.member_address,
-                        ).await?;
-                    },
-                    "removed" => {
-                        operations::sync_member_removal(
-                            &self.state.db,
-                            member_event.fund_id,
-                            &member_event.member_address,
-                        ).await?;
-                    },
-                    _ => {}
+    /// Resolves an on-chain proposal id to the local proposal, or says what
+    /// to do with an event about a proposal that is not indexed.
+    async fn resolve_proposal(
+        &self,
+        conn: &mut SqliteConnection,
+        chain_id: u64,
+    ) -> Result<std::result::Result<i64, Applied>> {
+        let governance_address = self.governance_address();
+        if let Some(proposal_id) =
+            operations::get_proposal_id_by_chain_id(&mut *conn, &governance_address, chain_id).await?
+        {
+            return Ok(Ok(proposal_id));
+        }
+
+        // Proposal ids and creation event sequence numbers both count from
+        // 0, so the creation cursor tells whether it is still to come
+        let created = operations::get_event_cursor(&mut *conn, PROPOSAL_CREATED_STREAM).await?;
+        if chain_id >= created {
+            Ok(Err(Applied::Deferred))
+        } else {
+            Ok(Err(Applied::Rejected(format!("Proposal {} is not indexed", chain_id))))
+        }
+    }
+
+    async fn apply_event(&self, conn: &mut SqliteConnection, decoded: &DecodedEvent) -> Result<Applied> {
+        match &decoded.event {
+            WindfallEvent::Transfer(transfer) => {
+                apply_balance_change(
+                    conn,
+                    decoded,
+                    &transfer.symbol,
+                    &transfer.from,
+                    &transfer.to,
+                    transfer.amount,
+                ).await?;
+            }
+            // Mints and burns move balances against the zero address, which
+            // also moves the asset's total supply
+            WindfallEvent::Mint(mint) => {
+                apply_balance_change(
+                    conn,
+                    decoded,
+                    &mint.symbol,
+                    &AccountAddress::ZERO.to_hex_literal(),
+                    &mint.to,
+                    mint.amount,
+                ).await?;
+            }
+            WindfallEvent::Burn(burn) => {
+                apply_balance_change(
+                    conn,
+                    decoded,
+                    &burn.symbol,
+                    &burn.from,
+                    &AccountAddress::ZERO.to_hex_literal(),
+                    burn.amount,
+                ).await?;
+            }
+            WindfallEvent::ProposalCreated(created) => {
+                let proposer = normalize_address(&created.proposer);
+                // Both create functions take the description last
+                let call = self.entry_call(decoded).await?;
+                let description = call
+                    .as_ref()
+                    .and_then(|call| call.arguments.last()?.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                // The chain does not say which fund a proposal is for; a
+                // proposer in exactly one fund settles it
+                let fund_id = operations::get_fund_id_by_member(&mut *conn, &proposer).await?;
+                let end_time = DbDateTime::from_timestamp_micros(created.timestamp + PROPOSAL_DURATION_MICROS)
+                    .unwrap_or_else(DbDateTime::now);
+
+                operations::sync_chain_proposal(
+                    conn,
+                    &self.governance_address(),
+                    created.proposal_id,
+                    fund_id,
+                    &proposer,
+                    &description,
+                    end_time,
+                    decoded.version,
+                ).await?;
+            }
+            WindfallEvent::ProposalExecuted(executed) if executed.success => {
+                if let Err(applied) = self.resolve_proposal(conn, executed.proposal_id).await? {
+                    return Ok(applied);
                 }
+
+                operations::sync_proposal_execution(
+                    conn,
+                    &self.governance_address(),
+                    executed.proposal_id,
+                    decoded.version,
+                ).await?;
             }
+            WindfallEvent::Vote(vote) => {
+                let proposal_id = match self.resolve_proposal(conn, vote.proposal_id).await? {
+                    Ok(proposal_id) => proposal_id,
+                    Err(applied) => return Ok(applied),
+                };
+
+                // The chain admits any registered account, but only the
+                // fund's active members may vote on its proposals
+                let voter = normalize_address(&vote.voter);
+                if let Some(fund_id) = operations::get_proposal_fund_id(&mut *conn, proposal_id).await? {
+                    let members = operations::get_active_member_addresses(&mut *conn, fund_id).await?;
+                    if !members.iter().any(|member| normalize_address(member) == voter) {
+                        return Ok(Applied::Rejected(format!(
+                            "{} is not an active member of fund {}",
+                            voter, fund_id
+                        )));
+                    }
+                }
+
+                operations::sync_vote(
+                    conn,
+                    proposal_id,
+                    &voter,
+                    vote.vote,
+                    event_time(decoded),
+                    decoded.version,
+                ).await?;
+            }
+            WindfallEvent::EmergencyVeto(veto) => {
+                let proposal_id = match self.resolve_proposal(conn, veto.proposal_id).await? {
+                    Ok(proposal_id) => proposal_id,
+                    Err(applied) => return Ok(applied),
+                };
+
+                operations::sync_proposal_veto(
+                    &mut *conn,
+                    &self.governance_address(),
+                    veto.proposal_id,
+                    decoded.version,
+                ).await?;
+                operations::sync_veto_vote(
+                    conn,
+                    proposal_id,
+                    &normalize_address(&veto.initiator),
+                    event_time(decoded),
+                    decoded.version,
+                ).await?;
+            }
+            WindfallEvent::PositionOpened(opened) => {
+                // Positions belong to the fund whose executor opened them
+                let call = self.entry_call(decoded).await?;
+                let fund_id = match &call {
+                    Some(call) => operations::get_fund_id_by_executor(&mut *conn, &call.sender).await?,
+                    None => None,
+                };
+                let asset_id = operations::get_asset_id_by_symbol(&mut *conn, &opened.asset_symbol).await?;
+
+                let (Some(fund_id), Some(asset_id)) = (fund_id, asset_id) else {
+                    warn!(
+                        "Position {} at sequence {} has no matching fund or asset",
+                        opened.position_id, decoded.sequence_number
+                    );
+                    return Ok(Applied::Done);
+                };
+
+                // The event omits direction; open_position takes it last
+                let is_long = call.as_ref().and_then(|c| c.bool_argument(3)).unwrap_or(true);
+
+                operations::sync_position_opened(
+                    conn,
+                    fund_id,
+                    asset_id,
+                    opened.position_id,
+                    opened.size,
+                    opened.entry_price,
+                    is_long,
+                    event_time(decoded),
+                    decoded.version,
+                ).await?;
+            }
+            WindfallEvent::PositionClosed(closed) => {
+                let call = self.entry_call(decoded).await?;
+                let position = match move_events::position_effect(closed, call.as_ref()) {
+                    PositionEffect::Modified { size, entry_price } => {
+                        operations::sync_position_modified(
+                            conn,
+                            closed.position_id,
+                            size,
+                            entry_price,
+                            decoded.version,
+                        ).await?
+                    }
+                    effect => {
+                        let status = if effect == PositionEffect::Liquidated {
+                            POSITION_LIQUIDATED
+                        } else {
+                            POSITION_CLOSED
+                        };
+
+                        operations::sync_position_closed(
+                            conn,
+                            closed.position_id,
+                            status,
+                            closed.exit_price,
+                            closed.pnl,
+                            event_time(decoded),
+                            decoded.version,
+                        ).await?
+                    }
+                };
+
+                if position.is_none() {
+                    warn!(
+                        "Position {} at sequence {} is not indexed",
+                        closed.position_id, decoded.sequence_number
+                    );
+                }
+            }
+            WindfallEvent::ShareAllocation(allocation) => {
+                operations::sync_share_allocation(
+                    conn,
+                    allocation.position_id,
+                    &allocation.user_address,
+                    allocation.shares,
+                    event_time(decoded),
+                    decoded.version,
+                ).await?;
+            }
+            WindfallEvent::ShareTransfer(transfer) => {
+                let result = operations::sync_share_transfer(
+                    conn,
+                    transfer.position_id,
+                    &transfer.from_address,
+                    &transfer.to_address,
+                    transfer.shares,
+                    event_time(decoded),
+                    decoded.version,
+                ).await;
+
+                // Same as balance transfers: local drift must not stall the stream
+                match result {
+                    Err(e @ AppError::InsufficientShares { .. }) => {
+                        error!("Skipping share transfer at sequence {}: {}", decoded.sequence_number, e);
+                    }
+                    other => other?,
+                }
+            }
+            WindfallEvent::MemberUpdate(update) => {
+                if update.new_share == 0 {
+                    operations::sync_member_removal(
+                        conn,
+                        update.fund_id as i64,
+                        &update.member_address,
+                        decoded.version,
+                    ).await?;
+                } else {
+                    operations::sync_member_state(
+                        conn,
+                        update.fund_id as i64,
+                        &update.member_address,
+                        update.new_share,
+                        "active".to_string(),
+                        decoded.version,
+                    ).await?;
+                }
+            }
+            // There is no local user registry; the processed-events ledger
+            // keeps them for the admin API
+            WindfallEvent::Registration(_) | WindfallEvent::Deactivation(_) => {}
+            // Decoded and recorded in the ledger, but not indexed
+            _ => {}
         }
 
-        Ok(())
+        Ok(Applied::Done)
     }
 }
 
-#[derive(serde::Deserialize)]
-struct AssetTransferEvent {
-    symbol: String,
-    from: String,
-    to: String,
+/// Applies a balance change the chain already accepted. An overdraft means
+/// local drift, which the synchronizer corrects, so it is logged rather
+/// than allowed to stall the stream.
+async fn apply_balance_change(
+    conn: &mut SqliteConnection,
+    decoded: &DecodedEvent,
+    symbol: &str,
+    from: &str,
+    to: &str,
     amount: u64,
+) -> Result<()> {
+    let result = operations::update_balances(conn, symbol, from, to, amount, decoded.version).await;
+
+    match result {
+        Err(e @ AppError::InsufficientBalance { .. }) => {
+            error!("Skipping balance change at sequence {}: {}", decoded.sequence_number, e);
+            Ok(())
+        }
+        other => other,
+    }
 }
 
-#[derive(serde::Deserialize)]
-struct ProposalEvent {
-    proposal_id: u64,
-    proposer: String,
-    event_type: String,
+/// Stores chain addresses in `to_hex_literal` form, as the REST API does.
+fn normalize_address(address: &str) -> String {
+    AccountAddress::from_str(address).map_or_else(|_| address.to_string(), |a| a.to_hex_literal())
 }
 
-#[derive(serde::Deserialize)]
-struct MemberEvent {
-    fund_id: i64,
-    member_address: String,
-    event_type: String,
-} 
\ No newline at end of file
+fn event_time(decoded: &DecodedEvent) -> DbDateTime {
+    DbDateTime::from_timestamp_micros(decoded.timestamp).unwrap_or_else(DbDateTime::now)
+}
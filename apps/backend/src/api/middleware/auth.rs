@@ -1,21 +1,100 @@
 use actix_web::{
-    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    Error, HttpMessage,
+    body::EitherBody,
+    dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::AUTHORIZATION,
+    Error, FromRequest, HttpMessage, HttpRequest, ResponseError,
 };
+use aptos_sdk::types::account_address::AccountAddress;
 use futures::future::{ready, LocalBoxFuture, Ready};
-use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use log::warn;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::future::Future;
-use std::task::{Context, Poll};
-
-#[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    sub: String,
-    exp: usize,
-    iat: usize,
+use tokio::time::Duration;
+use crate::{
+    config::{AuthConfig, MIN_JWT_SECRET_LEN},
+    error::{AppError, Result},
+};
+
+/// Paths served without a token.
+const PUBLIC_PATHS: &[&str] = &[
+    "/api/v1/health",
+    "/api/v1/auth/",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// Account address the token was issued to, as a hex literal.
+    pub sub: String,
+    pub exp: usize,
+    pub iat: usize,
+}
+
+/// Signs and verifies session tokens with an HS256 secret.
+#[derive(Clone)]
+pub struct JwtKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    token_ttl: Duration,
+}
+
+impl JwtKeys {
+    pub fn new(secret: &[u8], token_ttl: Duration) -> Self {
+        Self {
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+            token_ttl,
+        }
+    }
+
+    /// Keys for the configured secret. Without one, which configuration
+    /// validation only allows on dev networks, a random secret is used, so
+    /// tokens stop working when the process restarts.
+    pub fn from_config(config: &AuthConfig) -> Self {
+        match &config.jwt_secret {
+            Some(secret) => Self::new(secret.as_bytes(), config.token_ttl),
+            None => {
+                warn!("No JWT secret configured; issued tokens will not survive a restart");
+                let secret: [u8; MIN_JWT_SECRET_LEN] = rand::thread_rng().gen();
+                Self::new(&secret, config.token_ttl)
+            }
+        }
+    }
+
+    /// Issues a token whose subject is `address`.
+    pub fn issue(&self, address: &AccountAddress) -> Result<(String, Claims)> {
+        let iat = chrono::Utc::now().timestamp() as usize;
+        let claims = Claims {
+            sub: address.to_hex_literal(),
+            exp: iat + self.token_ttl.as_secs() as usize,
+            iat,
+        };
+
+        let token = encode(&Header::new(Algorithm::HS256), &claims, &self.encoding)
+            .map_err(|e| AppError::internal(format!("Failed to sign token: {}", e)))?;
+        Ok((token, claims))
+    }
+
+    pub fn validate(&self, token: &str) -> Result<Claims> {
+        let validation = Validation::new(Algorithm::HS256);
+
+        decode::<Claims>(token, &self.decoding, &validation)
+            .map(|data| data.claims)
+            .map_err(|_| AppError::unauthorized("Invalid token"))
+    }
+}
+
+/// Requires a valid bearer token on every non-public path and stores its
+/// claims in the request extensions.
+pub struct Authentication {
+    keys: JwtKeys,
 }
 
-pub struct Authentication;
+impl Authentication {
+    pub fn new(keys: JwtKeys) -> Self {
+        Self { keys }
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for Authentication
 where
@@ -23,19 +102,23 @@ where
     S::Future: 'static,
     B: 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
     type InitError = ();
     type Transform = AuthenticationMiddleware<S>;
-    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+    type Future = Ready<std::result::Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(AuthenticationMiddleware { service }))
+        ready(Ok(AuthenticationMiddleware {
+            service,
+            keys: self.keys.clone(),
+        }))
     }
 }
 
 pub struct AuthenticationMiddleware<S> {
     service: S,
+    keys: JwtKeys,
 }
 
 impl<S, B> Service<ServiceRequest> for AuthenticationMiddleware<S>
@@ -44,62 +127,29 @@ where
     S::Future: 'static,
     B: 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
-    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Future = LocalBoxFuture<'static, std::result::Result<Self::Response, Self::Error>>;
 
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        // Skip authentication for certain paths
         if should_skip_auth(&req) {
             let fut = self.service.call(req);
-            return Box::pin(async move {
-                let res = fut.await?;
-                Ok(res)
-            });
-        }
-
-        // Get the token from the Authorization header
-        let auth_header = match req.headers().get("Authorization") {
-            Some(header) => header,
-            None => {
-                return Box::pin(async move {
-                    Err(actix_web::error::ErrorUnauthorized("Missing authorization header"))
-                })
-            }
-        };
-
-        let auth_str = match auth_header.to_str() {
-            Ok(str) => str,
-            Err(_) => {
-                return Box::pin(async move {
-                    Err(actix_web::error::ErrorUnauthorized("Invalid authorization header"))
-                })
-            }
-        };
-
-        if !auth_str.starts_with("Bearer ") {
-            return Box::pin(async move {
-                Err(actix_web::error::ErrorUnauthorized("Invalid authorization scheme"))
-            });
+            return Box::pin(async move { Ok(fut.await?.map_into_left_body()) });
         }
 
-        let token = &auth_str[7..];
-
-        // Validate JWT token
-        match validate_token(token) {
+        let claims = bearer_token(&req).and_then(|token| self.keys.validate(token));
+        match claims {
             Ok(claims) => {
-                // Add claims to request extensions
                 req.extensions_mut().insert(claims);
                 let fut = self.service.call(req);
-                Box::pin(async move {
-                    let res = fut.await?;
-                    Ok(res)
-                })
+                Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
             }
-            Err(_) => Box::pin(async move {
-                Err(actix_web::error::ErrorUnauthorized("Invalid token"))
+            // Rendered inside the future so the envelope carries the request ID
+            Err(e) => Box::pin(async move {
+                let (req, _) = req.into_parts();
+                Ok(ServiceResponse::new(req, e.error_response()).map_into_right_body())
             }),
         }
     }
@@ -107,21 +157,45 @@ where
 
 fn should_skip_auth(req: &ServiceRequest) -> bool {
     let path = req.path();
-    // List of paths that don't require authentication
-    let public_paths = vec![
-        "/api/v1/health",
-        "/api/v1/auth/login",
-        "/api/v1/auth/register",
-    ];
-    public_paths.iter().any(|&p| path.starts_with(p))
+    PUBLIC_PATHS.iter().any(|p| path.starts_with(p))
+}
+
+fn bearer_token(req: &ServiceRequest) -> Result<&str> {
+    let header = req
+        .headers()
+        .get(AUTHORIZATION)
+        .ok_or(AppError::unauthorized("Missing authorization header"))?
+        .to_str()
+        .map_err(|_| AppError::unauthorized("Invalid authorization header"))?;
+
+    header
+        .strip_prefix("Bearer ")
+        .ok_or(AppError::unauthorized("Invalid authorization scheme"))
 }
 
-fn validate_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "your-256-bit-secret".to_string());
-    let key = DecodingKey::from_secret(secret.as_bytes());
-    let mut validation = Validation::new(Algorithm::HS256);
-    validation.validate_exp = true;
+/// The account the request's token was issued to. Handlers take this
+/// instead of trusting an address from the request body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticatedAccount(pub AccountAddress);
+
+impl AuthenticatedAccount {
+    pub fn address(&self) -> AccountAddress {
+        self.0
+    }
+}
 
-    let token_data = decode::<Claims>(token, &key, &validation)?;
-    Ok(token_data.claims)
-} 
\ No newline at end of file
+impl FromRequest for AuthenticatedAccount {
+    type Error = AppError;
+    type Future = Ready<Result<Self>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let account = match req.extensions().get::<Claims>() {
+            Some(claims) => AccountAddress::from_hex_literal(&claims.sub)
+                .map(AuthenticatedAccount)
+                .map_err(|_| AppError::unauthorized("Invalid token subject")),
+            None => Err(AppError::unauthorized("Authentication required")),
+        };
+
+        ready(account)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod auth;
+pub mod request_id;
+pub mod roles;
+
+pub use auth::{Authentication, AuthenticatedAccount, JwtKeys};
+pub use request_id::RequestId;
+pub use roles::{Authorized, FundRoles, PlatformAdmin, Role};
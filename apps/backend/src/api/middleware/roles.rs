@@ -0,0 +1,194 @@
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use aptos_sdk::types::account_address::AccountAddress;
+use futures::future::LocalBoxFuture;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use std::{fmt, marker::PhantomData, str::FromStr};
+use crate::{
+    AppState,
+    api::middleware::AuthenticatedAccount,
+    config::AuthConfig,
+    db::{operations, schema::MEMBER_ACTIVE},
+    error::{AppError, Result},
+};
+
+/// What a fund-scoped route requires of the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Any of the roles below.
+    Participant,
+    /// An active member of the fund.
+    Member,
+    /// The fund's executor or its wallet's actuator.
+    Executor,
+    /// A platform administrator.
+    Admin,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Role::Participant => "participant",
+            Role::Member => "member",
+            Role::Executor => "executor",
+            Role::Admin => "admin",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The roles an account holds in one fund.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FundRoles {
+    pub member: bool,
+    pub executor: bool,
+    pub admin: bool,
+}
+
+impl FundRoles {
+    /// Admins pass executor checks too, but never act as a member: votes
+    /// and messages only come from accounts with a stake in the fund.
+    pub fn satisfies(&self, role: Role) -> bool {
+        match role {
+            Role::Participant => self.member || self.executor || self.admin,
+            Role::Member => self.member,
+            Role::Executor => self.executor || self.admin,
+            Role::Admin => self.admin,
+        }
+    }
+}
+
+/// Resolves the roles `account` holds in `fund_id`. Fails with NotFound
+/// when the fund does not exist.
+pub async fn resolve_fund_roles(
+    pool: &Pool<Sqlite>,
+    fund_id: i64,
+    account: &AccountAddress,
+    admins: &[AccountAddress],
+) -> Result<FundRoles> {
+    let fund = operations::get_fund(pool, fund_id).await?;
+    let actuator = match operations::get_fund_wallet(pool, fund_id).await {
+        Ok(wallet) => Some(wallet.actuator_address),
+        Err(AppError::NotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+    let members = operations::get_fund_members(pool, fund_id).await?;
+
+    Ok(FundRoles {
+        member: members
+            .iter()
+            .any(|m| m.status == MEMBER_ACTIVE && same_address(&m.member_address, account)),
+        executor: same_address(&fund.executor_address, account)
+            || actuator.is_some_and(|a| same_address(&a, account)),
+        admin: admins.contains(account),
+    })
+}
+
+/// Stored addresses come from clients and events in both short and long
+/// form, so they are compared parsed.
+fn same_address(stored: &str, account: &AccountAddress) -> bool {
+    AccountAddress::from_str(stored).is_ok_and(|address| address == *account)
+}
+
+/// Marker for the role a route declares through [`Authorized`].
+pub trait RequiredRole {
+    const ROLE: Role;
+}
+
+pub struct Participant;
+pub struct Member;
+pub struct Executor;
+pub struct Admin;
+
+impl RequiredRole for Participant {
+    const ROLE: Role = Role::Participant;
+}
+
+impl RequiredRole for Member {
+    const ROLE: Role = Role::Member;
+}
+
+impl RequiredRole for Executor {
+    const ROLE: Role = Role::Executor;
+}
+
+impl RequiredRole for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+/// An authenticated caller holding role `R` in the fund named by the
+/// route's `{fund_id}` segment. Taking it as a handler argument declares
+/// the role the route requires.
+pub struct Authorized<R: RequiredRole> {
+    pub account: AccountAddress,
+    pub fund_id: i64,
+    pub roles: FundRoles,
+    _role: PhantomData<R>,
+}
+
+impl<R: RequiredRole + 'static> FromRequest for Authorized<R> {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+
+        Box::pin(async move {
+            let account = AuthenticatedAccount::extract(&req).await?.address();
+            let fund_id: i64 = req
+                .match_info()
+                .get("fund_id")
+                .and_then(|id| id.parse().ok())
+                .ok_or(AppError::invalid_input("Invalid fund ID"))?;
+            let state = req
+                .app_data::<web::Data<AppState>>()
+                .ok_or_else(|| AppError::internal("Application state is not configured"))?;
+            let admins = req
+                .app_data::<web::Data<AuthConfig>>()
+                .map(|config| config.admins.clone())
+                .unwrap_or_default();
+
+            let roles = resolve_fund_roles(&state.db, fund_id, &account, &admins).await?;
+            if !roles.satisfies(R::ROLE) {
+                return Err(AppError::Forbidden(format!(
+                    "{} role required in fund {}",
+                    R::ROLE, fund_id
+                )));
+            }
+
+            Ok(Self {
+                account,
+                fund_id,
+                roles,
+                _role: PhantomData,
+            })
+        })
+    }
+}
+
+/// An authenticated platform administrator, for routes that are not
+/// scoped to a fund.
+pub struct PlatformAdmin {
+    pub account: AccountAddress,
+}
+
+impl FromRequest for PlatformAdmin {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let req = req.clone();
+
+        Box::pin(async move {
+            let account = AuthenticatedAccount::extract(&req).await?.address();
+            let admin = req
+                .app_data::<web::Data<AuthConfig>>()
+                .is_some_and(|config| config.admins.contains(&account));
+
+            if !admin {
+                return Err(AppError::Forbidden(format!("{} role required", Role::Admin)));
+            }
+            Ok(Self { account })
+        })
+    }
+}
@@ -1,9 +1,22 @@
 pub mod account;
 pub mod routes;
 pub mod events;
+pub mod middleware;
 
 use actix_web::web;
+use crate::error::AppError;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     account::configure(cfg);
+}
+
+/// Makes malformed bodies, paths and queries fail with the same JSON error
+/// envelope as handler errors.
+pub fn configure_extractors(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::JsonConfig::default()
+            .error_handler(|err, _req| AppError::InvalidInput(err.to_string()).into()))
+        .app_data(web::PathConfig::default()
+            .error_handler(|err, _req| AppError::InvalidInput(err.to_string()).into()))
+        .app_data(web::QueryConfig::default()
+            .error_handler(|err, _req| AppError::InvalidInput(err.to_string()).into()));
 } 
\ No newline at end of file
@@ -0,0 +1,71 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Deserialize;
+use log::warn;
+use crate::AppState;
+use crate::api::middleware::PlatformAdmin;
+use crate::db::{operations, schema::SyncStatus};
+use crate::sync::CHECKPOINT;
+use crate::error::Result;
+
+const MAX_EVENTS_LIMIT: i64 = 500;
+
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    pub limit: Option<i64>,
+}
+
+/// Every route here takes a [`PlatformAdmin`].
+pub fn scope() -> actix_web::Scope {
+    web::scope("/admin")
+        .service(get_processed_events)
+        .service(get_dead_letter_events)
+        .service(get_sync_status)
+}
+
+#[get("/events")]
+async fn get_processed_events(
+    state: web::Data<AppState>,
+    _admin: PlatformAdmin,
+    query: web::Query<EventsQuery>,
+) -> Result<HttpResponse> {
+    let limit = query.limit.unwrap_or(50).clamp(1, MAX_EVENTS_LIMIT);
+
+    let events = operations::get_recent_processed_events(&state.db, limit).await?;
+    Ok(HttpResponse::Ok().json(events))
+}
+
+#[get("/dead-letters")]
+async fn get_dead_letter_events(
+    state: web::Data<AppState>,
+    _admin: PlatformAdmin,
+    query: web::Query<EventsQuery>,
+) -> Result<HttpResponse> {
+    let limit = query.limit.unwrap_or(50).clamp(1, MAX_EVENTS_LIMIT);
+
+    let events = operations::get_dead_letter_events(&state.db, limit).await?;
+    Ok(HttpResponse::Ok().json(events))
+}
+
+/// The synchronizer's checkpoint and its lag behind the node's tip.
+#[get("/sync")]
+async fn get_sync_status(state: web::Data<AppState>, _admin: PlatformAdmin) -> Result<HttpResponse> {
+    let checkpoint = operations::get_sync_checkpoint(&state.db, CHECKPOINT).await?;
+
+    let ledger_tip = match state.client.get_ledger_version().await {
+        Ok(tip) => Some(tip),
+        Err(e) => {
+            warn!("Failed to get ledger version for sync status: {}", e);
+            None
+        }
+    };
+    let lag = ledger_tip.map(|tip| match &checkpoint {
+        Some(c) => tip.saturating_sub(c.ledger_version as u64),
+        None => tip,
+    });
+
+    Ok(HttpResponse::Ok().json(SyncStatus {
+        checkpoint,
+        ledger_tip,
+        lag,
+    }))
+}
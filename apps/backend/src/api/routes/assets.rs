@@ -1,10 +1,16 @@
-use actix_web::{get, post, web, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpResponse};
 use serde::{Deserialize, Serialize};
 use crate::AppState;
+use crate::api::middleware::{roles::{Admin, Executor, Participant}, Authorized};
 use crate::db::operations;
+use crate::error::{AppError, Result};
+use crate::transactions::EntryFunctionCall;
+use aptos_sdk::types::account_address::AccountAddress;
 
 #[derive(Deserialize)]
 pub struct CreateFundWalletRequest {
+    /// Account that initialized the `asset::FundWallet` resource.
+    wallet_address: String,
     actuator_address: String,
     members: Vec<MemberInput>,
 }
@@ -52,6 +58,7 @@ pub fn scope() -> actix_web::Scope {
         .service(create_fund_wallet)
         .service(get_fund_wallet)
         .service(invest)
+        .service(invest_payload)
         .service(withdraw_profits)
         .service(update_member_share)
 }
@@ -59,115 +66,122 @@ pub fn scope() -> actix_web::Scope {
 #[post("")]
 async fn create_fund_wallet(
     state: web::Data<AppState>,
-    fund_id: web::Path<i64>,
+    auth: Authorized<Executor>,
     req: web::Json<CreateFundWalletRequest>,
-) -> impl Responder {
+) -> Result<HttpResponse> {
     // Validate total shares = 10000 (100%)
     let total_shares: u64 = req.members.iter().map(|m| m.ownership_share).sum();
     if total_shares != 10000 {
-        return HttpResponse::BadRequest().body("Total ownership shares must equal 10000 (100%)");
+        return Err(AppError::invalid_input("Total ownership shares must equal 10000 (100%)"));
     }
 
     // First create the fund wallet
-    let wallet = match operations::create_fund_wallet(
+    let wallet = operations::create_fund_wallet(
         &state.db,
-        fund_id.into_inner(),
+        auth.fund_id,
+        &req.wallet_address,
         &req.actuator_address,
-    ).await {
-        Ok(w) => w,
-        Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
-    };
+    ).await?;
 
     // Then add all members with their shares
     for member in &req.members {
-        match operations::create_fund_member(
+        operations::create_fund_member(
             &state.db,
             wallet.fund_id,
             &member.address,
             member.ownership_share as i64,
-        ).await {
-            Ok(_) => (),
-            Err(e) => return HttpResponse::InternalServerError().body(format!("Failed to add member {}: {}", member.address, e)),
-        }
+        ).await?;
     }
 
-    HttpResponse::Ok().json(wallet)
+    Ok(HttpResponse::Ok().json(wallet))
 }
 
 #[get("")]
 async fn get_fund_wallet(
     state: web::Data<AppState>,
-    fund_id: web::Path<i64>,
-) -> impl Responder {
-    match operations::get_fund_wallet(&state.db, fund_id.into_inner()).await {
-        Ok(wallet) => HttpResponse::Ok().json(wallet),
-        Err(e) => HttpResponse::NotFound().body(e.to_string()),
-    }
+    auth: Authorized<Participant>,
+) -> Result<HttpResponse> {
+    let wallet = operations::get_fund_wallet(&state.db, auth.fund_id).await?;
+    Ok(HttpResponse::Ok().json(wallet))
 }
 
 #[post("/invest")]
 async fn invest(
     state: web::Data<AppState>,
-    fund_id: web::Path<i64>,
+    auth: Authorized<Executor>,
     req: web::Json<InvestmentRequest>,
-) -> impl Responder {
-    let amount: i64 = match req.amount.try_into() {
-        Ok(val) => val,
-        Err(_) => return HttpResponse::BadRequest().body("Amount too large"),
-    };
+) -> Result<HttpResponse> {
+    let amount: i64 = req.amount
+        .try_into()
+        .map_err(|_| AppError::invalid_input("Amount too large"))?;
 
-    match operations::create_investment(
+    let investment = operations::create_investment(
         &state.db,
-        fund_id.into_inner(),
+        auth.fund_id,
         req.asset_id,
         amount,
         &req.target_address,
-    ).await {
-        Ok(investment) => HttpResponse::Ok().json(investment),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+    ).await?;
+    Ok(HttpResponse::Ok().json(investment))
+}
+
+/// The investment as an unsigned `asset::invest` payload, for actuators
+/// who sign with their own wallet.
+#[post("/invest/payload")]
+async fn invest_payload(
+    state: web::Data<AppState>,
+    auth: Authorized<Executor>,
+    req: web::Json<InvestmentRequest>,
+) -> Result<HttpResponse> {
+    let wallet = operations::get_fund_wallet(&state.db, auth.fund_id).await?;
+    let fund_addr = AccountAddress::from_hex_literal(&wallet.wallet_address)
+        .map_err(|_| AppError::internal(format!("Invalid wallet address {}", wallet.wallet_address)))?;
+    let target = AccountAddress::from_hex_literal(&req.target_address)
+        .map_err(|_| AppError::invalid_input("Invalid target address"))?;
+
+    let call = EntryFunctionCall::Invest {
+        fund_addr,
+        target,
+        amount: req.amount,
+    };
+    Ok(HttpResponse::Ok().json(call.json_payload(state.client.modules())?))
 }
 
 #[post("/withdraw")]
 async fn withdraw_profits(
     state: web::Data<AppState>,
-    fund_id: web::Path<i64>,
+    auth: Authorized<Executor>,
     req: web::Json<WithdrawRequest>,
-) -> impl Responder {
-    let amount: i64 = match req.amount.try_into() {
-        Ok(val) => val,
-        Err(_) => return HttpResponse::BadRequest().body("Amount too large"),
-    };
+) -> Result<HttpResponse> {
+    let amount: i64 = req.amount
+        .try_into()
+        .map_err(|_| AppError::invalid_input("Amount too large"))?;
 
-    match operations::withdraw_investment(
+    let withdrawal = operations::withdraw_investment(
         &state.db,
-        fund_id.into_inner(),
+        auth.fund_id,
         amount,
-    ).await {
-        Ok(withdrawal) => HttpResponse::Ok().json(withdrawal),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+    ).await?;
+    Ok(HttpResponse::Ok().json(withdrawal))
 }
 
 #[post("/members/{member_address}/share")]
 async fn update_member_share(
     state: web::Data<AppState>,
+    _auth: Authorized<Admin>,
     path: web::Path<(i64, String)>,
     req: web::Json<UpdateShareRequest>,
-) -> impl Responder {
+) -> Result<HttpResponse> {
     let (fund_id, member_address) = path.into_inner();
-    let new_share: i64 = match req.new_share.try_into() {
-        Ok(val) => val,
-        Err(_) => return HttpResponse::BadRequest().body("Share value too large"),
-    };
-    
-    match operations::update_member_share(
+    let new_share: i64 = req.new_share
+        .try_into()
+        .map_err(|_| AppError::invalid_input("Share value too large"))?;
+
+    let member = operations::update_member_share(
         &state.db,
         fund_id,
         &member_address,
         new_share,
-    ).await {
-        Ok(member) => HttpResponse::Ok().json(member),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+    ).await?;
+    Ok(HttpResponse::Ok().json(member))
 } 
\ No newline at end of file
@@ -0,0 +1,152 @@
+use actix_web::{post, web, HttpResponse};
+use aptos_sdk::{
+    crypto::{
+        ed25519::{Ed25519PublicKey, Ed25519Signature},
+        Signature,
+    },
+    types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey},
+};
+use chrono::Utc;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::{
+    AppState,
+    api::middleware::JwtKeys,
+    config::AuthConfig,
+    db::{operations, types::DbDateTime},
+    error::{AppError, Result},
+};
+
+#[derive(Deserialize)]
+pub struct NonceRequest {
+    pub address: String,
+}
+
+#[derive(Serialize)]
+pub struct NonceResponse {
+    pub address: String,
+    pub nonce: String,
+    /// Text the wallet must sign to log in with this nonce.
+    pub message: String,
+    pub expires_at: DbDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub address: String,
+    pub nonce: String,
+    /// Hex-encoded Ed25519 public key.
+    pub public_key: String,
+    /// Hex-encoded Ed25519 signature.
+    pub signature: String,
+    /// The exact text signed, for wallets that wrap the message with a
+    /// prefix of their own. It must contain the issued message.
+    pub full_message: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct LoginResponse {
+    pub token: String,
+    pub address: String,
+    pub expires_at: usize,
+}
+
+pub fn scope() -> actix_web::Scope {
+    web::scope("/auth")
+        .service(request_nonce)
+        .service(login)
+}
+
+/// Message a wallet signs to prove control of `address`.
+pub fn login_message(address: &AccountAddress, nonce: &str) -> String {
+    format!(
+        "Sign in to Windfall\n\nAddress: {}\nNonce: {}",
+        address.to_hex_literal(),
+        nonce
+    )
+}
+
+#[post("/nonce")]
+async fn request_nonce(
+    state: web::Data<AppState>,
+    auth: web::Data<AuthConfig>,
+    req: web::Json<NonceRequest>,
+) -> Result<HttpResponse> {
+    let address = parse_address(&req.address)?;
+    let nonce = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+    let ttl = chrono::Duration::from_std(auth.nonce_ttl)
+        .map_err(|e| AppError::config_error(&e.to_string()))?;
+
+    let issued = operations::create_auth_nonce(
+        &state.db,
+        &address.to_hex_literal(),
+        &nonce,
+        DbDateTime::from(Utc::now() + ttl),
+    ).await?;
+
+    Ok(HttpResponse::Ok().json(NonceResponse {
+        message: login_message(&address, &issued.nonce),
+        address: issued.address,
+        nonce: issued.nonce,
+        expires_at: issued.expires_at,
+    }))
+}
+
+#[post("/login")]
+async fn login(
+    state: web::Data<AppState>,
+    keys: web::Data<JwtKeys>,
+    req: web::Json<LoginRequest>,
+) -> Result<HttpResponse> {
+    let address = parse_address(&req.address)?;
+
+    // Consumed before verification, so a nonce admits exactly one attempt
+    let nonce = operations::consume_auth_nonce(&state.db, &address.to_hex_literal(), &req.nonce)
+        .await?
+        .ok_or(AppError::unauthorized("Unknown, used or expired nonce"))?;
+
+    let message = login_message(&address, &nonce.nonce);
+    let signed = match &req.full_message {
+        Some(full_message) if full_message.contains(&message) => full_message.as_str(),
+        Some(_) => return Err(AppError::unauthorized("Signed message does not match the nonce")),
+        None => message.as_str(),
+    };
+
+    let public_key = Ed25519PublicKey::try_from(decode_hex(&req.public_key, "public key")?.as_slice())
+        .map_err(|_| AppError::invalid_input("Invalid public key"))?;
+    let signature = Ed25519Signature::try_from(decode_hex(&req.signature, "signature")?.as_slice())
+        .map_err(|_| AppError::invalid_input("Invalid signature"))?;
+
+    signature
+        .verify_arbitrary_msg(signed.as_bytes(), &public_key)
+        .map_err(|_| AppError::unauthorized("Invalid signature"))?;
+
+    // The key must be the one the account currently authorizes. Accounts
+    // not yet created on chain can only be controlled by their original key.
+    let auth_key = AuthenticationKey::ed25519(&public_key);
+    let authorized = match state.client.get_authentication_key(address).await {
+        Ok(current) => current == auth_key,
+        Err(AppError::NotFound(_)) => auth_key.account_address() == address,
+        Err(e) => return Err(e),
+    };
+    if !authorized {
+        return Err(AppError::unauthorized("Public key is not authorized for this account"));
+    }
+
+    let (token, claims) = keys.issue(&address)?;
+    Ok(HttpResponse::Ok().json(LoginResponse {
+        token,
+        address: claims.sub,
+        expires_at: claims.exp,
+    }))
+}
+
+fn parse_address(address: &str) -> Result<AccountAddress> {
+    AccountAddress::from_hex_literal(address)
+        .map_err(|_| AppError::invalid_input("Invalid account address"))
+}
+
+fn decode_hex(value: &str, what: &str) -> Result<Vec<u8>> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|_| AppError::InvalidInput(format!("Invalid {} encoding", what)))
+}
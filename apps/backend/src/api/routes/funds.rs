@@ -1,7 +1,8 @@
-use actix_web::{get, post, web, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpResponse};
 use serde::Deserialize;
 use crate::AppState;
 use crate::db::operations;
+use crate::error::Result;
 
 #[derive(Deserialize)]
 pub struct CreateFundRequest {
@@ -20,31 +21,25 @@ pub fn scope() -> actix_web::Scope {
 async fn create_fund(
     state: web::Data<AppState>,
     req: web::Json<CreateFundRequest>,
-) -> impl Responder {
-    match operations::create_fund(&state.db, req.name.clone(), req.executor_address.clone()).await {
-        Ok(fund) => HttpResponse::Ok().json(fund),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+) -> Result<HttpResponse> {
+    let fund = operations::create_fund(&state.db, req.name.clone(), req.executor_address.clone()).await?;
+    Ok(HttpResponse::Ok().json(fund))
 }
 
 #[get("/{fund_id}")]
 async fn get_fund(
     state: web::Data<AppState>,
     fund_id: web::Path<i64>,
-) -> impl Responder {
-    match operations::get_fund(&state.db, fund_id.into_inner()).await {
-        Ok(fund) => HttpResponse::Ok().json(fund),
-        Err(e) => HttpResponse::NotFound().body(e.to_string()),
-    }
+) -> Result<HttpResponse> {
+    let fund = operations::get_fund(&state.db, fund_id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(fund))
 }
 
 #[get("/{fund_id}/members")]
 async fn get_fund_members(
     state: web::Data<AppState>,
     fund_id: web::Path<i64>,
-) -> impl Responder {
-    match operations::get_fund_members(&state.db, fund_id.into_inner()).await {
-        Ok(members) => HttpResponse::Ok().json(members),
-        Err(e) => HttpResponse::NotFound().body(e.to_string()),
-    }
-} 
\ No newline at end of file
+) -> Result<HttpResponse> {
+    let members = operations::get_fund_members(&state.db, fund_id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(members))
+}
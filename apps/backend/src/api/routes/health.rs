@@ -0,0 +1,13 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use crate::AppState;
+
+pub fn scope() -> actix_web::Scope {
+    web::scope("/health")
+        .service(get_node_health)
+}
+
+/// Circuit state, latency, lag and staleness of every configured node.
+#[get("/nodes")]
+async fn get_node_health(state: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(state.client.health_checker().node_statuses().await)
+}
@@ -1,7 +1,9 @@
-use actix_web::{post, web, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpResponse};
 use serde::Deserialize;
 use crate::AppState;
+use crate::api::middleware::{roles::{Executor, Participant}, Authorized};
 use crate::db::operations;
+use crate::error::{AppError, Result};
 
 #[derive(Deserialize)]
 pub struct AddMemberRequest {
@@ -11,21 +13,32 @@ pub struct AddMemberRequest {
 pub fn scope() -> actix_web::Scope {
     web::scope("/funds/{fund_id}/members")
         .service(add_member)
+        .service(get_member_shares)
 }
 
 #[post("")]
 async fn add_member(
     state: web::Data<AppState>,
-    fund_id: web::Path<i64>,
+    auth: Authorized<Executor>,
     req: web::Json<AddMemberRequest>,
-) -> impl Responder {
-    let member = match req.member_address.parse() {
-        Ok(addr) => addr,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid member address"),
-    };
+) -> Result<HttpResponse> {
+    let member = req.member_address
+        .parse()
+        .map_err(|_| AppError::invalid_input("Invalid member address"))?;
 
-    match operations::add_fund_member(&state.db, fund_id.into_inner(), member).await {
-        Ok(member) => HttpResponse::Ok().json(member),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
-} 
\ No newline at end of file
+    let member = operations::add_fund_member(&state.db, auth.fund_id, member).await?;
+    Ok(HttpResponse::Ok().json(member))
+}
+
+/// A member's shares across the fund's positions, open and closed.
+#[get("/{member_address}/shares")]
+async fn get_member_shares(
+    state: web::Data<AppState>,
+    _auth: Authorized<Participant>,
+    path: web::Path<(i64, String)>,
+) -> Result<HttpResponse> {
+    let (fund_id, member_address) = path.into_inner();
+
+    let shares = operations::get_member_position_shares(&state.db, fund_id, &member_address).await?;
+    Ok(HttpResponse::Ok().json(shares))
+}
@@ -1,12 +1,13 @@
-use actix_web::{get, post, web, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpResponse};
 use serde::Deserialize;
 use crate::AppState;
+use crate::api::middleware::{roles::{Member, Participant}, Authorized};
 use crate::db::operations;
+use crate::error::Result;
 
 #[derive(Deserialize)]
 pub struct CreateMessageRequest {
     pub content: String,
-    pub sender_address: String,
 }
 
 #[derive(Deserialize)]
@@ -24,38 +25,29 @@ pub fn scope() -> actix_web::Scope {
 #[post("")]
 async fn create_message(
     state: web::Data<AppState>,
-    fund_id: web::Path<i64>,
+    auth: Authorized<Member>,
     req: web::Json<CreateMessageRequest>,
-) -> impl Responder {
-    let sender = match req.sender_address.parse() {
-        Ok(addr) => addr,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid sender address"),
-    };
-
-    match operations::create_message(
+) -> Result<HttpResponse> {
+    let message = operations::create_message(
         &state.db,
-        fund_id.into_inner(),
-        sender,
+        auth.fund_id,
+        auth.account.to_hex_literal(),
         req.content.clone(),
-    ).await {
-        Ok(message) => HttpResponse::Ok().json(message),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
+    ).await?;
+    Ok(HttpResponse::Ok().json(message))
 }
 
 #[get("")]
 async fn get_messages(
     state: web::Data<AppState>,
-    fund_id: web::Path<i64>,
+    auth: Authorized<Participant>,
     query: web::Query<GetMessagesQuery>,
-) -> impl Responder {
-    match operations::get_messages(
+) -> Result<HttpResponse> {
+    let messages = operations::get_messages(
         &state.db,
-        fund_id.into_inner(),
+        auth.fund_id,
         query.limit.unwrap_or(50),
         query.before_id,
-    ).await {
-        Ok(messages) => HttpResponse::Ok().json(messages),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
-    }
-} 
\ No newline at end of file
+    ).await?;
+    Ok(HttpResponse::Ok().json(messages))
+}
@@ -1,6 +1,10 @@
+pub mod admin;
+pub mod auth;
 pub mod funds;
+pub mod health;
 pub mod members;
 pub mod messages;
+pub mod positions;
 pub mod proposals;
 pub mod assets;
 pub mod transactions;
@@ -12,6 +16,10 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
        .service(members::scope())
        .service(messages::scope())
        .service(proposals::scope())
+       .service(positions::scope())
        .service(assets::scope())
-       .service(transactions::scope());
+       .service(transactions::scope())
+       .service(admin::scope())
+       .service(health::scope())
+       .service(auth::scope());
 } 
\ No newline at end of file
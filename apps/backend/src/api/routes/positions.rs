@@ -0,0 +1,53 @@
+use actix_web::{get, web, HttpResponse};
+use serde::Deserialize;
+use crate::AppState;
+use crate::db::{
+    operations,
+    schema::{POSITION_CLOSED, POSITION_LIQUIDATED, POSITION_OPEN},
+};
+use crate::error::{AppError, Result};
+
+#[derive(Deserialize)]
+pub struct PositionsQuery {
+    pub status: Option<String>,
+}
+
+pub fn scope() -> actix_web::Scope {
+    web::scope("/funds/{fund_id}/positions")
+        .service(get_positions)
+        .service(get_position)
+}
+
+/// Lists a fund's positions. Without `status` both open and historical
+/// positions are returned.
+#[get("")]
+async fn get_positions(
+    state: web::Data<AppState>,
+    fund_id: web::Path<i64>,
+    query: web::Query<PositionsQuery>,
+) -> Result<HttpResponse> {
+    let status = query.status.as_deref();
+    if let Some(status) = status {
+        if ![POSITION_OPEN, POSITION_CLOSED, POSITION_LIQUIDATED].contains(&status) {
+            return Err(AppError::InvalidInput(format!("Invalid position status: {}", status)));
+        }
+    }
+
+    let positions = operations::get_fund_positions(&state.db, fund_id.into_inner(), status).await?;
+    Ok(HttpResponse::Ok().json(positions))
+}
+
+#[get("/{position_id}")]
+async fn get_position(
+    state: web::Data<AppState>,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse> {
+    let (fund_id, position_id) = path.into_inner();
+
+    let position = operations::get_position_by_id(&state.db, position_id).await?;
+    if position.fund_id != fund_id {
+        return Err(AppError::NotFound(format!("Position {} in fund {}", position_id, fund_id)));
+    }
+
+    Ok(HttpResponse::Ok().json(position))
+}
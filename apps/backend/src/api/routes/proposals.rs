@@ -1,120 +1,281 @@
-use actix_web::{get, post, web, HttpResponse, Responder};
+use actix_web::{get, post, web, HttpResponse};
 use serde::Deserialize;
 use chrono::{DateTime, Utc};
-use log::error;
 use crate::{
     AppState,
-    db::{operations, types::DbDateTime},
+    config::GovernanceConfig,
+    api::middleware::{
+        roles::{Executor, Member, Participant},
+        Authorized,
+    },
+    db::{
+        operations,
+        schema::{Proposal, PROPOSAL_ACTIVE, PROPOSAL_STATUSES},
+        types::DbDateTime,
+    },
+    error::{AppError, Result},
+    transactions::EntryFunctionCall,
+    governance::{
+        tally::{self, Tally},
+        ProposalExecutor,
+    },
 };
 
 #[derive(Deserialize)]
 pub struct CreateProposalRequest {
     title: String,
     description: String,
-    proposer_address: String,
     end_time: DateTime<Utc>,
 }
 
 #[derive(Deserialize)]
 pub struct VoteRequest {
-    voter_address: String,
     vote_type: bool,
 }
 
 #[derive(Deserialize)]
-pub struct EmergencyVetoRequest {
-    initiator_address: String,
+pub struct ProposalsQuery {
+    pub status: Option<String>,
 }
 
 pub fn scope() -> actix_web::Scope {
     web::scope("/funds/{fund_id}/proposals")
         .service(create_proposal)
+        .service(get_proposals)
         .service(get_proposal)
         .service(vote_on_proposal)
+        .service(vote_payload)
+        .service(get_proposal_votes)
+        .service(get_proposal_tally)
         .service(emergency_veto)
+        .service(execute_proposal)
+        .service(get_proposal_executions)
 }
 
 #[post("")]
 async fn create_proposal(
     state: web::Data<AppState>,
-    _fund_id: web::Path<i64>,
+    auth: Authorized<Member>,
     req: web::Json<CreateProposalRequest>,
-) -> impl Responder {
+) -> Result<HttpResponse> {
     // First create the proposal
-    let proposal = match operations::create_proposal(
+    let proposal = operations::create_proposal(
         &state.db,
+        auth.fund_id,
         &req.title,
         &req.description,
         DbDateTime::from(req.end_time),
-    ).await {
-        Ok(p) => p,
-        Err(e) => return HttpResponse::InternalServerError().body(e.to_string()),
-    };
+    ).await?;
 
-    // Then sync it with the proposer address
-    match operations::sync_proposal_creation(
+    // It is linked to the chain once the proposer creates it there with the
+    // same description
+    let proposal = operations::set_proposal_proposer(
         &state.db,
-        proposal.id as u64,
-        &req.proposer_address,
-    ).await {
-        Ok(proposal) => HttpResponse::Ok().json(proposal),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+        proposal.id,
+        &auth.account.to_hex_literal(),
+    ).await?;
+    Ok(HttpResponse::Ok().json(proposal))
+}
+
+/// Lists a fund's proposals. Without `status` proposals in every state
+/// are returned.
+#[get("")]
+async fn get_proposals(
+    state: web::Data<AppState>,
+    auth: Authorized<Participant>,
+    query: web::Query<ProposalsQuery>,
+) -> Result<HttpResponse> {
+    let status = query.status.as_deref();
+    if let Some(status) = status {
+        if !PROPOSAL_STATUSES.contains(&status) {
+            return Err(AppError::InvalidInput(format!("Invalid proposal status: {}", status)));
+        }
     }
+
+    let proposals = operations::get_fund_proposals(&state.db, auth.fund_id, status).await?;
+    Ok(HttpResponse::Ok().json(proposals))
 }
 
 #[get("/{proposal_id}")]
 async fn get_proposal(
     state: web::Data<AppState>,
-    proposal_id: web::Path<i64>,
-) -> impl Responder {
-    match operations::get_proposal(&state.db, proposal_id.into_inner()).await {
-        Ok(proposal) => HttpResponse::Ok().json(proposal),
-        Err(e) => HttpResponse::NotFound().body(e.to_string()),
-    }
+    _auth: Authorized<Participant>,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse> {
+    let (fund_id, proposal_id) = path.into_inner();
+
+    let proposal = operations::get_fund_proposal(&state.db, fund_id, proposal_id).await?;
+    Ok(HttpResponse::Ok().json(proposal))
 }
 
 #[post("/{proposal_id}/votes")]
 async fn vote_on_proposal(
     state: web::Data<AppState>,
-    proposal_id: web::Path<i64>,
+    auth: Authorized<Member>,
+    path: web::Path<(i64, i64)>,
     req: web::Json<VoteRequest>,
-) -> impl Responder {
-    let voter = match req.voter_address.parse() {
-        Ok(addr) => addr,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid voter address"),
-    };
+) -> Result<HttpResponse> {
+    let (fund_id, proposal_id) = path.into_inner();
+
+    // Membership was checked against the path's fund, so the proposal
+    // must belong to it
+    let proposal = operations::get_fund_proposal(&state.db, fund_id, proposal_id).await?;
+    ensure_voting_open(&proposal)?;
 
-    match operations::vote_on_proposal(
+    let vote = operations::vote_on_proposal(
         &state.db,
-        proposal_id.into_inner(),
-        voter,
+        proposal_id,
+        auth.account,
         req.vote_type,
-    ).await {
-        Ok(vote) => HttpResponse::Ok().json(vote),
-        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    ).await?;
+    Ok(HttpResponse::Ok().json(vote))
+}
+
+/// The vote as an unsigned `governance::vote` payload, for members who
+/// sign with their own wallet.
+#[post("/{proposal_id}/votes/payload")]
+async fn vote_payload(
+    state: web::Data<AppState>,
+    _auth: Authorized<Member>,
+    path: web::Path<(i64, i64)>,
+    req: web::Json<VoteRequest>,
+) -> Result<HttpResponse> {
+    let (fund_id, proposal_id) = path.into_inner();
+
+    let proposal = operations::get_fund_proposal(&state.db, fund_id, proposal_id).await?;
+    ensure_voting_open(&proposal)?;
+    if !proposal.synced {
+        return Err(AppError::InvalidInput(format!(
+            "Proposal {} has not been created on chain",
+            proposal_id
+        )));
+    }
+
+    let call = EntryFunctionCall::Vote {
+        proposal_id: proposal.chain_id as u64,
+        vote: req.vote_type,
+    };
+    Ok(HttpResponse::Ok().json(call.json_payload(state.client.modules())?))
+}
+
+fn ensure_voting_open(proposal: &Proposal) -> Result<()> {
+    if proposal.status != PROPOSAL_ACTIVE {
+        return Err(AppError::InvalidInput(format!(
+            "Proposal {} is {} and no longer open for voting",
+            proposal.id, proposal.status
+        )));
+    }
+    if proposal.end_time.0 <= DbDateTime::now().0 {
+        return Err(AppError::InvalidInput(format!(
+            "Voting on proposal {} closed at {}",
+            proposal.id, proposal.end_time.into_datetime()
+        )));
     }
+
+    Ok(())
+}
+
+#[get("/{proposal_id}/votes")]
+async fn get_proposal_votes(
+    state: web::Data<AppState>,
+    _auth: Authorized<Participant>,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse> {
+    let (fund_id, proposal_id) = path.into_inner();
+    operations::get_fund_proposal(&state.db, fund_id, proposal_id).await?;
+
+    let votes = operations::get_proposal_votes(&state.db, proposal_id).await?;
+    Ok(HttpResponse::Ok().json(votes))
+}
+
+/// Share-weighted tally of the votes so far. Proposals whose voting has
+/// closed are settled first, so the status is final.
+#[get("/{proposal_id}/tally")]
+async fn get_proposal_tally(
+    state: web::Data<AppState>,
+    config: web::Data<GovernanceConfig>,
+    _auth: Authorized<Participant>,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse> {
+    let (fund_id, proposal_id) = path.into_inner();
+
+    let mut proposal = operations::get_fund_proposal(&state.db, fund_id, proposal_id).await?;
+    if let Some(settled) = tally::finalize_proposal(&state.db, &config, &proposal).await? {
+        proposal = settled;
+    }
+
+    let tally = Tally::for_proposal(&state.db, &config, &proposal).await?;
+    Ok(HttpResponse::Ok().json(tally))
 }
 
 #[post("/{proposal_id}/emergency-veto")]
 async fn emergency_veto(
     state: web::Data<AppState>,
-    proposal_id: web::Path<i64>,
-    req: web::Json<EmergencyVetoRequest>,
-) -> impl Responder {
-    // TODO: Add authorization check for initiator_address
-    // For now, just log who initiated the veto
-    log::info!("Emergency veto initiated by: {}", req.initiator_address);
-
-    match operations::emergency_veto_proposal(
+    config: web::Data<GovernanceConfig>,
+    auth: Authorized<Executor>,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse> {
+    let (fund_id, proposal_id) = path.into_inner();
+
+    let proposal = operations::get_fund_proposal(&state.db, fund_id, proposal_id).await?;
+    if proposal.status != PROPOSAL_ACTIVE {
+        return Err(AppError::Conflict(format!(
+            "Proposal {} is {} and can no longer be vetoed",
+            proposal_id, proposal.status
+        )));
+    }
+    let tally = Tally::for_proposal(&state.db, &config, &proposal).await?;
+    if !tally.emergency_veto_allowed {
+        return Err(AppError::InvalidInput(format!(
+            "Votes against proposal {} are below the veto threshold",
+            proposal_id
+        )));
+    }
+
+    log::info!(
+        "Emergency veto of proposal {} in fund {} initiated by: {}",
+        proposal_id, fund_id, auth.account.to_hex_literal()
+    );
+
+    let proposal = operations::emergency_veto_proposal(
         &state.db,
-        proposal_id.into_inner(),
+        fund_id,
+        proposal_id,
     )
-    .await
-    {
-        Ok(proposal) => HttpResponse::Ok().json(proposal),
-        Err(e) => {
-            error!("Failed to veto proposal: {}", e);
-            HttpResponse::InternalServerError().body(e.to_string())
-        }
-    }
-} 
\ No newline at end of file
+    .await?;
+    Ok(HttpResponse::Ok().json(proposal))
+}
+
+/// Executes a passed proposal now instead of waiting for the scheduler.
+/// Responds with the attempt, which records a failed transaction too.
+#[post("/{proposal_id}/execute")]
+async fn execute_proposal(
+    state: web::Data<AppState>,
+    executor: web::Data<ProposalExecutor>,
+    auth: Authorized<Executor>,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse> {
+    let (fund_id, proposal_id) = path.into_inner();
+
+    let proposal = operations::get_fund_proposal(&state.db, fund_id, proposal_id).await?;
+    log::info!(
+        "Execution of proposal {} in fund {} requested by: {}",
+        proposal_id, fund_id, auth.account.to_hex_literal()
+    );
+
+    let execution = executor.execute(&proposal).await?;
+    Ok(HttpResponse::Ok().json(execution))
+}
+
+#[get("/{proposal_id}/executions")]
+async fn get_proposal_executions(
+    state: web::Data<AppState>,
+    _auth: Authorized<Participant>,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse> {
+    let (fund_id, proposal_id) = path.into_inner();
+
+    operations::get_fund_proposal(&state.db, fund_id, proposal_id).await?;
+    let executions = operations::get_proposal_executions(&state.db, proposal_id).await?;
+    Ok(HttpResponse::Ok().json(executions))
+}
@@ -1,7 +1,16 @@
-use actix_web::{get, web, HttpResponse, Responder};
-use serde::Serialize;
+use actix_web::{get, post, web, HttpResponse};
+use serde::{Deserialize, Serialize};
 use crate::AppState;
-use aptos_sdk::rest_client::Transaction;
+use crate::api::middleware::AuthenticatedAccount;
+use crate::db::{operations, schema::{TRANSACTION_FAILED, TRANSACTION_PENDING, TRANSACTION_SUCCEEDED}};
+use crate::error::{AppError, Result};
+use crate::transactions::{EntryFunctionCall, TransactionParams};
+use aptos_sdk::{
+    bcs,
+    crypto::ed25519::Ed25519PublicKey,
+    rest_client::Transaction,
+    types::transaction::{SignedTransaction, TransactionPayload},
+};
 
 #[derive(Serialize)]
 pub struct TransactionStatus {
@@ -13,31 +22,130 @@ pub struct TransactionStatus {
     gas_used: Option<u64>,
 }
 
+#[derive(Deserialize)]
+pub struct SubmitTransactionRequest {
+    /// Hex-encoded BCS bytes of the wallet-signed transaction.
+    pub signed_transaction: String,
+}
+
+#[derive(Deserialize)]
+pub struct SimulateTransactionRequest {
+    /// Hex-encoded Ed25519 public key of the caller's wallet.
+    pub public_key: String,
+    pub payload: EntryFunctionCall,
+}
+
 pub fn scope() -> actix_web::Scope {
     web::scope("/transactions")
+        .service(simulate_transaction)
+        .service(submit_transaction)
         .service(get_transaction_status)
 }
 
+/// Dry-runs a call from the caller's account and reports its gas cost
+/// and, if it would abort, why.
+#[post("/simulate")]
+async fn simulate_transaction(
+    state: web::Data<AppState>,
+    account: AuthenticatedAccount,
+    req: web::Json<SimulateTransactionRequest>,
+) -> Result<HttpResponse> {
+    let public_key = hex::decode(req.public_key.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| Ed25519PublicKey::try_from(bytes.as_slice()).ok())
+        .ok_or_else(|| AppError::invalid_input("Invalid public key"))?;
+
+    let client = &state.client;
+    let sender = account.address();
+    let params = TransactionParams::new(
+        sender,
+        client.get_sequence_number(sender).await?,
+        client.get_chain_id().await?,
+    );
+    let raw = req.payload.raw_transaction(client.modules(), &params)?;
+
+    let simulation = client.simulate(raw, public_key).await?;
+    Ok(HttpResponse::Ok().json(simulation))
+}
+
+/// Relays a transaction signed by the caller's wallet and tracks it by
+/// hash. Transactions that would abort are rejected before submission.
+#[post("")]
+async fn submit_transaction(
+    state: web::Data<AppState>,
+    account: AuthenticatedAccount,
+    req: web::Json<SubmitTransactionRequest>,
+) -> Result<HttpResponse> {
+    let bytes = hex::decode(req.signed_transaction.trim_start_matches("0x"))
+        .map_err(|_| AppError::invalid_input("Invalid transaction encoding"))?;
+    let txn: SignedTransaction = bcs::from_bytes(&bytes)
+        .map_err(|_| AppError::invalid_input("Invalid signed transaction"))?;
+
+    // Only the caller's own transactions are relayed
+    if txn.sender() != account.address() {
+        return Err(AppError::Forbidden(format!(
+            "Transaction is sent by {}, not the authenticated account",
+            txn.sender().to_hex_literal()
+        )));
+    }
+    txn.clone()
+        .check_signature()
+        .map_err(|_| AppError::invalid_input("Invalid transaction signature"))?;
+
+    let function = match txn.payload() {
+        TransactionPayload::EntryFunction(entry) => {
+            Some(format!("{}::{}", entry.module().name(), entry.function()))
+        }
+        _ => None,
+    };
+    let sequence_number = txn.sequence_number();
+
+    let pending = state.client.submit_simulated(txn).await?;
+    let submitted = operations::create_submitted_transaction(
+        &state.db,
+        &pending.hash.to_string(),
+        &account.address().to_hex_literal(),
+        sequence_number,
+        function.as_deref(),
+    ).await?;
+    Ok(HttpResponse::Ok().json(submitted))
+}
+
 #[get("/{hash}")]
 async fn get_transaction_status(
     state: web::Data<AppState>,
     hash: web::Path<String>,
-) -> impl Responder {
-    match state.client.get_transaction_status(&hash).await {
-        Ok(txn) => {
-            let status = TransactionStatus {
-                hash: hash.to_string(),
-                status: txn.type_str().to_string(),
-                success: txn.success(),
-                version: txn.version(),
-                vm_status: Some(txn.vm_status().to_string()),
-                gas_used: match txn {
-                    Transaction::UserTransaction(t) => Some(t.info.gas_used.0),
-                    _ => None,
-                },
-            };
-            HttpResponse::Ok().json(status)
-        },
-        Err(e) => HttpResponse::NotFound().body(e.to_string()),
+) -> Result<HttpResponse> {
+    let txn = state.client.get_transaction_status(&hash).await?;
+
+    // Settle the record of a relayed transaction once it has committed
+    if let (false, Some(version)) = (txn.is_pending(), txn.version()) {
+        let tracked_hash = format!("0x{}", hash.trim_start_matches("0x").to_lowercase());
+        let tracked = operations::get_submitted_transaction(&state.db, &tracked_hash).await?;
+        if tracked.is_some_and(|tracked| tracked.status == TRANSACTION_PENDING) {
+            let status = if txn.success() { TRANSACTION_SUCCEEDED } else { TRANSACTION_FAILED };
+            let gas_used = txn.transaction_info().map(|info| info.gas_used.0).unwrap_or_default();
+            operations::complete_submitted_transaction(
+                &state.db,
+                &tracked_hash,
+                status,
+                version,
+                gas_used,
+                &txn.vm_status(),
+            ).await?;
+        }
     }
-} 
\ No newline at end of file
+
+    let status = TransactionStatus {
+        hash: hash.to_string(),
+        status: txn.type_str().to_string(),
+        success: txn.success(),
+        version: txn.version(),
+        vm_status: Some(txn.vm_status().to_string()),
+        gas_used: match txn {
+            Transaction::UserTransaction(t) => Some(t.info.gas_used.0),
+            _ => None,
+        },
+    };
+    Ok(HttpResponse::Ok().json(status))
+}
@@ -1,23 +1,37 @@
 use aptos_sdk::{
-    rest_client::{Client as AptosRestClient, PendingTransaction, Transaction},
+    rest_client::{
+        aptos_api_types::{EntryFunctionId, VersionedEvent, ViewRequest},
+        Client as AptosRestClient, PendingTransaction, Transaction,
+    },
     types::{
         account_address::AccountAddress,
         account_config::CORE_CODE_ADDRESS,
-        transaction::SignedTransaction,
+        transaction::{
+            authenticator::{AuthenticationKey, TransactionAuthenticator},
+            RawTransaction, SignedTransaction,
+        },
         chain_id::ChainId,
     },
-    crypto::HashValue,
+    crypto::{
+        ed25519::{Ed25519PublicKey, Ed25519Signature},
+        HashValue,
+    },
 };
-use std::sync::Arc;
+use std::{collections::{BTreeMap, HashMap}, str::FromStr, sync::Arc};
 use hex::FromHex;
 use crate::{
-    config::ClientConfig,
-    error::{AppError, Result},
-    utils::{RateLimiter, HealthChecker},
+    config::{ClientConfig, ModuleConfig, RetryConfig},
+    error::{is_sequence_number_status, AppError, Result},
+    transactions::Simulation,
+    utils::{RateLimiter, HealthChecker, SequenceNumbers},
 };
 use tracing::{info, warn};
 use async_trait::async_trait;
-use tokio::time::sleep;
+use rand::Rng;
+use tokio::time::{sleep, Duration};
+
+/// Longest server-requested pause honored before a retry.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
 
 #[async_trait]
 pub trait ClientInterface: Send + Sync {
@@ -26,6 +40,7 @@ pub trait ClientInterface: Send + Sync {
     async fn get_sequence_number(&self, address: AccountAddress) -> Result<u64>;
     async fn submit_transaction(&self, txn: SignedTransaction) -> Result<PendingTransaction>;
     async fn get_transaction_status(&self, txn_hash: &str) -> Result<Transaction>;
+    async fn get_transaction_by_version(&self, version: u64) -> Result<Transaction>;
     async fn wait_for_transaction(&self, pending_transaction: &PendingTransaction) -> Result<Transaction>;
     async fn get_account_events(
         &self,
@@ -34,14 +49,21 @@ pub trait ClientInterface: Send + Sync {
         field: &str,
         start: Option<u64>,
         limit: Option<u16>,
-    ) -> Result<Vec<serde_json::Value>>;
+    ) -> Result<Vec<VersionedEvent>>;
     async fn get_resource<T: serde::de::DeserializeOwned + Send>(
         &self,
         address: AccountAddress,
         resource_type: &str,
     ) -> Result<T>;
+    async fn get_resource_at_version<T: serde::de::DeserializeOwned + Send>(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: u64,
+    ) -> Result<T>;
     async fn simulate_transaction(&self, txn: &SignedTransaction) -> Result<Vec<serde_json::Value>>;
     async fn get_chain_id(&self) -> Result<ChainId>;
+    async fn get_ledger_version(&self) -> Result<u64>;
 }
 
 #[derive(Clone)]
@@ -49,12 +71,18 @@ pub struct Client {
     config: ClientConfig,
     health_checker: Arc<HealthChecker>,
     rate_limiter: Arc<RateLimiter>,
+    sequence_numbers: Arc<SequenceNumbers>,
+    /// For what the REST client does not expose, such as response headers.
+    http: reqwest::Client,
 }
 
 impl Client {
     pub async fn new(config: ClientConfig) -> Result<Self> {
         info!("Initializing Aptos client with config: {:?}", config);
-        let health_checker = Arc::new(HealthChecker::new());
+        let health_checker = Arc::new(match config.chain_id {
+            Some(chain_id) => HealthChecker::with_chain_id(chain_id),
+            None => HealthChecker::new(),
+        });
         let rate_limiter = Arc::new(RateLimiter::new(
             config.rate_limit.requests_per_second,
             config.rate_limit.burst_limit,
@@ -72,59 +100,134 @@ impl Client {
             config,
             health_checker,
             rate_limiter,
+            sequence_numbers: Arc::new(SequenceNumbers::new()),
+            http: reqwest::Client::new(),
         })
     }
 
-    async fn get_client(&self) -> Result<AptosRestClient> {
-        self.rate_limiter.acquire_permit().await?;
-        self.health_checker.get_healthy_client().await
+    /// A client without nodes, for tests that never reach the chain. Every
+    /// chain call fails with `NoHealthyNodes`.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn mock() -> Self {
+        let config = ClientConfig::default();
+        let health_checker = Arc::new(match config.chain_id {
+            Some(chain_id) => HealthChecker::with_chain_id(chain_id),
+            None => HealthChecker::new(),
+        });
+        let rate_limiter = Arc::new(RateLimiter::new(
+            config.rate_limit.requests_per_second,
+            config.rate_limit.burst_limit,
+        ));
+
+        Self {
+            config,
+            health_checker,
+            rate_limiter,
+            sequence_numbers: Arc::new(SequenceNumbers::new()),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn health_checker(&self) -> &Arc<HealthChecker> {
+        &self.health_checker
+    }
+
+    /// Sequence numbers reserved for the accounts the backend signs for.
+    pub fn sequence_numbers(&self) -> &SequenceNumbers {
+        &self.sequence_numbers
+    }
+
+    /// Where the Windfall modules are published.
+    pub fn modules(&self) -> &ModuleConfig {
+        &self.config.modules
     }
 
     async fn execute_with_retry<'a, F, Fut, T>(&'a self, operation: F) -> Result<T>
     where
-        F: Fn() -> Fut + Send + Sync + 'a,
+        F: Fn(AptosRestClient) -> Fut + Send + Sync + 'a,
+        Fut: std::future::Future<Output = Result<T>> + Send + 'a,
+        T: 'a,
+    {
+        self.execute_on_node(|_node_url, client| operation(client)).await
+    }
+
+    /// Runs `operation` against the best healthy node. Only retryable
+    /// errors are retried, each time on a node not tried yet when there is
+    /// one; terminal errors are returned straight away.
+    async fn execute_on_node<'a, F, Fut, T>(&'a self, operation: F) -> Result<T>
+    where
+        F: Fn(String, AptosRestClient) -> Fut + Send + Sync + 'a,
         Fut: std::future::Future<Output = Result<T>> + Send + 'a,
         T: 'a,
     {
+        let retry = &self.config.retry_config;
+        let mut tried = Vec::new();
         let mut attempts = 0;
-        let mut last_error = None;
-        let mut delay = self.config.retry_config.base_delay;
-
-        while attempts < self.config.retry_config.max_attempts {
-            match operation().await {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    attempts += 1;
-                    last_error = Some(e);
-
-                    if attempts < self.config.retry_config.max_attempts {
-                        sleep(delay).await;
-                        delay = std::cmp::min(
-                            delay + self.config.retry_config.base_delay,
-                            self.config.retry_config.max_delay
-                        );
-                    }
+
+        loop {
+            attempts += 1;
+            self.rate_limiter.acquire_permit().await?;
+            let (node_url, client) = self.health_checker.get_healthy_node_excluding(&tried).await?;
+
+            let error = match operation(node_url.clone(), client).await {
+                Ok(result) => {
+                    self.health_checker.record_success(&node_url).await;
+                    return Ok(result);
                 }
+                Err(e) => e,
+            };
+            let error = match error {
+                AppError::Transient { message, retry_after: None, throttled: true } => AppError::Transient {
+                    retry_after: self.fetch_retry_after(&node_url).await,
+                    message,
+                    throttled: true,
+                },
+                e => e,
+            };
+
+            if !error.is_retryable() || attempts >= retry.max_attempts {
+                return Err(error);
             }
+
+            self.health_checker.mark_unhealthy(&node_url).await;
+            tried.push(node_url.clone());
+
+            let delay = backoff_delay(retry, attempts, error.retry_after());
+            warn!("Attempt {} on {} failed, retrying in {:?}: {}", attempts, node_url, delay, error);
+            sleep(delay).await;
+        }
+    }
+
+    /// Reads `Retry-After` from the node directly, as the REST client drops
+    /// response headers. None unless the node is still throttling.
+    async fn fetch_retry_after(&self, node_url: &str) -> Option<Duration> {
+        let response = self.http
+            .get(node_url)
+            .timeout(Duration::from_secs(2))
+            .send()
+            .await
+            .ok()?;
+        if !matches!(response.status().as_u16(), 429 | 503) {
+            return None;
         }
 
-        Err(last_error.unwrap_or_else(|| AppError::Internal("Retry failed with no error".to_string())))
+        let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+        parse_retry_after(value, chrono::Utc::now())
     }
 
     pub async fn get_account_balance(&self, address: AccountAddress) -> Result<u64> {
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
+        self.execute_with_retry(|client| async move {
             let account_resource = client
                 .get_account_resource(
                     address,
                     "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
                 )
                 .await
-                .map_err(|e| AppError::internal(format!("Failed to get account resource: {}", e)))?;
+                .map_err(|e| AppError::from_rest_error("Failed to get account resource", e))?;
 
             let coin_store = account_resource
                 .into_inner()
-                .ok_or_else(|| AppError::internal("Coin store not found".to_string()))?;
+                .ok_or_else(|| AppError::NotFound("Coin store".to_string()))?;
 
             let balance = coin_store
                 .data
@@ -139,24 +242,73 @@ impl Client {
     }
 
     pub async fn get_sequence_number(&self, address: AccountAddress) -> Result<u64> {
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
+        self.execute_with_retry(|client| async move {
             let account = client
                 .get_account(address)
                 .await
-                .map_err(|e| AppError::internal(format!("Failed to get sequence number: {}", e)))?;
+                .map_err(|e| AppError::from_rest_error("Failed to get sequence number", e))?;
             
             Ok(account.into_inner().sequence_number)
         }).await
     }
 
+    /// Reserves the next sequence number of an account the backend signs
+    /// for, without asking the node unless a resync is due. The number
+    /// must be completed, released or resynced once the transaction's
+    /// fate is known.
+    pub async fn reserve_sequence_number(
+        &self,
+        address: AccountAddress,
+        expiration_timestamp_secs: u64,
+    ) -> Result<u64> {
+        self.sequence_numbers
+            .reserve(address, expiration_timestamp_secs, || self.get_sequence_number(address))
+            .await
+    }
+
+    /// Settles a reservation whose transaction failed to submit with
+    /// `error`. Rejected numbers are returned, a number the node disputed
+    /// triggers a resync, and one whose transaction may still have reached
+    /// the mempool stays reserved until it expires.
+    pub async fn release_sequence_number(&self, address: AccountAddress, sequence_number: u64, error: &AppError) {
+        match error {
+            AppError::SequenceNumber(_) => self.sequence_numbers.resync(address).await,
+            e if !e.is_retryable() => self.sequence_numbers.release(address, sequence_number).await,
+            _ => {}
+        }
+    }
+
+    /// Key currently authorized to sign for `address`. Differs from the
+    /// address itself once the account has rotated its key.
+    pub async fn get_authentication_key(&self, address: AccountAddress) -> Result<AuthenticationKey> {
+        self.execute_with_retry(|client| async move {
+            let account = client
+                .get_account(address)
+                .await
+                .map_err(|e| AppError::from_rest_error("Failed to get authentication key", e))?;
+
+            Ok(account.into_inner().authentication_key)
+        }).await
+    }
+
     pub async fn submit_transaction(&self, txn: SignedTransaction) -> Result<PendingTransaction> {
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
+        // A transaction signed for another chain can only fail or, worse,
+        // succeed somewhere unintended
+        if let Some(expected) = self.health_checker.expected_chain_id().await {
+            let actual = txn.chain_id().id();
+            if actual != expected {
+                return Err(AppError::chain_id_mismatch(expected, actual));
+            }
+        }
+
+        let txn = &txn;
+        self.execute_on_node(|node_url, client| async move {
+            self.health_checker.verify_chain(&node_url).await?;
+
             let response = client
-                .submit(&txn)
+                .submit(txn)
                 .await
-                .map_err(|e| AppError::transaction_error(&format!("Failed to submit transaction: {}", e)))?;
+                .map_err(|e| AppError::from_rest_error("Failed to submit transaction", e))?;
             Ok(response.into_inner())
         }).await
     }
@@ -173,12 +325,22 @@ impl Client {
         let hash = HashValue::from_slice(&hash_bytes)
             .map_err(|e| AppError::InvalidInput(e.to_string()))?;
 
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
+        self.execute_with_retry(|client| async move {
             let txn_resp = client
                 .get_transaction_by_hash(hash)
                 .await
-                .map_err(|e| AppError::transaction_error(&e.to_string()))?;
+                .map_err(|e| AppError::from_rest_error("Failed to get transaction", e))?;
+
+            Ok(txn_resp.into_inner())
+        }).await
+    }
+
+    pub async fn get_transaction_by_version(&self, version: u64) -> Result<Transaction> {
+        self.execute_with_retry(|client| async move {
+            let txn_resp = client
+                .get_transaction_by_version(version)
+                .await
+                .map_err(|e| AppError::from_rest_error("Failed to get transaction", e))?;
 
             Ok(txn_resp.into_inner())
         }).await
@@ -188,26 +350,22 @@ impl Client {
         &self,
         pending_transaction: &PendingTransaction,
     ) -> Result<Transaction> {
-        let pending_transaction = pending_transaction.clone();
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
+        self.execute_with_retry(|client| async move {
             let txn = client
                 .wait_for_transaction(&pending_transaction)
                 .await
-                .map_err(|e| AppError::transaction_error(&e.to_string()))?;
+                .map_err(|e| AppError::from_rest_error("Failed to get transaction", e))?;
 
             Ok(txn.into_inner())
         }).await
     }
 
     pub async fn get_core_account_modules(&self) -> Result<Vec<String>> {
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
-            
+        self.execute_with_retry(|client| async move {
             let modules = client
                 .get_account_modules(CORE_CODE_ADDRESS)
                 .await
-                .map_err(|e| AppError::internal(format!("Failed to get modules: {}", e)))?;
+                .map_err(|e| AppError::from_rest_error("Failed to get modules", e))?;
 
             let modules_inner = modules.into_inner();
             info!("Retrieved {} raw modules from core account", modules_inner.len());
@@ -230,6 +388,59 @@ impl Client {
         }).await
     }
 
+    /// Modules published under `address`, with the names of their structs.
+    pub async fn get_account_module_structs(&self, address: AccountAddress) -> Result<HashMap<String, Vec<String>>> {
+        self.execute_with_retry(|client| async move {
+            let modules = client
+                .get_account_modules(address)
+                .await
+                .map_err(|e| AppError::from_rest_error("Failed to get modules", e))?;
+
+            Ok(modules
+                .into_inner()
+                .into_iter()
+                .filter_map(|module| {
+                    let parsed = module.try_parse_abi().ok()?;
+                    let abi = parsed.abi.as_ref()?;
+                    let structs = abi.structs.iter().map(|s| s.name.to_string()).collect();
+                    Some((abi.name.to_string(), structs))
+                })
+                .collect())
+        }).await
+    }
+
+    /// Fails unless every module in `modules` and every struct in
+    /// `structs`, given as `(module, struct)`, is published at its
+    /// module's configured address.
+    pub async fn verify_modules(&self, modules: &[&str], structs: &[(&str, &str)]) -> Result<()> {
+        let mut required: BTreeMap<&str, Vec<&str>> = modules.iter().map(|m| (*m, Vec::new())).collect();
+        for (module, name) in structs {
+            required.entry(module).or_default().push(name);
+        }
+
+        for (module, names) in required {
+            let address = self.config.modules.module_address(module);
+            let published = self.get_account_module_structs(address).await?;
+
+            let Some(published_structs) = published.get(module) else {
+                return Err(AppError::config_error(&format!(
+                    "module {} is not published at {}",
+                    module,
+                    address.to_hex_literal()
+                )));
+            };
+            if let Some(missing) = names.iter().find(|name| !published_structs.iter().any(|s| s == *name)) {
+                return Err(AppError::config_error(&format!(
+                    "struct {} is not published",
+                    self.config.modules.type_name(module, missing)
+                )));
+            }
+            info!("Found module {}::{}", address.to_hex_literal(), module);
+        }
+
+        Ok(())
+    }
+
     pub async fn get_account_events(
         &self,
         address: AccountAddress,
@@ -237,15 +448,14 @@ impl Client {
         field: &str,
         start: Option<u64>,
         limit: Option<u16>,
-    ) -> Result<Vec<serde_json::Value>> {
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
+    ) -> Result<Vec<VersionedEvent>> {
+        self.execute_with_retry(|client| async move {
             let events = client
                 .get_account_events(address, event_handle, field, start, limit)
                 .await
-                .map_err(|e| AppError::internal(format!("Failed to get events: {}", e)))?;
+                .map_err(|e| AppError::from_rest_error("Failed to get events", e))?;
 
-            Ok(events.into_inner().into_iter().map(|e| e.data).collect())
+            Ok(events.into_inner())
         }).await
     }
 
@@ -254,16 +464,38 @@ impl Client {
         address: AccountAddress,
         resource_type: &str,
     ) -> Result<T> {
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
+        self.execute_with_retry(|client| async move {
             let resource = client
                 .get_account_resource(address, resource_type)
                 .await
-                .map_err(|e| AppError::internal(format!("Failed to get resource: {}", e)))?;
+                .map_err(|e| AppError::from_rest_error("Failed to get resource", e))?;
+
+            let data = resource
+                .into_inner()
+                .ok_or_else(|| AppError::NotFound(format!("Resource {}", resource_type)))?;
+
+            serde_json::from_value(data.data)
+                .map_err(|e| AppError::deserialization_error(&format!("Failed to deserialize resource: {}", e)))
+        }).await
+    }
+
+    /// Reads a resource as of `version`, so several reads see one
+    /// consistent ledger state.
+    pub async fn get_resource_at_version<T: serde::de::DeserializeOwned + Send>(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: u64,
+    ) -> Result<T> {
+        self.execute_with_retry(|client| async move {
+            let resource = client
+                .get_account_resource_at_version(address, resource_type, version)
+                .await
+                .map_err(|e| AppError::from_rest_error("Failed to get resource", e))?;
 
             let data = resource
                 .into_inner()
-                .ok_or_else(|| AppError::internal(format!("Resource {} not found", resource_type)))?;
+                .ok_or_else(|| AppError::NotFound(format!("Resource {}", resource_type)))?;
 
             serde_json::from_value(data.data)
                 .map_err(|e| AppError::deserialization_error(&format!("Failed to deserialize resource: {}", e)))
@@ -271,28 +503,130 @@ impl Client {
     }
 
     pub async fn simulate_transaction(&self, txn: &SignedTransaction) -> Result<Vec<serde_json::Value>> {
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
+        self.execute_with_retry(|client| async move {
             let response = client
                 .simulate(txn)
                 .await
-                .map_err(|e| AppError::transaction_error(&format!("Failed to simulate transaction: {}", e)))?;
+                .map_err(|e| AppError::from_rest_error("Failed to simulate transaction", e))?;
 
             Ok(response.into_inner().into_iter().map(|txn| serde_json::to_value(txn).unwrap()).collect())
         }).await
     }
 
+    /// Calls the `#[view]` function `function`, given as
+    /// `<address>::<module>::<name>`, against the state at `version`.
+    pub async fn view_at_version(
+        &self,
+        function: &str,
+        arguments: Vec<serde_json::Value>,
+        version: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let request = ViewRequest {
+            function: EntryFunctionId::from_str(function)
+                .map_err(|e| AppError::internal(format!("Invalid view function {}: {}", function, e)))?,
+            type_arguments: Vec::new(),
+            arguments,
+        };
+        let request = &request;
+        self.execute_with_retry(|client| async move {
+            let values = client
+                .view(request, Some(version))
+                .await
+                .map_err(|e| AppError::from_rest_error("Failed to call view function", e))?;
+
+            Ok(values.into_inner())
+        }).await
+    }
+
+    /// Dry-runs `raw` as if signed by `public_key`. The node refuses to
+    /// simulate validly signed transactions, so a dummy signature is used.
+    pub async fn simulate(&self, raw: RawTransaction, public_key: Ed25519PublicKey) -> Result<Simulation> {
+        let txn = SignedTransaction::new(raw, public_key, Ed25519Signature::dummy_signature());
+        let txn = &txn;
+        self.execute_with_retry(|client| async move {
+            let response = client
+                .simulate(txn)
+                .await
+                .map_err(|e| AppError::from_rest_error("Failed to simulate transaction", e))?;
+
+            response
+                .into_inner()
+                .first()
+                .map(|txn| Simulation::from_user_transaction(txn, self.modules()))
+                .ok_or_else(|| AppError::internal("Simulation returned no transaction"))
+        }).await
+    }
+
+    /// Submits `txn` only if a simulation shows it would succeed, so a
+    /// doomed transaction costs no gas. Transactions not signed by a single
+    /// Ed25519 key are submitted without simulating.
+    ///
+    /// Simulations run against committed state, so a transaction queued
+    /// behind one still in the mempool fails with SEQUENCE_NUMBER_TOO_NEW.
+    /// Sequence number failures are therefore left for the mempool to judge.
+    pub async fn submit_simulated(&self, txn: SignedTransaction) -> Result<PendingTransaction> {
+        if let TransactionAuthenticator::Ed25519 { public_key, .. } = txn.authenticator_ref() {
+            let simulation = self.simulate(txn.raw_transaction_ref().clone(), public_key.clone()).await?;
+            if !is_sequence_number_status(&simulation.vm_status) {
+                if let Some(reason) = simulation.failure_reason() {
+                    return Err(AppError::SimulationFailed(reason));
+                }
+            }
+        }
+
+        self.submit_transaction(txn).await
+    }
+
     pub async fn get_chain_id(&self) -> Result<ChainId> {
-        self.execute_with_retry(|| async {
-            let client = self.get_client().await?;
+        self.execute_with_retry(|client| async move {
             let info = client
                 .get_ledger_information()
                 .await
-                .map_err(|e| AppError::network_error(&format!("Failed to get chain ID: {}", e)))?;
+                .map_err(|e| AppError::from_rest_error("Failed to get chain ID", e))?;
 
             Ok(ChainId::new(info.into_inner().chain_id))
         }).await
     }
+
+    pub async fn get_ledger_version(&self) -> Result<u64> {
+        self.execute_with_retry(|client| async move {
+            let info = client
+                .get_ledger_information()
+                .await
+                .map_err(|e| AppError::from_rest_error("Failed to get ledger version", e))?;
+
+            Ok(info.into_inner().version)
+        }).await
+    }
+}
+
+/// Parses a `Retry-After` value, given either in seconds or as an HTTP
+/// date, clamped to `MAX_RETRY_AFTER`.
+pub fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    let delay = match value.parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => {
+            let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+            (at.with_timezone(&chrono::Utc) - now).to_std().unwrap_or(Duration::ZERO)
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+/// Exponential backoff with equal jitter: half the delay is fixed, the
+/// other half random, so clients that failed together spread out. A
+/// server-provided `Retry-After` wins, within `MAX_RETRY_AFTER`.
+pub fn backoff_delay(config: &RetryConfig, attempt: u32, retry_after: Option<Duration>) -> Duration {
+    if let Some(retry_after) = retry_after {
+        return retry_after.min(MAX_RETRY_AFTER);
+    }
+
+    let exponent = attempt.saturating_sub(1).min(16);
+    let delay = config.base_delay.saturating_mul(1 << exponent).min(config.max_delay);
+    let half = delay / 2;
+
+    half + half.mul_f64(rand::thread_rng().gen::<f64>())
 }
 
 #[async_trait]
@@ -317,6 +651,10 @@ impl ClientInterface for Client {
         self.get_transaction_status(txn_hash).await
     }
 
+    async fn get_transaction_by_version(&self, version: u64) -> Result<Transaction> {
+        self.get_transaction_by_version(version).await
+    }
+
     async fn wait_for_transaction(&self, pending_transaction: &PendingTransaction) -> Result<Transaction> {
         self.wait_for_transaction(pending_transaction).await
     }
@@ -328,7 +666,7 @@ impl ClientInterface for Client {
         field: &str,
         start: Option<u64>,
         limit: Option<u16>,
-    ) -> Result<Vec<serde_json::Value>> {
+    ) -> Result<Vec<VersionedEvent>> {
         self.get_account_events(address, event_handle, field, start, limit).await
     }
 
@@ -340,6 +678,15 @@ impl ClientInterface for Client {
         self.get_resource(address, resource_type).await
     }
 
+    async fn get_resource_at_version<T: serde::de::DeserializeOwned + Send>(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: u64,
+    ) -> Result<T> {
+        self.get_resource_at_version(address, resource_type, version).await
+    }
+
     async fn simulate_transaction(&self, txn: &SignedTransaction) -> Result<Vec<serde_json::Value>> {
         self.simulate_transaction(txn).await
     }
@@ -347,4 +694,8 @@ impl ClientInterface for Client {
     async fn get_chain_id(&self) -> Result<ChainId> {
         self.get_chain_id().await
     }
+
+    async fn get_ledger_version(&self) -> Result<u64> {
+        self.get_ledger_version().await
+    }
 } 
@@ -1,18 +1,95 @@
+use aptos_sdk::types::account_address::AccountAddress;
+use log::warn;
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Display, net::SocketAddr, path::Path, str::FromStr};
 use tokio::time::Duration;
 use url::Url;
+use crate::{
+    error::{AppError, Result},
+    transactions::{DEFAULT_GAS_UNIT_PRICE, DEFAULT_MAX_GAS_AMOUNT, DEFAULT_TRANSACTION_TTL},
+};
+
+/// Prefix of environment variables that override file settings.
+pub const ENV_PREFIX: &str = "WINDFALL_";
+
+/// Environment variable naming the TOML config file.
+pub const CONFIG_FILE_ENV: &str = "WINDFALL_CONFIG";
+
+/// Address of the `windfall` named address in `contracts/Move.toml`.
+pub const DEFAULT_MODULE_ADDRESS: &str =
+    "0x69229b793f4887833847bd71d94f0f628fab1da32473d6fec1e183da9ffafcf7";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeConfig {
     pub url: Url,
+    #[serde(with = "humantime_serde", default = "default_health_check_interval")]
     pub health_check_interval: Duration,
+    #[serde(with = "humantime_serde", default = "default_node_timeout")]
     pub timeout: Duration,
 }
 
+impl NodeConfig {
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            health_check_interval: default_health_check_interval(),
+            timeout: default_node_timeout(),
+        }
+    }
+}
+
+fn default_health_check_interval() -> Duration {
+    Duration::from_secs(30)
+}
+
+fn default_node_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+/// Aptos network a node serves, as far as its URL tells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+    Local,
+}
+
+impl Network {
+    /// Chain ID of the long-lived public networks. Devnet is reset with a
+    /// new ID and local networks choose their own.
+    pub fn chain_id(&self) -> Option<u8> {
+        match self {
+            Network::Mainnet => Some(1),
+            Network::Testnet => Some(2),
+            Network::Devnet | Network::Local => None,
+        }
+    }
+
+    /// Infers the network from the node host; `None` for hosts that do
+    /// not name one, such as private endpoints.
+    pub fn from_url(url: &Url) -> Option<Self> {
+        let host = url.host_str()?;
+        if host.contains("mainnet") {
+            Some(Network::Mainnet)
+        } else if host.contains("testnet") {
+            Some(Network::Testnet)
+        } else if host.contains("devnet") {
+            Some(Network::Devnet)
+        } else if host == "localhost" || host == "127.0.0.1" {
+            Some(Network::Local)
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetryConfig {
     pub max_attempts: u32,
+    #[serde(with = "humantime_serde")]
     pub base_delay: Duration,
+    #[serde(with = "humantime_serde")]
     pub max_delay: Duration,
 }
 
@@ -22,27 +99,119 @@ pub struct RateLimitConfig {
     pub burst_limit: u32,
 }
 
+/// Where the Windfall Move modules are published.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleConfig {
+    /// Publisher of the Windfall package.
+    pub address: AccountAddress,
+    /// Modules published somewhere else, keyed by module name.
+    #[serde(default)]
+    pub overrides: HashMap<String, AccountAddress>,
+}
+
+impl ModuleConfig {
+    pub fn module_address(&self, module: &str) -> AccountAddress {
+        self.overrides.get(module).copied().unwrap_or(self.address)
+    }
+
+    /// Fully qualified Move type of a struct, e.g. `0x1::asset::AssetEvents`.
+    pub fn type_name(&self, module: &str, name: &str) -> String {
+        format!("{}::{}::{}", self.module_address(module).to_hex_literal(), module, name)
+    }
+}
+
+impl Default for ModuleConfig {
+    fn default() -> Self {
+        Self {
+            address: AccountAddress::from_hex_literal(DEFAULT_MODULE_ADDRESS).unwrap(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClientConfig {
     pub primary_node: NodeConfig,
     pub fallback_nodes: Vec<NodeConfig>,
     pub retry_config: RetryConfig,
     pub rate_limit: RateLimitConfig,
+    pub modules: ModuleConfig,
+    /// Chain every node must serve. When unset, the chain reported by the
+    /// primary node at startup is pinned.
+    #[serde(default)]
+    pub chain_id: Option<u8>,
+}
+
+impl ClientConfig {
+    pub fn nodes(&self) -> impl Iterator<Item = &NodeConfig> {
+        std::iter::once(&self.primary_node).chain(&self.fallback_nodes)
+    }
+
+    /// Whether the primary node serves devnet or a local network, whose
+    /// state is thrown away.
+    pub fn is_dev_network(&self) -> bool {
+        matches!(
+            Network::from_url(&self.primary_node.url),
+            Some(Network::Devnet | Network::Local)
+        )
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        // A fallback on another network would serve a different ledger
+        let primary_network = Network::from_url(&self.primary_node.url);
+        for node in &self.fallback_nodes {
+            let network = Network::from_url(&node.url);
+            if let (Some(primary), Some(fallback)) = (primary_network, network) {
+                if primary != fallback {
+                    return Err(AppError::config_error(&format!(
+                        "fallback node {} is on {:?} but the primary node is on {:?}",
+                        node.url, fallback, primary
+                    )));
+                }
+            }
+        }
+
+        if let (Some(expected), Some(network)) = (self.chain_id, primary_network) {
+            if network.chain_id().is_some_and(|id| id != expected) {
+                return Err(AppError::config_error(&format!(
+                    "chain_id {} does not match the {:?} primary node",
+                    expected, network
+                )));
+            }
+        }
+
+        for node in self.nodes() {
+            if node.timeout.is_zero() || node.health_check_interval.is_zero() {
+                return Err(AppError::config_error(&format!(
+                    "node {} must have a non-zero timeout and health check interval",
+                    node.url
+                )));
+            }
+        }
+
+        if self.retry_config.max_attempts == 0 {
+            return Err(AppError::config_error("retry_config.max_attempts must be at least 1"));
+        }
+        if self.retry_config.base_delay > self.retry_config.max_delay {
+            return Err(AppError::config_error("retry_config.base_delay exceeds max_delay"));
+        }
+
+        if self.rate_limit.requests_per_second == 0 {
+            return Err(AppError::config_error("rate_limit.requests_per_second must be at least 1"));
+        }
+        if self.rate_limit.burst_limit < self.rate_limit.requests_per_second {
+            return Err(AppError::config_error("rate_limit.burst_limit is below requests_per_second"));
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for ClientConfig {
     fn default() -> Self {
         Self {
-            primary_node: NodeConfig {
-                url: Url::parse("https://fullnode.mainnet.aptoslabs.com").unwrap(),
-                health_check_interval: Duration::from_secs(30),
-                timeout: Duration::from_secs(10),
-            },
-            fallback_nodes: vec![NodeConfig {
-                url: Url::parse("https://fullnode.testnet.aptoslabs.com").unwrap(),
-                health_check_interval: Duration::from_secs(30),
-                timeout: Duration::from_secs(10),
-            }],
+            primary_node: NodeConfig::new(Url::parse("https://fullnode.mainnet.aptoslabs.com").unwrap()),
+            fallback_nodes: vec![NodeConfig::new(Url::parse("https://api.mainnet.aptoslabs.com").unwrap())],
             retry_config: RetryConfig {
                 max_attempts: 3,
                 base_delay: Duration::from_millis(500),
@@ -52,6 +221,428 @@ impl Default for ClientConfig {
                 requests_per_second: 50,
                 burst_limit: 100,
             },
+            modules: ModuleConfig::default(),
+            chain_id: Network::Mainnet.chain_id(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Versions a change must sit below the ledger tip before it is applied.
+    pub confirmation_depth: u64,
+    /// Versions below the tip for which applied changes stay revertible.
+    pub rollback_window: u64,
+    /// Lag, in versions, above which the synchronizer switches to catch-up mode.
+    pub catch_up_threshold: u64,
+    /// Pause between synchronizer passes while catching up.
+    #[serde(with = "humantime_serde")]
+    pub catch_up_interval: Duration,
+    /// Pause between synchronizer passes once caught up.
+    #[serde(with = "humantime_serde")]
+    pub sync_interval: Duration,
+    /// Pause between event listener polls.
+    #[serde(with = "humantime_serde")]
+    pub event_interval: Duration,
+}
+
+impl SyncConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.confirmation_depth >= self.rollback_window {
+            return Err(AppError::config_error(
+                "sync.confirmation_depth must be below sync.rollback_window",
+            ));
+        }
+        if self.sync_interval.is_zero() || self.event_interval.is_zero() || self.catch_up_interval.is_zero() {
+            return Err(AppError::config_error("sync intervals must be non-zero"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            confirmation_depth: 1_000,
+            rollback_window: 1_000_000,
+            catch_up_threshold: 100_000,
+            catch_up_interval: Duration::from_secs(1),
+            sync_interval: Duration::from_secs(10),
+            event_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Vote thresholds, mirroring `GovernanceConfig` in `governance.move`.
+/// Thresholds are percentages of the share weight cast on a proposal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernanceConfig {
+    /// Yes weight must exceed this share of the cast weight to pass.
+    pub quorum_threshold: u64,
+    /// No weight at or above this share of the cast weight allows an
+    /// emergency veto.
+    pub veto_threshold: u64,
+    /// Pause between passes that finalize proposals whose voting closed.
+    #[serde(with = "humantime_serde")]
+    pub tally_interval: Duration,
+}
+
+impl GovernanceConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (name, threshold) in [
+            ("quorum_threshold", self.quorum_threshold),
+            ("veto_threshold", self.veto_threshold),
+        ] {
+            if !(1..=100).contains(&threshold) {
+                return Err(AppError::config_error(&format!(
+                    "governance.{} must be between 1 and 100",
+                    name
+                )));
+            }
+        }
+        if self.tally_interval.is_zero() {
+            return Err(AppError::config_error("governance.tally_interval must be non-zero"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for GovernanceConfig {
+    fn default() -> Self {
+        Self {
+            quorum_threshold: 51,
+            veto_threshold: 30,
+            tally_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Length of an Ed25519 private key, in bytes.
+pub const PRIVATE_KEY_LEN: usize = 32;
+
+/// Drives `governance::execute_proposal` for proposals that passed.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExecutionConfig {
+    /// Hex-encoded Ed25519 key of the account that signs executions.
+    /// Never rendered by `--print-config`; execution is disabled without it.
+    #[serde(default, skip_serializing)]
+    pub private_key: Option<String>,
+    /// Attempts per proposal before the scheduler gives up on it. Manual
+    /// executions are not limited.
+    pub max_attempts: u32,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    /// How long a submitted transaction stays valid.
+    #[serde(with = "humantime_serde")]
+    pub transaction_ttl: Duration,
+    /// Pause between passes that execute passed proposals.
+    #[serde(with = "humantime_serde")]
+    pub execute_interval: Duration,
+}
+
+impl ExecutionConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(key) = &self.private_key {
+            let valid = hex::decode(key.trim_start_matches("0x"))
+                .is_ok_and(|bytes| bytes.len() == PRIVATE_KEY_LEN);
+            if !valid {
+                return Err(AppError::config_error(&format!(
+                    "execution.private_key must be {} hex-encoded bytes",
+                    PRIVATE_KEY_LEN
+                )));
+            }
+        }
+        if self.max_attempts == 0 {
+            return Err(AppError::config_error("execution.max_attempts must be at least 1"));
+        }
+        if self.max_gas_amount == 0 || self.gas_unit_price == 0 {
+            return Err(AppError::config_error("execution gas settings must be non-zero"));
+        }
+        if self.transaction_ttl.is_zero() || self.execute_interval.is_zero() {
+            return Err(AppError::config_error(
+                "execution.transaction_ttl and execution.execute_interval must be non-zero",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            private_key: None,
+            max_attempts: 3,
+            max_gas_amount: DEFAULT_MAX_GAS_AMOUNT,
+            gas_unit_price: DEFAULT_GAS_UNIT_PRICE,
+            transaction_ttl: DEFAULT_TRANSACTION_TTL,
+            execute_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl std::fmt::Debug for ExecutionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecutionConfig")
+            .field("private_key", &self.private_key.as_ref().map(|_| "<redacted>"))
+            .field("max_attempts", &self.max_attempts)
+            .field("max_gas_amount", &self.max_gas_amount)
+            .field("gas_unit_price", &self.gas_unit_price)
+            .field("transaction_ttl", &self.transaction_ttl)
+            .field("execute_interval", &self.execute_interval)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerConfig {
+    pub bind_address: SocketAddr,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: SocketAddr::from(([127, 0, 0, 1], 8080)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseConfig {
+    pub max_connections: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self { max_connections: 5 }
+    }
+}
+
+/// Shortest accepted JWT signing secret, in bytes.
+pub const MIN_JWT_SECRET_LEN: usize = 32;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// HMAC secret for session tokens. Never rendered by `--print-config`.
+    /// Required unless the primary node is on devnet or a local network,
+    /// where a random secret is generated at startup instead.
+    #[serde(default, skip_serializing)]
+    pub jwt_secret: Option<String>,
+    #[serde(with = "humantime_serde")]
+    pub token_ttl: Duration,
+    #[serde(with = "humantime_serde")]
+    pub nonce_ttl: Duration,
+    /// Platform administrators, who pass executor and admin checks on
+    /// every fund.
+    #[serde(default)]
+    pub admins: Vec<AccountAddress>,
+}
+
+impl AuthConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(secret) = &self.jwt_secret {
+            if secret.len() < MIN_JWT_SECRET_LEN {
+                return Err(AppError::config_error(&format!(
+                    "auth.jwt_secret must be at least {} bytes",
+                    MIN_JWT_SECRET_LEN
+                )));
+            }
         }
+        if self.token_ttl.is_zero() || self.nonce_ttl.is_zero() {
+            return Err(AppError::config_error("auth.token_ttl and auth.nonce_ttl must be non-zero"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            jwt_secret: None,
+            token_ttl: Duration::from_secs(24 * 60 * 60),
+            nonce_ttl: Duration::from_secs(5 * 60),
+            admins: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthConfig")
+            .field("jwt_secret", &self.jwt_secret.as_ref().map(|_| "<redacted>"))
+            .field("token_ttl", &self.token_ttl)
+            .field("nonce_ttl", &self.nonce_ttl)
+            .field("admins", &self.admins)
+            .finish()
+    }
+}
+
+/// Everything the backend binary is configured with. Settings are layered:
+/// built-in defaults, then the TOML file, then `WINDFALL_*` variables.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub client: ClientConfig,
+    pub sync: SyncConfig,
+    pub governance: GovernanceConfig,
+    pub execution: ExecutionConfig,
+}
+
+impl AppConfig {
+    /// Loads the layered configuration. Without an explicit `path` the file
+    /// named by `WINDFALL_CONFIG` is used, if any.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let env_path = std::env::var(CONFIG_FILE_ENV).ok();
+        let path = path.or(env_path.as_deref().map(Path::new));
+
+        let mut config = match path {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path).map_err(|e| {
+                    AppError::config_error(&format!("cannot read {}: {}", path.display(), e))
+                })?;
+                Self::from_toml(&contents)?
+            }
+            None => Self::default(),
+        };
+
+        config.apply_env(std::env::vars())?;
+        Ok(config)
+    }
+
+    /// Parses a TOML document over the defaults. Tables merge key by key;
+    /// arrays such as `fallback_nodes` replace the default list.
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        let overlay: toml::Value = toml::from_str(contents)
+            .map_err(|e| AppError::config_error(&e.to_string()))?;
+        let mut merged = toml::Value::try_from(Self::default())
+            .map_err(|e| AppError::config_error(&e.to_string()))?;
+
+        merge_toml(&mut merged, overlay);
+
+        merged.try_into().map_err(|e: toml::de::Error| AppError::config_error(&e.to_string()))
     }
-} 
\ No newline at end of file
+
+    /// Applies `WINDFALL_*` overrides from `vars`.
+    pub fn apply_env<I>(&mut self, vars: I) -> Result<()>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        for (key, value) in vars {
+            let Some(name) = key.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+
+            match name {
+                "CONFIG" => {}
+                "BIND_ADDRESS" => self.server.bind_address = parse_env(&key, &value)?,
+                "DB_MAX_CONNECTIONS" => self.database.max_connections = parse_env(&key, &value)?,
+                "JWT_SECRET" => self.auth.jwt_secret = Some(value),
+                "TOKEN_TTL" => self.auth.token_ttl = parse_duration(&key, &value)?,
+                "NONCE_TTL" => self.auth.nonce_ttl = parse_duration(&key, &value)?,
+                "ADMINS" => {
+                    self.auth.admins = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|address| !address.is_empty())
+                        .map(|address| {
+                            AccountAddress::from_hex_literal(address)
+                                .map_err(|e| AppError::config_error(&format!("{}: {}", key, e)))
+                        })
+                        .collect::<Result<_>>()?;
+                }
+                "PRIMARY_NODE" => {
+                    self.client.primary_node.url = parse_env(&key, &value)?;
+                }
+                "FALLBACK_NODES" => {
+                    self.client.fallback_nodes = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|url| !url.is_empty())
+                        .map(|url| parse_env(&key, url).map(NodeConfig::new))
+                        .collect::<Result<_>>()?;
+                }
+                "RETRY_MAX_ATTEMPTS" => self.client.retry_config.max_attempts = parse_env(&key, &value)?,
+                "RETRY_BASE_DELAY" => self.client.retry_config.base_delay = parse_duration(&key, &value)?,
+                "RETRY_MAX_DELAY" => self.client.retry_config.max_delay = parse_duration(&key, &value)?,
+                "RATE_LIMIT_RPS" => self.client.rate_limit.requests_per_second = parse_env(&key, &value)?,
+                "RATE_LIMIT_BURST" => self.client.rate_limit.burst_limit = parse_env(&key, &value)?,
+                "MODULE_ADDRESS" => {
+                    self.client.modules.address = AccountAddress::from_hex_literal(&value)
+                        .map_err(|e| AppError::config_error(&format!("{}: {}", key, e)))?;
+                }
+                "CHAIN_ID" => self.client.chain_id = Some(parse_env(&key, &value)?),
+                "SYNC_INTERVAL" => self.sync.sync_interval = parse_duration(&key, &value)?,
+                "EVENT_INTERVAL" => self.sync.event_interval = parse_duration(&key, &value)?,
+                "CONFIRMATION_DEPTH" => self.sync.confirmation_depth = parse_env(&key, &value)?,
+                "QUORUM_THRESHOLD" => self.governance.quorum_threshold = parse_env(&key, &value)?,
+                "VETO_THRESHOLD" => self.governance.veto_threshold = parse_env(&key, &value)?,
+                "EXECUTOR_PRIVATE_KEY" => self.execution.private_key = Some(value),
+                "EXECUTION_MAX_ATTEMPTS" => self.execution.max_attempts = parse_env(&key, &value)?,
+                _ => warn!("Ignoring unknown configuration variable {}", key),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.database.max_connections == 0 {
+            return Err(AppError::config_error("database.max_connections must be at least 1"));
+        }
+
+        self.auth.validate()?;
+        // A per-process secret logs everyone out on restart and differs
+        // between replicas, so only throwaway networks may go without one
+        if self.auth.jwt_secret.is_none() && !self.client.is_dev_network() {
+            return Err(AppError::config_error(
+                "auth.jwt_secret is required unless the primary node is on devnet or a local network",
+            ));
+        }
+        self.client.validate()?;
+        self.sync.validate()?;
+        self.governance.validate()?;
+        self.execution.validate()
+    }
+
+    /// Renders the effective configuration for `--print-config`.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| AppError::config_error(&e.to_string()))
+    }
+}
+
+fn merge_toml(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn parse_env<T>(key: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|e| AppError::config_error(&format!("{}: {}", key, e)))
+}
+
+fn parse_duration(key: &str, value: &str) -> Result<Duration> {
+    humantime::parse_duration(value.trim())
+        .map_err(|e| AppError::config_error(&format!("{}: {}", key, e)))
+}
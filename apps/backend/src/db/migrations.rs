@@ -0,0 +1,187 @@
+use sqlx::{Executor, FromRow, Pool, Sqlite};
+use aptos_sdk::crypto::HashValue;
+use log::info;
+use crate::error::{AppError, Result};
+
+/// A single forward-only schema migration. Versions must be strictly
+/// increasing and are never reused once released.
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        HashValue::sha3_256_of(self.sql.as_bytes()).to_hex()
+    }
+}
+
+/// All migrations known to this binary, in application order.
+/// New migrations are appended here and to `migrations/`.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        sql: include_str!("../../migrations/0001_initial_schema.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "event_cursors",
+        sql: include_str!("../../migrations/0002_event_cursors.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "processed_events",
+        sql: include_str!("../../migrations/0003_processed_events.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "dead_letter_events",
+        sql: include_str!("../../migrations/0004_dead_letter_events.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "chain_votes",
+        sql: include_str!("../../migrations/0005_chain_votes.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "position_lifecycle",
+        sql: include_str!("../../migrations/0006_position_lifecycle.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "position_shares",
+        sql: include_str!("../../migrations/0007_position_shares.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "ledger_versions",
+        sql: include_str!("../../migrations/0008_ledger_versions.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "sync_checkpoints",
+        sql: include_str!("../../migrations/0009_sync_checkpoints.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "auth_nonces",
+        sql: include_str!("../../migrations/0010_auth_nonces.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "proposal_funds",
+        sql: include_str!("../../migrations/0011_proposal_funds.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "proposal_executions",
+        sql: include_str!("../../migrations/0012_proposal_executions.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "submitted_transactions",
+        sql: include_str!("../../migrations/0013_submitted_transactions.sql"),
+    },
+];
+
+#[derive(Debug, FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+}
+
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<()> {
+    run(pool, MIGRATIONS).await
+}
+
+pub async fn run(pool: &Pool<Sqlite>, migrations: &[Migration]) -> Result<()> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    let applied = applied_migrations(pool).await?;
+    let latest = migrations.last().map_or(0, |m| m.version);
+
+    // Refuse to touch a database that a newer binary has already upgraded
+    if let Some(newest) = applied.last() {
+        if newest.version > latest {
+            return Err(AppError::migration_error(&format!(
+                "database schema version {} is newer than this binary supports ({})",
+                newest.version, latest
+            )));
+        }
+    }
+
+    for migration in migrations {
+        match applied.iter().find(|a| a.version == migration.version) {
+            Some(existing) => {
+                if existing.checksum != migration.checksum() {
+                    return Err(AppError::migration_error(&format!(
+                        "checksum mismatch for applied migration {} ({})",
+                        migration.version, migration.name
+                    )));
+                }
+            }
+            None => apply(pool, migration).await?,
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn applied_migrations(pool: &Pool<Sqlite>) -> Result<Vec<AppliedMigration>> {
+    Ok(sqlx::query_as::<_, AppliedMigration>(
+        r#"
+        SELECT version, name, checksum
+        FROM schema_migrations
+        ORDER BY version
+        "#,
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+async fn apply(pool: &Pool<Sqlite>, migration: &Migration) -> Result<()> {
+    info!("Applying migration {} ({})", migration.version, migration.name);
+
+    let mut tx = pool.begin().await?;
+
+    (&mut *tx).execute(migration.sql)
+        .await
+        .map_err(|e| AppError::migration_error(&format!(
+            "migration {} ({}) failed: {}",
+            migration.version, migration.name, e
+        )))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO schema_migrations (version, name, checksum)
+        VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(migration.version)
+    .bind(migration.name)
+    .bind(migration.checksum())
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
-use crate::error::Result;
+use crate::{config::DatabaseConfig, error::Result};
 
+pub mod migrations;
 pub mod operations;
 pub mod schema;
 pub mod types;
@@ -12,13 +13,13 @@ pub use operations::*;
 
 pub type Pool = SqlitePool;
 
-pub async fn create_pool() -> Result<Pool> {
+pub async fn create_pool(config: &DatabaseConfig) -> Result<Pool> {
     let database_url = std::env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set");
     
     // Configure the connection pool
     let pool = SqlitePoolOptions::new()
-        .max_connections(5)
+        .max_connections(config.max_connections)
         .connect(&database_url)
         .await?;
     
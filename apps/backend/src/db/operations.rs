@@ -1,11 +1,16 @@
-use sqlx::{Pool, Sqlite};
+use sqlx::{Connection, Executor, Pool, Sqlite, SqliteConnection};
 use sqlx::FromRow;
 use anyhow::Context;
-use aptos_sdk::types::account_address::AccountAddress;
+use aptos_sdk::{
+    rest_client::aptos_api_types::VersionedEvent,
+    types::account_address::AccountAddress,
+};
 use crate::db::types::DbDateTime;
 use crate::error::{AppError, Result};
 use crate::db::schema::*;
 use crate::sync::HolderInfo;
+use crate::move_events::EVENT_SCHEMA_VERSION;
+use std::str::FromStr;
 
 async fn get_by_id<T>(pool: &Pool<Sqlite>, table: &str, id: i64) -> Result<T>
 where
@@ -57,12 +62,86 @@ pub async fn create_fund(
     .await
     .map_err(|e| match e {
         sqlx::Error::Database(e) if e.message().contains("UNIQUE constraint failed") => {
-            AppError::InvalidInput(format!("Fund with name {} already exists", name))
+            AppError::AlreadyExists(format!("Fund with name {}", name))
         }
         e => AppError::Database(e)
     })
 }
 
+/// Resolves the fund whose executor signed a transaction, which is how
+/// positions opened by a fund's actuator are attributed to it.
+pub async fn get_fund_id_by_executor<'e, E>(
+    executor: E,
+    executor_address: &str,
+) -> Result<Option<i64>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    Ok(sqlx::query_scalar!(
+        r#"
+        SELECT id as "id!"
+        FROM funds
+        WHERE executor_address = ?
+        ORDER BY id
+        LIMIT 1
+        "#,
+        executor_address
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to look up fund by executor")?)
+}
+
+/// The fund `member_address` actively belongs to, when it belongs to
+/// exactly one.
+pub async fn get_fund_id_by_member<'e, E>(
+    executor: E,
+    member_address: &str,
+) -> Result<Option<i64>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let fund_ids = sqlx::query_scalar!(
+        r#"
+        SELECT fund_id as "fund_id!"
+        FROM fund_members
+        WHERE member_address = ? AND status = 'active'
+        "#,
+        member_address
+    )
+    .fetch_all(executor)
+    .await
+    .context("Failed to look up fund by member")?;
+
+    Ok(match fund_ids.as_slice() {
+        [fund_id] => Some(*fund_id),
+        _ => None,
+    })
+}
+
+/// Addresses of a fund's active members, in whatever form they were stored.
+pub async fn get_active_member_addresses<'e, E>(
+    executor: E,
+    fund_id: i64,
+) -> Result<Vec<String>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let addresses = sqlx::query_scalar!(
+        r#"
+        SELECT member_address as "member_address!"
+        FROM fund_members
+        WHERE fund_id = ? AND status = 'active'
+        "#,
+        fund_id
+    )
+    .fetch_all(executor)
+    .await
+    .context("Failed to get active member addresses")?;
+
+    Ok(addresses)
+}
+
 pub async fn get_fund(pool: &Pool<Sqlite>, fund_id: i64) -> Result<Fund> {
     get_by_id::<Fund>(pool, "funds", fund_id).await
 }
@@ -221,6 +300,11 @@ pub async fn create_position(
             size as "size!", 
             entry_price as "entry_price!", 
             is_long as "is_long!", 
+            chain_position_id,
+            status as "status!",
+            exit_price,
+            pnl,
+            closed_at as "closed_at: DbDateTime",
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
@@ -243,9 +327,383 @@ pub async fn get_position_by_id(pool: &Pool<Sqlite>, position_id: i64) -> Result
     get_by_id::<Position>(pool, "positions", position_id).await
 }
 
+/// Lists a fund's positions, newest first, optionally filtered by status.
+pub async fn get_fund_positions(
+    pool: &Pool<Sqlite>,
+    fund_id: i64,
+    status: Option<&str>,
+) -> Result<Vec<Position>> {
+    let positions = sqlx::query_as!(
+        Position,
+        r#"
+        SELECT 
+            id as "id!", 
+            fund_id as "fund_id!", 
+            asset_id as "asset_id!", 
+            size as "size!", 
+            entry_price as "entry_price!", 
+            is_long as "is_long!", 
+            chain_position_id,
+            status as "status!",
+            exit_price,
+            pnl,
+            closed_at as "closed_at: DbDateTime",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM positions
+        WHERE fund_id = ? AND (? IS NULL OR status = ?)
+        ORDER BY created_at DESC, id DESC
+        "#,
+        fund_id,
+        status,
+        status
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get fund positions")?;
+
+    Ok(positions)
+}
+
+/// Records a position opened on chain. Replays of the same open event
+/// leave the existing row untouched.
+#[allow(clippy::too_many_arguments)]
+pub async fn sync_position_opened<'e, E>(
+    executor: E,
+    fund_id: i64,
+    asset_id: i64,
+    chain_position_id: u64,
+    size: u64,
+    entry_price: u64,
+    is_long: bool,
+    opened_at: DbDateTime,
+    ledger_version: u64,
+) -> Result<Position>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let chain_position_id = chain_position_id as i64;
+    let size = size as i64;
+    let entry_price = entry_price as i64;
+    let ledger_version = ledger_version as i64;
+    let now = DbDateTime::now();
+
+    let position = sqlx::query_as!(
+        Position,
+        r#"
+        INSERT INTO positions (fund_id, asset_id, size, entry_price, is_long, chain_position_id, status, ledger_version, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, 'open', ?, ?, ?)
+        ON CONFLICT (chain_position_id) DO UPDATE
+        SET updated_at = positions.updated_at
+        RETURNING 
+            id as "id!", 
+            fund_id as "fund_id!", 
+            asset_id as "asset_id!", 
+            size as "size!", 
+            entry_price as "entry_price!", 
+            is_long as "is_long!", 
+            chain_position_id,
+            status as "status!",
+            exit_price,
+            pnl,
+            closed_at as "closed_at: DbDateTime",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        fund_id,
+        asset_id,
+        size,
+        entry_price,
+        is_long,
+        chain_position_id,
+        ledger_version,
+        opened_at,
+        now
+    )
+    .fetch_one(executor)
+    .await
+    .context("Failed to sync position opening")?;
+
+    Ok(position)
+}
+
+/// Applies `modify_position`: the position stays open with a new entry
+/// price and, when known, a new size. Returns None for positions that were
+/// never indexed or are no longer open.
+pub async fn sync_position_modified<'e, E>(
+    executor: E,
+    chain_position_id: u64,
+    size: Option<u64>,
+    entry_price: u64,
+    ledger_version: u64,
+) -> Result<Option<Position>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let chain_position_id = chain_position_id as i64;
+    let size = size.map(|s| s as i64);
+    let entry_price = entry_price as i64;
+    let ledger_version = ledger_version as i64;
+    let now = DbDateTime::now();
+
+    let position = sqlx::query_as!(
+        Position,
+        r#"
+        UPDATE positions
+        SET size = COALESCE(?, size), entry_price = ?, ledger_version = ?, updated_at = ?
+        WHERE chain_position_id = ? AND status = 'open'
+        RETURNING 
+            id as "id!", 
+            fund_id as "fund_id!", 
+            asset_id as "asset_id!", 
+            size as "size!", 
+            entry_price as "entry_price!", 
+            is_long as "is_long!", 
+            chain_position_id,
+            status as "status!",
+            exit_price,
+            pnl,
+            closed_at as "closed_at: DbDateTime",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        size,
+        entry_price,
+        ledger_version,
+        now,
+        chain_position_id
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to sync position modification")?;
+
+    Ok(position)
+}
+
+/// Marks a position closed or liquidated with its exit price and realized
+/// pnl. Returns None for positions that were never indexed.
+pub async fn sync_position_closed<'e, E>(
+    executor: E,
+    chain_position_id: u64,
+    status: &str,
+    exit_price: u64,
+    pnl: u64,
+    closed_at: DbDateTime,
+    ledger_version: u64,
+) -> Result<Option<Position>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let chain_position_id = chain_position_id as i64;
+    let exit_price = exit_price as i64;
+    let pnl = pnl as i64;
+    let ledger_version = ledger_version as i64;
+    let now = DbDateTime::now();
+
+    let position = sqlx::query_as!(
+        Position,
+        r#"
+        UPDATE positions
+        SET status = ?, exit_price = ?, pnl = ?, closed_at = ?, ledger_version = ?, updated_at = ?
+        WHERE chain_position_id = ?
+        RETURNING 
+            id as "id!", 
+            fund_id as "fund_id!", 
+            asset_id as "asset_id!", 
+            size as "size!", 
+            entry_price as "entry_price!", 
+            is_long as "is_long!", 
+            chain_position_id,
+            status as "status!",
+            exit_price,
+            pnl,
+            closed_at as "closed_at: DbDateTime",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        status,
+        exit_price,
+        pnl,
+        closed_at,
+        ledger_version,
+        now,
+        chain_position_id
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to sync position close")?;
+
+    Ok(position)
+}
+
+/// Records a share allocation. `allocate_shares` refuses to allocate twice
+/// to the same holder on chain, so an existing row is simply overwritten.
+pub async fn sync_share_allocation<'e, E>(
+    executor: E,
+    chain_position_id: u64,
+    holder_address: &str,
+    shares: u64,
+    allocated_at: DbDateTime,
+    ledger_version: u64,
+) -> Result<PositionShare>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let chain_position_id = chain_position_id as i64;
+    let shares = shares as i64;
+    let ledger_version = ledger_version as i64;
+    let now = DbDateTime::now();
+
+    let share = sqlx::query_as!(
+        PositionShare,
+        r#"
+        INSERT INTO position_shares (chain_position_id, holder_address, shares, entry_timestamp, last_updated, ledger_version, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (chain_position_id, holder_address) DO UPDATE
+        SET shares = excluded.shares,
+            entry_timestamp = excluded.entry_timestamp,
+            last_updated = excluded.last_updated,
+            ledger_version = excluded.ledger_version,
+            updated_at = excluded.updated_at
+        RETURNING 
+            id as "id!", 
+            chain_position_id as "chain_position_id!", 
+            holder_address as "holder_address!", 
+            shares as "shares!", 
+            entry_timestamp as "entry_timestamp!: DbDateTime", 
+            last_updated as "last_updated!: DbDateTime", 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        chain_position_id,
+        holder_address,
+        shares,
+        allocated_at,
+        allocated_at,
+        ledger_version,
+        now,
+        now
+    )
+    .fetch_one(executor)
+    .await
+    .context("Failed to sync share allocation")?;
+
+    Ok(share)
+}
+
+/// Moves shares between holders like `position::transfer_shares`: the
+/// sender must cover the amount, and a receiver without shares in the
+/// position gets a new entry timestamp.
+pub async fn sync_share_transfer(
+    conn: &mut SqliteConnection,
+    chain_position_id: u64,
+    from_address: &str,
+    to_address: &str,
+    shares: u64,
+    transferred_at: DbDateTime,
+    ledger_version: u64,
+) -> Result<()> {
+    let position_id = chain_position_id as i64;
+    let shares_i64 = i64::try_from(shares)
+        .map_err(|_| AppError::InvalidInput(format!("Share amount {} too large", shares)))?;
+    let now = DbDateTime::now();
+    let ledger_version = ledger_version as i64;
+
+    // Runs as a savepoint when the caller already holds a transaction
+    let mut tx = conn.begin().await?;
+
+    let debited = sqlx::query!(
+        r#"
+        UPDATE position_shares
+        SET shares = shares - ?, last_updated = ?, ledger_version = ?, updated_at = ?
+        WHERE chain_position_id = ? AND holder_address = ? AND shares >= ?
+        "#,
+        shares_i64,
+        transferred_at,
+        ledger_version,
+        now,
+        position_id,
+        from_address,
+        shares_i64
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update sender shares")?;
+
+    if debited.rows_affected() == 0 {
+        return Err(AppError::InsufficientShares {
+            holder: from_address.to_string(),
+            position_id: chain_position_id,
+            requested: shares,
+        });
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO position_shares (chain_position_id, holder_address, shares, entry_timestamp, last_updated, ledger_version, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (chain_position_id, holder_address) DO UPDATE
+        SET shares = shares + excluded.shares,
+            last_updated = excluded.last_updated,
+            ledger_version = excluded.ledger_version,
+            updated_at = excluded.updated_at
+        "#,
+        position_id,
+        to_address,
+        shares_i64,
+        transferred_at,
+        transferred_at,
+        ledger_version,
+        now,
+        now
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update receiver shares")?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Lists a member's non-empty shares across a fund's indexed positions.
+pub async fn get_member_position_shares(
+    pool: &Pool<Sqlite>,
+    fund_id: i64,
+    member_address: &str,
+) -> Result<Vec<MemberPositionShare>> {
+    let shares = sqlx::query_as!(
+        MemberPositionShare,
+        r#"
+        SELECT 
+            p.id as "position_id!", 
+            s.chain_position_id as "chain_position_id!", 
+            p.asset_id as "asset_id!", 
+            p.status as "status!", 
+            p.size as "size!", 
+            p.entry_price as "entry_price!", 
+            p.is_long as "is_long!", 
+            s.shares as "shares!", 
+            s.entry_timestamp as "entry_timestamp!: DbDateTime", 
+            s.last_updated as "last_updated!: DbDateTime"
+        FROM position_shares s
+        JOIN positions p ON p.chain_position_id = s.chain_position_id
+        WHERE p.fund_id = ? AND s.holder_address = ? AND s.shares > 0
+        ORDER BY s.entry_timestamp DESC
+        "#,
+        fund_id,
+        member_address
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get member position shares")?;
+
+    Ok(shares)
+}
+
 // Proposal operations
 pub async fn create_proposal(
     pool: &Pool<Sqlite>,
+    fund_id: i64,
     title: &str,
     description: &str,
     end_time: DbDateTime,
@@ -255,24 +713,28 @@ pub async fn create_proposal(
         Proposal,
         r#"
         INSERT INTO proposals (
-            title, description, end_time, 
+            fund_id, title, description, end_time, status,
             executed, vetoed, chain_id, synced,
             proposer_address, created_at, updated_at
         )
-        VALUES (?, ?, ?, false, false, 0, false, NULL, ?, ?)
+        VALUES (?, ?, ?, ?, 'active', false, false, 0, false, NULL, ?, ?)
         RETURNING 
             id as "id!", 
+            fund_id, 
             title as "title!", 
             description as "description!", 
             end_time as "end_time!", 
+            status as "status!", 
             executed as "executed!", 
             vetoed as "vetoed!", 
             chain_id as "chain_id!", 
+            chain_address,
             synced as "synced!",
             proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
+        fund_id,
         title,
         description,
         end_time,
@@ -294,12 +756,15 @@ pub async fn get_proposal_by_id(
         r#"
         SELECT 
             id as "id!", 
+            fund_id, 
             title as "title!", 
             description as "description!", 
             end_time as "end_time!", 
+            status as "status!", 
             executed as "executed!", 
             vetoed as "vetoed!", 
             chain_id as "chain_id!", 
+            chain_address,
             synced as "synced!",
             proposer_address,
             created_at as "created_at!", 
@@ -319,760 +784,978 @@ pub async fn get_proposal(pool: &Pool<Sqlite>, proposal_id: i64) -> Result<Propo
     get_by_id::<Proposal>(pool, "proposals", proposal_id).await
 }
 
-pub async fn vote_on_proposal(
+/// Fetches a proposal through the fund it belongs to. Proposals of other
+/// funds are reported as missing.
+pub async fn get_fund_proposal(
     pool: &Pool<Sqlite>,
+    fund_id: i64,
     proposal_id: i64,
-    voter_address: AccountAddress,
-    vote_type: bool,
-) -> Result<Vote> {
-    let now = DbDateTime::now();
-    let voter_str = voter_address.to_string();
-    
-    let vote = sqlx::query_as!(
-        Vote,
+) -> Result<Proposal> {
+    let proposal = get_proposal(pool, proposal_id).await?;
+    if proposal.fund_id != Some(fund_id) {
+        return Err(AppError::NotFound(format!("Proposal {} in fund {}", proposal_id, fund_id)));
+    }
+
+    Ok(proposal)
+}
+
+/// Lists a fund's proposals, newest first, optionally filtered by status.
+pub async fn get_fund_proposals(
+    pool: &Pool<Sqlite>,
+    fund_id: i64,
+    status: Option<&str>,
+) -> Result<Vec<Proposal>> {
+    let proposals = sqlx::query_as!(
+        Proposal,
         r#"
-        INSERT INTO votes (proposal_id, voter_address, vote_type, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?)
-        RETURNING 
+        SELECT 
             id as "id!", 
-            proposal_id as "proposal_id!", 
-            voter_address as "voter_address!", 
-            vote_type as "vote_type!", 
+            fund_id, 
+            title as "title!", 
+            description as "description!", 
+            end_time as "end_time!", 
+            status as "status!", 
+            executed as "executed!", 
+            vetoed as "vetoed!", 
+            chain_id as "chain_id!", 
+            chain_address,
+            synced as "synced!",
+            proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
+        FROM proposals
+        WHERE fund_id = ? AND (? IS NULL OR status = ?)
+        ORDER BY created_at DESC, id DESC
         "#,
-        proposal_id,
-        voter_str,
-        vote_type,
-        now,
-        now
+        fund_id,
+        status,
+        status
     )
-    .fetch_one(pool)
+    .fetch_all(pool)
     .await
-    .context("Failed to create vote")?;
+    .context("Failed to get fund proposals")?;
 
-    Ok(vote)
+    Ok(proposals)
 }
 
-pub async fn emergency_veto_proposal(
+/// Active proposals whose voting closed at or before `now`, oldest first.
+pub async fn get_ended_active_proposals(
     pool: &Pool<Sqlite>,
-    proposal_id: i64,
-) -> Result<Proposal> {
-    let now = DbDateTime::now();
-    let proposal = sqlx::query_as!(
+    now: DbDateTime,
+) -> Result<Vec<Proposal>> {
+    let proposals = sqlx::query_as!(
         Proposal,
         r#"
-        UPDATE proposals 
-        SET vetoed = true, updated_at = ?
-        WHERE id = ?
-        RETURNING 
+        SELECT 
             id as "id!", 
+            fund_id, 
             title as "title!", 
             description as "description!", 
             end_time as "end_time!", 
+            status as "status!", 
             executed as "executed!", 
             vetoed as "vetoed!", 
             chain_id as "chain_id!", 
+            chain_address,
             synced as "synced!",
             proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
+        FROM proposals
+        WHERE status = 'active' AND end_time <= ?
+        ORDER BY end_time, id
         "#,
-        now,
-        proposal_id
+        now
     )
-    .fetch_one(pool)
+    .fetch_all(pool)
     .await
-    .context("Failed to veto proposal")?;
-    Ok(proposal)
+    .context("Failed to get ended proposals")?;
+
+    Ok(proposals)
 }
 
-pub async fn sync_proposal_creation(
+/// Moves an active proposal to its final `status`. Returns None when the
+/// proposal has left the active state in the meantime.
+pub async fn finalize_proposal(
     pool: &Pool<Sqlite>,
-    proposal_id: u64,
-    proposer: &str,
-) -> Result<Proposal> {
+    proposal_id: i64,
+    status: &str,
+) -> Result<Option<Proposal>> {
     let now = DbDateTime::now();
-    let chain_id = proposal_id as i64;
-    
+
     let proposal = sqlx::query_as!(
         Proposal,
         r#"
-        UPDATE proposals 
-        SET chain_id = ?, proposer_address = ?, synced = true, updated_at = ?
-        WHERE id = ?
+        UPDATE proposals
+        SET status = ?, updated_at = ?
+        WHERE id = ? AND status = 'active'
         RETURNING 
             id as "id!", 
+            fund_id, 
             title as "title!", 
             description as "description!", 
             end_time as "end_time!", 
+            status as "status!", 
             executed as "executed!", 
             vetoed as "vetoed!", 
             chain_id as "chain_id!", 
+            chain_address,
             synced as "synced!",
             proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        chain_id,
-        proposer,
+        status,
         now,
-        chain_id
+        proposal_id
     )
-    .fetch_one(pool)
+    .fetch_optional(pool)
     .await
-    .context("Failed to sync proposal creation")?;
+    .context("Failed to finalize proposal")?;
 
     Ok(proposal)
 }
 
-pub async fn sync_proposal_execution(
+pub async fn vote_on_proposal(
     pool: &Pool<Sqlite>,
-    proposal_id: u64,
-) -> Result<Proposal> {
+    proposal_id: i64,
+    voter_address: AccountAddress,
+    vote_type: bool,
+) -> Result<Vote> {
     let now = DbDateTime::now();
-    let id = proposal_id as i64;
+    // Same form as votes indexed from the chain, so a voter has one row
+    let voter_str = voter_address.to_hex_literal();
     
+    let vote = sqlx::query_as!(
+        Vote,
+        r#"
+        INSERT INTO votes (proposal_id, voter_address, vote_type, voted_at, is_veto, on_chain, created_at, updated_at)
+        VALUES (?, ?, ?, ?, false, false, ?, ?)
+        RETURNING 
+            id as "id!", 
+            proposal_id as "proposal_id!", 
+            voter_address as "voter_address!", 
+            vote_type as "vote_type!", 
+            voted_at as "voted_at: DbDateTime",
+            is_veto as "is_veto!",
+            on_chain as "on_chain!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        proposal_id,
+        voter_str,
+        vote_type,
+        now,
+        now,
+        now
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to create vote")?;
+
+    Ok(vote)
+}
+
+/// Vetoes an active proposal of `fund_id`. Fails with a conflict if it has
+/// already been vetoed or is no longer active.
+pub async fn emergency_veto_proposal(
+    pool: &Pool<Sqlite>,
+    fund_id: i64,
+    proposal_id: i64,
+) -> Result<Proposal> {
+    let now = DbDateTime::now();
     let proposal = sqlx::query_as!(
         Proposal,
         r#"
         UPDATE proposals 
-        SET executed = true, updated_at = ?
-        WHERE id = ?
+        SET vetoed = true, status = 'vetoed', updated_at = ?
+        WHERE id = ? AND fund_id = ? AND status = 'active' AND NOT vetoed
         RETURNING 
             id as "id!", 
+            fund_id, 
             title as "title!", 
             description as "description!", 
             end_time as "end_time!", 
+            status as "status!", 
             executed as "executed!", 
             vetoed as "vetoed!", 
             chain_id as "chain_id!", 
+            chain_address,
             synced as "synced!",
             proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
         now,
-        id
+        proposal_id,
+        fund_id
     )
-    .fetch_one(pool)
+    .fetch_optional(pool)
     .await
-    .context("Failed to sync proposal execution")?;
+    .context("Failed to veto proposal")?;
 
-    Ok(proposal)
+    proposal.ok_or_else(|| AppError::Conflict(format!("Proposal {} is no longer active", proposal_id)))
 }
 
-pub async fn sync_proposal_veto(
+/// Records who proposed a proposal created through the REST API. It stays
+/// unsynced until its creation is seen on chain.
+pub async fn set_proposal_proposer(
     pool: &Pool<Sqlite>,
-    proposal_id: u64,
+    proposal_id: i64,
+    proposer: &str,
 ) -> Result<Proposal> {
     let now = DbDateTime::now();
-    let id = proposal_id as i64;
-    
+
     let proposal = sqlx::query_as!(
         Proposal,
         r#"
-        UPDATE proposals 
-        SET vetoed = true, updated_at = ?
+        UPDATE proposals
+        SET proposer_address = ?, updated_at = ?
         WHERE id = ?
         RETURNING 
             id as "id!", 
+            fund_id, 
             title as "title!", 
             description as "description!", 
             end_time as "end_time!", 
+            status as "status!", 
             executed as "executed!", 
             vetoed as "vetoed!", 
             chain_id as "chain_id!", 
+            chain_address,
             synced as "synced!",
             proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
+        proposer,
         now,
-        id
+        proposal_id
     )
     .fetch_one(pool)
     .await
-    .context("Failed to sync proposal veto")?;
+    .context("Failed to set proposal proposer")?;
 
     Ok(proposal)
 }
 
-// Asset operations
-pub async fn create_asset(
-    pool: &Pool<Sqlite>,
-    symbol: String,
-    name: String,
-    decimals: i32,
-) -> Result<Asset> {
+/// Indexes a proposal created on chain. The proposal already linked to
+/// `chain_id` in the `GovernanceData` at `chain_address` is refreshed, as
+/// is one linked by id alone before chain addresses were recorded.
+/// Otherwise the oldest unsynced proposal with the same proposer and
+/// description, created through the REST API, is linked to it; failing that
+/// a new one is inserted for `fund_id`.
+pub async fn sync_chain_proposal(
+    conn: &mut SqliteConnection,
+    chain_address: &str,
+    chain_id: u64,
+    fund_id: Option<i64>,
+    proposer: &str,
+    description: &str,
+    end_time: DbDateTime,
+    ledger_version: u64,
+) -> Result<Proposal> {
     let now = DbDateTime::now();
-    
-    let asset = sqlx::query_as!(
-        Asset,
+    let chain_id = chain_id as i64;
+    let ledger_version = ledger_version as i64;
+
+    let existing = sqlx::query_as!(
+        Proposal,
         r#"
-        INSERT INTO assets (symbol, name, decimals, version, address, total_supply, created_at, updated_at)
-        VALUES (?, ?, ?, 1, NULL, 0, ?, ?)
+        UPDATE proposals
+        SET chain_address = ?, proposer_address = ?, ledger_version = ?, updated_at = ?
+        WHERE id = (
+            SELECT id FROM proposals
+            WHERE (chain_address = ? OR chain_address IS NULL) AND chain_id = ? AND synced = true
+            ORDER BY chain_address IS NULL, id
+            LIMIT 1
+        )
         RETURNING 
             id as "id!", 
-            symbol as "symbol!", 
-            name as "name!", 
-            decimals as "decimals!: i32",
-            version as "version!",
-            address as "address",
-            total_supply as "total_supply!",
+            fund_id, 
+            title as "title!", 
+            description as "description!", 
+            end_time as "end_time!", 
+            status as "status!", 
+            executed as "executed!", 
+            vetoed as "vetoed!", 
+            chain_id as "chain_id!", 
+            chain_address,
+            synced as "synced!",
+            proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        symbol,
-        name,
-        decimals,
+        chain_address,
+        proposer,
+        ledger_version,
         now,
-        now
+        chain_address,
+        chain_id
     )
-    .fetch_one(pool)
+    .fetch_optional(&mut *conn)
     .await
-    .context("Failed to create asset")?;
+    .context("Failed to sync proposal creation")?;
 
-    Ok(asset)
-}
+    if let Some(proposal) = existing {
+        return Ok(proposal);
+    }
 
-// Balance operations
-pub async fn get_asset_balances(
-    pool: &Pool<Sqlite>,
-    asset_id: i64,
-) -> Result<Vec<Balance>> {
-    let balances = sqlx::query_as!(
-        Balance,
+    // The chain's voting period is the one that counts
+    let linked = sqlx::query_as!(
+        Proposal,
         r#"
-        SELECT 
+        UPDATE proposals
+        SET chain_address = ?, chain_id = ?, synced = true, end_time = ?, ledger_version = ?, updated_at = ?
+        WHERE id = (
+            SELECT id FROM proposals
+            WHERE synced = false AND status = 'active' AND proposer_address = ? AND description = ?
+            ORDER BY id
+            LIMIT 1
+        )
+        RETURNING 
             id as "id!", 
-            asset_id as "asset_id!", 
-            holder_address as "holder_address!", 
-            amount as "amount!",
+            fund_id, 
+            title as "title!", 
+            description as "description!", 
+            end_time as "end_time!", 
+            status as "status!", 
+            executed as "executed!", 
+            vetoed as "vetoed!", 
+            chain_id as "chain_id!", 
+            chain_address,
+            synced as "synced!",
+            proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
-        FROM balances WHERE asset_id = ?
         "#,
-        asset_id
+        chain_address,
+        chain_id,
+        end_time,
+        ledger_version,
+        now,
+        proposer,
+        description
     )
-    .fetch_all(pool)
+    .fetch_optional(&mut *conn)
     .await
-    .context("Failed to get asset balances")?;
+    .context("Failed to link proposal to chain")?;
 
-    Ok(balances)
-}
+    if let Some(proposal) = linked {
+        return Ok(proposal);
+    }
 
-pub async fn create_balance(
-    pool: &Pool<Sqlite>,
-    asset_id: i64,
-    holder_address: AccountAddress,
-    amount: i64,
-) -> Result<Balance> {
-    let now = DbDateTime::now();
-    let holder_str = holder_address.to_string();
-    
-    let balance = sqlx::query_as!(
-        Balance,
+    let title = format!("On-chain proposal {}", chain_id);
+    let proposal = sqlx::query_as!(
+        Proposal,
         r#"
-        INSERT INTO balances (asset_id, holder_address, amount, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?)
+        INSERT INTO proposals (
+            fund_id, title, description, end_time, status,
+            executed, vetoed, chain_address, chain_id, synced,
+            proposer_address, ledger_version, created_at, updated_at
+        )
+        VALUES (?, ?, ?, ?, 'active', false, false, ?, ?, true, ?, ?, ?, ?)
         RETURNING 
             id as "id!", 
-            asset_id as "asset_id!", 
-            holder_address as "holder_address!", 
-            amount as "amount!",
+            fund_id, 
+            title as "title!", 
+            description as "description!", 
+            end_time as "end_time!", 
+            status as "status!", 
+            executed as "executed!", 
+            vetoed as "vetoed!", 
+            chain_id as "chain_id!", 
+            chain_address,
+            synced as "synced!",
+            proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        asset_id,
-        holder_str,
-        amount,
+        fund_id,
+        title,
+        description,
+        end_time,
+        chain_address,
+        chain_id,
+        proposer,
+        ledger_version,
         now,
         now
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *conn)
     .await
-    .context("Failed to create balance")?;
+    .context("Failed to index chain proposal")?;
 
-    Ok(balance)
-}
-
-pub async fn get_fund_by_id(pool: &Pool<Sqlite>, fund_id: i64) -> Result<Fund> {
-    get_fund(pool, fund_id).await
+    Ok(proposal)
 }
 
-pub async fn get_fund_messages(
-    pool: &Pool<Sqlite>,
-    fund_id: i64,
-    limit: i64,
-    before_id: Option<i64>,
-) -> Result<Vec<Message>> {
-    let query = format!(
+/// Marks the proposal linked to `chain_id` in the `GovernanceData` at
+/// `chain_address` as executed. Returns None when
+/// no proposal is linked to it.
+pub async fn sync_proposal_execution<'e, E>(
+    executor: E,
+    chain_address: &str,
+    chain_id: u64,
+    ledger_version: u64,
+) -> Result<Option<Proposal>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = DbDateTime::now();
+    let chain_id = chain_id as i64;
+    let ledger_version = ledger_version as i64;
+    
+    let proposal = sqlx::query_as!(
+        Proposal,
         r#"
-        SELECT 
+        UPDATE proposals 
+        SET executed = true, status = 'executed', ledger_version = ?, updated_at = ?
+        WHERE chain_address = ? AND chain_id = ? AND synced = true
+        RETURNING 
             id as "id!", 
-            fund_id as "fund_id!", 
-            sender_address as "sender_address!", 
-            content as "content!",
+            fund_id, 
+            title as "title!", 
+            description as "description!", 
+            end_time as "end_time!", 
+            status as "status!", 
+            executed as "executed!", 
+            vetoed as "vetoed!", 
+            chain_id as "chain_id!", 
+            chain_address,
+            synced as "synced!",
+            proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
-        FROM messages 
-        WHERE fund_id = ?1 {}
-        ORDER BY id DESC
-        LIMIT ?2
         "#,
-        before_id.map_or(String::new(), |_| String::from("AND id < ?3"))
-    );
-
-    let mut q = sqlx::query_as::<_, Message>(&query)
-        .bind(fund_id)
-        .bind(limit);
-
-    if let Some(before) = before_id {
-        q = q.bind(before);
-    }
+        ledger_version,
+        now,
+        chain_address,
+        chain_id
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to sync proposal execution")?;
 
-    q.fetch_all(pool)
-        .await
-        .map_err(AppError::Database)
+    Ok(proposal)
 }
 
-pub async fn create_fund_wallet(
-    pool: &Pool<Sqlite>,
-    fund_id: i64,
-    wallet_address: &str,
-) -> Result<FundWallet> {
+/// Marks the proposal linked to `chain_id` in the `GovernanceData` at
+/// `chain_address` as vetoed. Returns None when no
+/// proposal is linked to it.
+pub async fn sync_proposal_veto<'e, E>(
+    executor: E,
+    chain_address: &str,
+    chain_id: u64,
+    ledger_version: u64,
+) -> Result<Option<Proposal>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     let now = DbDateTime::now();
-    let wallet = sqlx::query_as!(
-        FundWallet,
+    let chain_id = chain_id as i64;
+    let ledger_version = ledger_version as i64;
+    
+    let proposal = sqlx::query_as!(
+        Proposal,
         r#"
-        INSERT INTO fund_wallets (fund_id, wallet_address, created_at, updated_at)
-        VALUES (?, ?, ?, ?)
+        UPDATE proposals 
+        SET vetoed = true, status = 'vetoed', ledger_version = ?, updated_at = ?
+        WHERE chain_address = ? AND chain_id = ? AND synced = true
         RETURNING 
             id as "id!", 
-            fund_id as "fund_id!", 
-            wallet_address as "wallet_address!", 
+            fund_id, 
+            title as "title!", 
+            description as "description!", 
+            end_time as "end_time!", 
+            status as "status!", 
+            executed as "executed!", 
+            vetoed as "vetoed!", 
+            chain_id as "chain_id!", 
+            chain_address,
+            synced as "synced!",
+            proposer_address,
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        fund_id,
-        wallet_address,
+        ledger_version,
         now,
-        now
+        chain_address,
+        chain_id
     )
-    .fetch_one(pool)
+    .fetch_optional(executor)
     .await
-    .context("Failed to create fund wallet")?;
-    Ok(wallet)
+    .context("Failed to sync proposal veto")?;
+
+    Ok(proposal)
 }
 
-pub async fn get_fund_wallet(
-    pool: &Pool<Sqlite>,
-    fund_id: i64,
-) -> Result<FundWallet> {
-    let wallet = sqlx::query_as!(
-        FundWallet,
+pub async fn get_proposal_id_by_chain_id<'e, E>(
+    executor: E,
+    chain_address: &str,
+    chain_id: u64,
+) -> Result<Option<i64>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let chain_id_i64 = chain_id as i64;
+
+    Ok(sqlx::query_scalar!(
         r#"
-        SELECT 
-            id as "id!", 
-            fund_id as "fund_id!", 
-            wallet_address as "wallet_address!", 
-            created_at as "created_at!", 
-            updated_at as "updated_at!"
-        FROM fund_wallets 
-        WHERE fund_id = ?
+        SELECT id as "id!"
+        FROM proposals
+        WHERE chain_address = ? AND chain_id = ? AND synced = true
         "#,
-        fund_id
+        chain_address,
+        chain_id_i64
     )
-    .fetch_one(pool)
+    .fetch_optional(executor)
     .await
-    .context("Failed to get fund wallet")?;
-    Ok(wallet)
+    .context("Failed to look up proposal by chain id")?)
 }
 
-pub async fn create_investment(
-    pool: &Pool<Sqlite>,
-    fund_id: i64,
-    asset_id: i64,
-    amount: i64,
-    investor_address: &str,
-) -> Result<Investment> {
+/// The fund a proposal belongs to, if it is tied to one.
+pub async fn get_proposal_fund_id<'e, E>(
+    executor: E,
+    proposal_id: i64,
+) -> Result<Option<i64>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    Ok(sqlx::query_scalar!(
+        r#"
+        SELECT fund_id
+        FROM proposals
+        WHERE id = ?
+        "#,
+        proposal_id
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to look up proposal fund")?
+    .flatten())
+}
+
+/// Upserts a vote seen on chain. The chain is authoritative, so it
+/// overwrites whatever the REST API recorded for the same voter.
+pub async fn sync_vote<'e, E>(
+    executor: E,
+    proposal_id: i64,
+    voter_address: &str,
+    vote_type: bool,
+    voted_at: DbDateTime,
+    ledger_version: u64,
+) -> Result<Vote>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     let now = DbDateTime::now();
-    let investment = sqlx::query_as!(
-        Investment,
+    let ledger_version = ledger_version as i64;
+
+    let vote = sqlx::query_as!(
+        Vote,
         r#"
-        INSERT INTO investments (
-            fund_id, asset_id, amount, withdrawn_amount,
-            investor_address, created_at, updated_at
-        )
-        VALUES (?, ?, ?, 0, ?, ?, ?)
+        INSERT INTO votes (proposal_id, voter_address, vote_type, voted_at, is_veto, on_chain, ledger_version, created_at, updated_at)
+        VALUES (?, ?, ?, ?, false, true, ?, ?, ?)
+        ON CONFLICT (proposal_id, voter_address) DO UPDATE
+        SET vote_type = excluded.vote_type,
+            voted_at = excluded.voted_at,
+            on_chain = true,
+            ledger_version = excluded.ledger_version,
+            updated_at = excluded.updated_at
         RETURNING 
             id as "id!", 
-            fund_id as "fund_id!", 
-            asset_id as "asset_id!", 
-            amount as "amount!", 
-            withdrawn_amount as "withdrawn_amount!", 
-            investor_address as "investor_address!", 
+            proposal_id as "proposal_id!", 
+            voter_address as "voter_address!", 
+            vote_type as "vote_type!", 
+            voted_at as "voted_at: DbDateTime",
+            is_veto as "is_veto!",
+            on_chain as "on_chain!",
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        fund_id,
-        asset_id,
-        amount,
-        investor_address,
+        proposal_id,
+        voter_address,
+        vote_type,
+        voted_at,
+        ledger_version,
         now,
         now
     )
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
-    .context("Failed to create investment")?;
-    Ok(investment)
+    .context("Failed to sync vote")?;
+
+    Ok(vote)
 }
 
-pub async fn withdraw_investment(
-    pool: &Pool<Sqlite>,
-    investment_id: i64,
-    amount: i64,
-) -> Result<Investment> {
+/// Records the initiator of an emergency veto. An existing vote keeps its
+/// direction; a veto without a prior vote counts as a vote against.
+pub async fn sync_veto_vote<'e, E>(
+    executor: E,
+    proposal_id: i64,
+    initiator_address: &str,
+    voted_at: DbDateTime,
+    ledger_version: u64,
+) -> Result<Vote>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     let now = DbDateTime::now();
-    let investment = sqlx::query_as!(
-        Investment,
+    let ledger_version = ledger_version as i64;
+
+    let vote = sqlx::query_as!(
+        Vote,
         r#"
-        UPDATE investments 
-        SET withdrawn_amount = withdrawn_amount + ?, updated_at = ?
-        WHERE id = ?
+        INSERT INTO votes (proposal_id, voter_address, vote_type, voted_at, is_veto, on_chain, ledger_version, created_at, updated_at)
+        VALUES (?, ?, false, ?, true, true, ?, ?, ?)
+        ON CONFLICT (proposal_id, voter_address) DO UPDATE
+        SET is_veto = true,
+            on_chain = true,
+            ledger_version = excluded.ledger_version,
+            voted_at = COALESCE(votes.voted_at, excluded.voted_at),
+            updated_at = excluded.updated_at
         RETURNING 
             id as "id!", 
-            fund_id as "fund_id!", 
-            asset_id as "asset_id!", 
-            amount as "amount!", 
-            withdrawn_amount as "withdrawn_amount!", 
-            investor_address as "investor_address!", 
+            proposal_id as "proposal_id!", 
+            voter_address as "voter_address!", 
+            vote_type as "vote_type!", 
+            voted_at as "voted_at: DbDateTime",
+            is_veto as "is_veto!",
+            on_chain as "on_chain!",
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        amount,
+        proposal_id,
+        initiator_address,
+        voted_at,
+        ledger_version,
         now,
-        investment_id
+        now
     )
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
-    .context("Failed to withdraw investment")?;
-    Ok(investment)
+    .context("Failed to sync veto vote")?;
+
+    Ok(vote)
 }
 
-pub async fn update_member_share(
+pub async fn get_proposal_votes(
     pool: &Pool<Sqlite>,
-    fund_id: i64,
-    member_address: &str,
-    new_share: i64,
-) -> Result<FundMember> {
-    let now = DbDateTime::now();
-    Ok(sqlx::query_as!(
-        FundMember,
+    proposal_id: i64,
+) -> Result<Vec<Vote>> {
+    let votes = sqlx::query_as!(
+        Vote,
         r#"
-        UPDATE fund_members 
-        SET share = ?, updated_at = ?
-        WHERE fund_id = ? AND member_address = ?
-        RETURNING 
+        SELECT 
             id as "id!", 
-            fund_id as "fund_id!", 
-            member_address as "member_address!", 
-            share as "share!",
-            status as "status!",
+            proposal_id as "proposal_id!", 
+            voter_address as "voter_address!", 
+            vote_type as "vote_type!", 
+            voted_at as "voted_at: DbDateTime",
+            is_veto as "is_veto!",
+            on_chain as "on_chain!",
             created_at as "created_at!", 
             updated_at as "updated_at!"
+        FROM votes
+        WHERE proposal_id = ?
+        ORDER BY id
         "#,
-        new_share,
-        now,
-        fund_id,
-        member_address
+        proposal_id
     )
-    .fetch_one(pool)
+    .fetch_all(pool)
     .await
-    .context("Failed to update member share")?)
+    .context("Failed to get proposal votes")?;
+
+    Ok(votes)
 }
 
-pub async fn update_balances(
+// Asset operations
+pub async fn create_asset(
     pool: &Pool<Sqlite>,
-    symbol: &str,
-    from_address: &str,
-    to_address: &str,
-    amount: u64,
-) -> Result<()> {
+    symbol: String,
+    name: String,
+    decimals: i32,
+) -> Result<Asset> {
     let now = DbDateTime::now();
-    let amount_i64 = amount as i64;
     
-    // Get asset ID from symbol
     let asset = sqlx::query_as!(
         Asset,
         r#"
-        SELECT 
+        INSERT INTO assets (symbol, name, decimals, version, address, total_supply, created_at, updated_at)
+        VALUES (?, ?, ?, 1, NULL, 0, ?, ?)
+        RETURNING 
             id as "id!", 
             symbol as "symbol!", 
             name as "name!", 
             decimals as "decimals!: i32",
             version as "version!",
-            address,
+            address as "address",
             total_supply as "total_supply!",
             created_at as "created_at!", 
             updated_at as "updated_at!"
-        FROM assets WHERE symbol = ?
         "#,
-        symbol
+        symbol,
+        name,
+        decimals,
+        now,
+        now
     )
     .fetch_one(pool)
     .await
-    .context("Failed to find asset")?;
+    .context("Failed to create asset")?;
 
-    // Update sender's balance
-    sqlx::query!(
-        r#"
-        UPDATE balances 
-        SET amount = amount - ?, updated_at = ?
-        WHERE asset_id = ? AND holder_address = ?
-        "#,
-        amount_i64,
-        now,
-        asset.id,
-        from_address
-    )
-    .execute(pool)
-    .await
-    .context("Failed to update sender balance")?;
+    Ok(asset)
+}
 
-    // Update receiver's balance
-    sqlx::query!(
+// Balance operations
+pub async fn get_asset_balances(
+    pool: &Pool<Sqlite>,
+    asset_id: i64,
+) -> Result<Vec<Balance>> {
+    let balances = sqlx::query_as!(
+        Balance,
         r#"
-        UPDATE balances 
-        SET amount = amount + ?, updated_at = ?
-        WHERE asset_id = ? AND holder_address = ?
+        SELECT 
+            id as "id!", 
+            asset_id as "asset_id!", 
+            holder_address as "holder_address!", 
+            amount as "amount!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM balances WHERE asset_id = ?
         "#,
-        amount_i64,
-        now,
-        asset.id,
-        to_address
+        asset_id
     )
-    .execute(pool)
+    .fetch_all(pool)
     .await
-    .context("Failed to update receiver balance")?;
+    .context("Failed to get asset balances")?;
 
-    Ok(())
+    Ok(balances)
 }
 
-pub async fn sync_member_addition(
+pub async fn create_balance(
     pool: &Pool<Sqlite>,
-    fund_id: i64,
-    member_address: &str,
-) -> Result<FundMember> {
+    asset_id: i64,
+    holder_address: AccountAddress,
+    amount: i64,
+) -> Result<Balance> {
     let now = DbDateTime::now();
+    let holder_str = holder_address.to_string();
     
-    let member = sqlx::query_as!(
-        FundMember,
+    let balance = sqlx::query_as!(
+        Balance,
         r#"
-        INSERT INTO fund_members (fund_id, member_address, share, status, created_at, updated_at)
-        VALUES (?, ?, 0, 'active', ?, ?)
+        INSERT INTO balances (asset_id, holder_address, amount, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?)
         RETURNING 
             id as "id!", 
-            fund_id as "fund_id!", 
-            member_address as "member_address!", 
-            share as "share!",
-            status as "status!",
+            asset_id as "asset_id!", 
+            holder_address as "holder_address!", 
+            amount as "amount!",
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        fund_id,
-        member_address,
+        asset_id,
+        holder_str,
+        amount,
         now,
         now
     )
     .fetch_one(pool)
     .await
-    .context("Failed to sync member addition")?;
+    .context("Failed to create balance")?;
 
-    Ok(member)
+    Ok(balance)
 }
 
-pub async fn sync_member_removal(
-    pool: &Pool<Sqlite>,
-    fund_id: i64,
-    member_address: &str,
-) -> Result<()> {
-    sqlx::query!(
-        r#"
-        DELETE FROM fund_members
-        WHERE fund_id = ? AND member_address = ?
-        "#,
-        fund_id,
-        member_address
-    )
-    .execute(pool)
-    .await
-    .context("Failed to sync member removal")?;
-
-    Ok(())
+pub async fn get_fund_by_id(pool: &Pool<Sqlite>, fund_id: i64) -> Result<Fund> {
+    get_fund(pool, fund_id).await
 }
 
-pub async fn get_asset_by_symbol(
+pub async fn get_fund_messages(
     pool: &Pool<Sqlite>,
-    symbol: &str,
-) -> Result<Asset> {
-    Ok(sqlx::query_as!(
-        Asset,
+    fund_id: i64,
+    limit: i64,
+    before_id: Option<i64>,
+) -> Result<Vec<Message>> {
+    let query = format!(
         r#"
         SELECT 
             id as "id!", 
-            symbol as "symbol!", 
-            name as "name!", 
-            decimals as "decimals!: i32",
-            version as "version!",
-            address,
-            total_supply as "total_supply!",
+            fund_id as "fund_id!", 
+            sender_address as "sender_address!", 
+            content as "content!",
             created_at as "created_at!", 
             updated_at as "updated_at!"
-        FROM assets 
-        WHERE symbol = ?
+        FROM messages 
+        WHERE fund_id = ?1 {}
+        ORDER BY id DESC
+        LIMIT ?2
         "#,
-        symbol
-    )
-    .fetch_one(pool)
-    .await
-    .context("Failed to get asset")?)
+        before_id.map_or(String::new(), |_| String::from("AND id < ?3"))
+    );
+
+    let mut q = sqlx::query_as::<_, Message>(&query)
+        .bind(fund_id)
+        .bind(limit);
+
+    if let Some(before) = before_id {
+        q = q.bind(before);
+    }
+
+    q.fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
 }
 
-pub async fn create_fund_member(
+/// Creates the fund's wallet. A wallet whose address was never recorded is
+/// filled in instead; any other existing wallet is left untouched.
+pub async fn create_fund_wallet(
     pool: &Pool<Sqlite>,
     fund_id: i64,
-    member_address: &str,
-    share: i64,
-) -> Result<FundMember> {
+    wallet_address: &str,
+    actuator_address: &str,
+) -> Result<FundWallet> {
     let now = DbDateTime::now();
-    Ok(sqlx::query_as!(
-        FundMember,
+    let wallet = sqlx::query_as!(
+        FundWallet,
         r#"
-        INSERT INTO fund_members (fund_id, member_address, share, status, created_at, updated_at)
-        VALUES (?, ?, ?, 'active', ?, ?)
+        INSERT INTO fund_wallets (fund_id, wallet_address, actuator_address, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (fund_id) DO UPDATE
+        SET wallet_address = excluded.wallet_address,
+            actuator_address = excluded.actuator_address,
+            updated_at = excluded.updated_at
+        WHERE fund_wallets.wallet_address = ''
         RETURNING 
             id as "id!", 
             fund_id as "fund_id!", 
-            member_address as "member_address!", 
-            share as "share!",
-            status as "status!",
+            wallet_address as "wallet_address!", 
+            actuator_address as "actuator_address!", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
         fund_id,
-        member_address,
-        share,
+        wallet_address,
+        actuator_address,
         now,
         now
     )
-    .fetch_one(pool)
+    .fetch_optional(pool)
     .await
-    .context("Failed to create fund member")?)
+    .context("Failed to create fund wallet")?;
+
+    wallet.ok_or_else(|| AppError::AlreadyExists(format!("Wallet of fund {}", fund_id)))
 }
 
-pub async fn get_fund_member(
+pub async fn get_fund_wallet(
     pool: &Pool<Sqlite>,
     fund_id: i64,
-    member_address: &str,
-) -> Result<FundMember> {
-    Ok(sqlx::query_as!(
-        FundMember,
+) -> Result<FundWallet> {
+    let wallet = sqlx::query_as!(
+        FundWallet,
         r#"
         SELECT 
             id as "id!", 
             fund_id as "fund_id!", 
-            member_address as "member_address!", 
-            share as "share!",
-            status as "status!",
+            wallet_address as "wallet_address!", 
+            actuator_address as "actuator_address!", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
-        FROM fund_members 
-        WHERE fund_id = ? AND member_address = ?
+        FROM fund_wallets 
+        WHERE fund_id = ?
         "#,
-        fund_id,
-        member_address
+        fund_id
     )
     .fetch_one(pool)
     .await
-    .context("Failed to get fund member")?)
+    .context("Failed to get fund wallet")?;
+    Ok(wallet)
 }
 
-pub async fn get_all_funds(pool: &Pool<Sqlite>) -> Result<Vec<Fund>> {
-    let funds = sqlx::query_as!(
-        Fund,
+pub async fn create_investment(
+    pool: &Pool<Sqlite>,
+    fund_id: i64,
+    asset_id: i64,
+    amount: i64,
+    investor_address: &str,
+) -> Result<Investment> {
+    let now = DbDateTime::now();
+    let investment = sqlx::query_as!(
+        Investment,
         r#"
-        SELECT 
+        INSERT INTO investments (
+            fund_id, asset_id, amount, withdrawn_amount,
+            investor_address, created_at, updated_at
+        )
+        VALUES (?, ?, ?, 0, ?, ?, ?)
+        RETURNING 
             id as "id!", 
-            name as "name!", 
-            executor_address as "executor_address!",
-            version as "version!",
-            status as "status!",
+            fund_id as "fund_id!", 
+            asset_id as "asset_id!", 
+            amount as "amount!", 
+            withdrawn_amount as "withdrawn_amount!", 
+            investor_address as "investor_address!", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
-        FROM funds
-        "#
+        "#,
+        fund_id,
+        asset_id,
+        amount,
+        investor_address,
+        now,
+        now
     )
-    .fetch_all(pool)
+    .fetch_one(pool)
     .await
-    .context("Failed to get all funds")?;
-
-    Ok(funds)
+    .context("Failed to create investment")?;
+    Ok(investment)
 }
 
-pub async fn update_fund_state(
+pub async fn withdraw_investment(
     pool: &Pool<Sqlite>,
-    fund_id: i64,
-    version: u64,
-    status: String,
-) -> Result<Fund> {
+    investment_id: i64,
+    amount: i64,
+) -> Result<Investment> {
     let now = DbDateTime::now();
-    let version_i64 = version as i64;
-    
-    let fund = sqlx::query_as!(
-        Fund,
+    let investment = sqlx::query_as!(
+        Investment,
         r#"
-        UPDATE funds 
-        SET version = ?, status = ?, updated_at = ?
+        UPDATE investments 
+        SET withdrawn_amount = withdrawn_amount + ?, updated_at = ?
         WHERE id = ?
         RETURNING 
             id as "id!", 
-            name as "name!", 
-            executor_address as "executor_address!",
-            version as "version!",
-            status as "status!",
+            fund_id as "fund_id!", 
+            asset_id as "asset_id!", 
+            amount as "amount!", 
+            withdrawn_amount as "withdrawn_amount!", 
+            investor_address as "investor_address!", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        version_i64,
-        status,
+        amount,
         now,
-        fund_id
+        investment_id
     )
     .fetch_one(pool)
     .await
-    .context("Failed to update fund state")?;
-
-    Ok(fund)
+    .context("Failed to withdraw investment")?;
+    Ok(investment)
 }
 
-pub async fn sync_member_state(
+pub async fn update_member_share(
     pool: &Pool<Sqlite>,
     fund_id: i64,
     member_address: &str,
-    share: u64,
-    status: String,
+    new_share: i64,
 ) -> Result<FundMember> {
     let now = DbDateTime::now();
-    let share_i64 = share as i64;
-    
-    // Try to update existing member first
-    let result = sqlx::query_as!(
+    Ok(sqlx::query_as!(
         FundMember,
         r#"
         UPDATE fund_members 
-        SET share = ?, status = ?, updated_at = ?
+        SET share = ?, updated_at = ?
         WHERE fund_id = ? AND member_address = ?
         RETURNING 
             id as "id!", 
@@ -1083,52 +1766,43 @@ pub async fn sync_member_state(
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        share_i64,
-        status,
+        new_share,
         now,
         fund_id,
         member_address
     )
-    .fetch_optional(pool)
+    .fetch_one(pool)
     .await
-    .context("Failed to update member state")?;
-
-    match result {
-        Some(member) => Ok(member),
-        None => {
-            // Member doesn't exist, create new one
-            let member = sqlx::query_as!(
-                FundMember,
-                r#"
-                INSERT INTO fund_members (fund_id, member_address, share, status, created_at, updated_at)
-                VALUES (?, ?, ?, ?, ?, ?)
-                RETURNING 
-                    id as "id!", 
-                    fund_id as "fund_id!", 
-                    member_address as "member_address!", 
-                    share as "share!",
-                    status as "status!",
-                    created_at as "created_at!", 
-                    updated_at as "updated_at!"
-                "#,
-                fund_id,
-                member_address,
-                share_i64,
-                status,
-                now,
-                now
-            )
-            .fetch_one(pool)
-            .await
-            .context("Failed to create member")?;
+    .context("Failed to update member share")?)
+}
 
-            Ok(member)
-        }
-    }
+fn is_zero_address(address: &str) -> bool {
+    AccountAddress::from_str(address).map_or(false, |a| a == AccountAddress::ZERO)
 }
 
-pub async fn get_all_assets(pool: &Pool<Sqlite>) -> Result<Vec<Asset>> {
-    let assets = sqlx::query_as!(
+/// Applies a transfer to the local balance table. The zero address stands
+/// for mint (as sender) or burn (as receiver), which also moves
+/// `assets.total_supply`. Fails without side effects on overdraft.
+pub async fn update_balances(
+    conn: &mut SqliteConnection,
+    symbol: &str,
+    from_address: &str,
+    to_address: &str,
+    amount: u64,
+    ledger_version: u64,
+) -> Result<()> {
+    let now = DbDateTime::now();
+    let amount_i64 = i64::try_from(amount)
+        .map_err(|_| AppError::InvalidInput(format!("Transfer amount {} too large", amount)))?;
+    let ledger_version = ledger_version as i64;
+    let is_mint = is_zero_address(from_address);
+    let is_burn = is_zero_address(to_address);
+
+    // Runs as a savepoint when the caller already holds a transaction
+    let mut tx = conn.begin().await?;
+
+    // Get asset ID from symbol
+    let asset = sqlx::query_as!(
         Asset,
         r#"
         SELECT 
@@ -1141,87 +1815,202 @@ pub async fn get_all_assets(pool: &Pool<Sqlite>) -> Result<Vec<Asset>> {
             total_supply as "total_supply!",
             created_at as "created_at!", 
             updated_at as "updated_at!"
-        FROM assets
-        "#
+        FROM assets WHERE symbol = ?
+        "#,
+        symbol
     )
-    .fetch_all(pool)
+    .fetch_one(&mut *tx)
     .await
-    .context("Failed to get all assets")?;
+    .map_err(|e| match e {
+        sqlx::Error::RowNotFound => AppError::NotFound(format!("Asset {} not found", symbol)),
+        e => AppError::Database(e)
+    })?;
 
-    Ok(assets)
-}
+    if is_mint {
+        sqlx::query!(
+            r#"
+            UPDATE assets 
+            SET total_supply = total_supply + ?, ledger_version = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            amount_i64,
+            ledger_version,
+            now,
+            asset.id
+        )
+        .execute(&mut *tx)
+        .await
+        .context("Failed to increase total supply")?;
+    } else {
+        // Debit the sender only if it can cover the amount
+        let debited = sqlx::query!(
+            r#"
+            UPDATE balances 
+            SET amount = amount - ?, ledger_version = ?, updated_at = ?
+            WHERE asset_id = ? AND holder_address = ? AND amount >= ?
+            "#,
+            amount_i64,
+            ledger_version,
+            now,
+            asset.id,
+            from_address,
+            amount_i64
+        )
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update sender balance")?;
+
+        if debited.rows_affected() == 0 {
+            return Err(AppError::InsufficientBalance {
+                holder: from_address.to_string(),
+                symbol: symbol.to_string(),
+                requested: amount,
+            });
+        }
+    }
 
-pub async fn update_asset_state(
-    pool: &Pool<Sqlite>,
-    asset_id: i64,
-    version: u64,
-    total_supply: u64,
-    holders: Vec<HolderInfo>,
-) -> Result<Asset> {
+    if is_burn {
+        sqlx::query!(
+            r#"
+            UPDATE assets 
+            SET total_supply = MAX(total_supply - ?, 0), ledger_version = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            amount_i64,
+            ledger_version,
+            now,
+            asset.id
+        )
+        .execute(&mut *tx)
+        .await
+        .context("Failed to decrease total supply")?;
+    } else {
+        // Credit the receiver, creating its row on first receipt
+        sqlx::query!(
+            r#"
+            INSERT INTO balances (asset_id, holder_address, amount, ledger_version, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (asset_id, holder_address) DO UPDATE
+            SET amount = amount + excluded.amount,
+                ledger_version = excluded.ledger_version,
+                updated_at = excluded.updated_at
+            "#,
+            asset.id,
+            to_address,
+            amount_i64,
+            ledger_version,
+            now,
+            now
+        )
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update receiver balance")?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn sync_member_addition<'e, E>(
+    executor: E,
+    fund_id: i64,
+    member_address: &str,
+) -> Result<FundMember>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     let now = DbDateTime::now();
-    let version_i64 = version as i64;
-    let total_supply_i64 = total_supply as i64;
     
-    // Start a transaction
-    let mut tx = pool.begin().await?;
-
-    // Update asset
-    let asset = sqlx::query_as!(
-        Asset,
+    let member = sqlx::query_as!(
+        FundMember,
         r#"
-        UPDATE assets 
-        SET version = ?, total_supply = ?, updated_at = ?
-        WHERE id = ?
+        INSERT INTO fund_members (fund_id, member_address, share, status, created_at, updated_at)
+        VALUES (?, ?, 0, 'active', ?, ?)
         RETURNING 
             id as "id!", 
-            symbol as "symbol!", 
-            name as "name!", 
-            decimals as "decimals!: i32",
-            version as "version!",
-            address as "address",
-            total_supply as "total_supply!",
+            fund_id as "fund_id!", 
+            member_address as "member_address!", 
+            share as "share!",
+            status as "status!",
             created_at as "created_at!", 
             updated_at as "updated_at!"
         "#,
-        version_i64,
-        total_supply_i64,
+        fund_id,
+        member_address,
         now,
-        asset_id
+        now
     )
-    .fetch_one(&mut *tx)
+    .fetch_one(executor)
     .await
-    .context("Failed to update asset state")?;
+    .context("Failed to sync member addition")?;
 
-    // Update balances for all holders
-    for holder in holders {
-        let balance_i64 = holder.balance as i64;
-        sqlx::query!(
-            r#"
-            INSERT INTO balances (asset_id, holder_address, amount, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?)
-            ON CONFLICT (asset_id, holder_address) DO UPDATE
-            SET amount = excluded.amount, updated_at = excluded.updated_at
-            "#,
-            asset_id,
-            holder.address,
-            balance_i64,
-            now,
-            now
-        )
-        .execute(&mut *tx)
-        .await
-        .context("Failed to update holder balance")?;
-    }
+    Ok(member)
+}
+
+/// Removes a member. The row is stamped with the removal's ledger version
+/// first so the delete is journaled and can be reverted by a rollback.
+pub async fn sync_member_removal(
+    conn: &mut SqliteConnection,
+    fund_id: i64,
+    member_address: &str,
+    ledger_version: u64,
+) -> Result<()> {
+    let ledger_version = ledger_version as i64;
+    let mut tx = conn.begin().await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE fund_members
+        SET ledger_version = ?
+        WHERE fund_id = ? AND member_address = ?
+        "#,
+        ledger_version,
+        fund_id,
+        member_address
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to stamp removed member")?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM fund_members
+        WHERE fund_id = ? AND member_address = ?
+        "#,
+        fund_id,
+        member_address
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to sync member removal")?;
 
-    // Commit transaction
     tx.commit().await?;
+    Ok(())
+}
 
-    Ok(asset)
+pub async fn get_asset_id_by_symbol<'e, E>(
+    executor: E,
+    symbol: &str,
+) -> Result<Option<i64>>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    Ok(sqlx::query_scalar!(
+        r#"
+        SELECT id as "id!"
+        FROM assets
+        WHERE symbol = ?
+        "#,
+        symbol
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to look up asset by symbol")?)
 }
 
-pub async fn get_asset_by_id(
+pub async fn get_asset_by_symbol(
     pool: &Pool<Sqlite>,
-    id: i64,
+    symbol: &str,
 ) -> Result<Asset> {
     Ok(sqlx::query_as!(
         Asset,
@@ -1237,12 +2026,1166 @@ pub async fn get_asset_by_id(
             created_at as "created_at!", 
             updated_at as "updated_at!"
         FROM assets 
-        WHERE id = ?
+        WHERE symbol = ?
         "#,
-        id
+        symbol
     )
     .fetch_one(pool)
     .await
     .context("Failed to get asset")?)
 }
 
+pub async fn create_fund_member(
+    pool: &Pool<Sqlite>,
+    fund_id: i64,
+    member_address: &str,
+    share: i64,
+) -> Result<FundMember> {
+    let now = DbDateTime::now();
+    Ok(sqlx::query_as!(
+        FundMember,
+        r#"
+        INSERT INTO fund_members (fund_id, member_address, share, status, created_at, updated_at)
+        VALUES (?, ?, ?, 'active', ?, ?)
+        RETURNING 
+            id as "id!", 
+            fund_id as "fund_id!", 
+            member_address as "member_address!", 
+            share as "share!",
+            status as "status!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        fund_id,
+        member_address,
+        share,
+        now,
+        now
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to create fund member")?)
+}
+
+pub async fn get_fund_member(
+    pool: &Pool<Sqlite>,
+    fund_id: i64,
+    member_address: &str,
+) -> Result<FundMember> {
+    Ok(sqlx::query_as!(
+        FundMember,
+        r#"
+        SELECT 
+            id as "id!", 
+            fund_id as "fund_id!", 
+            member_address as "member_address!", 
+            share as "share!",
+            status as "status!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM fund_members 
+        WHERE fund_id = ? AND member_address = ?
+        "#,
+        fund_id,
+        member_address
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to get fund member")?)
+}
+
+pub async fn get_all_funds(pool: &Pool<Sqlite>) -> Result<Vec<Fund>> {
+    let funds = sqlx::query_as!(
+        Fund,
+        r#"
+        SELECT 
+            id as "id!", 
+            name as "name!", 
+            executor_address as "executor_address!",
+            version as "version!",
+            status as "status!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM funds
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get all funds")?;
+
+    Ok(funds)
+}
+
+pub async fn update_fund_state(
+    pool: &Pool<Sqlite>,
+    fund_id: i64,
+    version: u64,
+    status: String,
+    ledger_version: u64,
+) -> Result<Fund> {
+    let now = DbDateTime::now();
+    let version_i64 = version as i64;
+    let ledger_version = ledger_version as i64;
+    
+    let fund = sqlx::query_as!(
+        Fund,
+        r#"
+        UPDATE funds 
+        SET version = ?, status = ?, ledger_version = ?, updated_at = ?
+        WHERE id = ?
+        RETURNING 
+            id as "id!", 
+            name as "name!", 
+            executor_address as "executor_address!",
+            version as "version!",
+            status as "status!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        version_i64,
+        status,
+        ledger_version,
+        now,
+        fund_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to update fund state")?;
+
+    Ok(fund)
+}
+
+pub async fn sync_member_state<'e, E>(
+    executor: E,
+    fund_id: i64,
+    member_address: &str,
+    share: u64,
+    status: String,
+    ledger_version: u64,
+) -> Result<FundMember>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = DbDateTime::now();
+    let share_i64 = share as i64;
+    let ledger_version = ledger_version as i64;
+    
+    // Update the existing member or create it on first sight
+    let member = sqlx::query_as!(
+        FundMember,
+        r#"
+        INSERT INTO fund_members (fund_id, member_address, share, status, ledger_version, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (fund_id, member_address) DO UPDATE
+        SET share = excluded.share,
+            status = excluded.status,
+            ledger_version = excluded.ledger_version,
+            updated_at = excluded.updated_at
+        RETURNING 
+            id as "id!", 
+            fund_id as "fund_id!", 
+            member_address as "member_address!", 
+            share as "share!",
+            status as "status!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        fund_id,
+        member_address,
+        share_i64,
+        status,
+        ledger_version,
+        now,
+        now
+    )
+    .fetch_one(executor)
+    .await
+    .context("Failed to sync member state")?;
+
+    Ok(member)
+}
+
+pub async fn get_all_assets(pool: &Pool<Sqlite>) -> Result<Vec<Asset>> {
+    let assets = sqlx::query_as!(
+        Asset,
+        r#"
+        SELECT 
+            id as "id!", 
+            symbol as "symbol!", 
+            name as "name!", 
+            decimals as "decimals!: i32",
+            version as "version!",
+            address,
+            total_supply as "total_supply!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM assets
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get all assets")?;
+
+    Ok(assets)
+}
+
+pub async fn update_asset_state(
+    pool: &Pool<Sqlite>,
+    asset_id: i64,
+    version: u64,
+    total_supply: u64,
+    holders: Vec<HolderInfo>,
+    ledger_version: u64,
+) -> Result<Asset> {
+    let now = DbDateTime::now();
+    let version_i64 = version as i64;
+    let total_supply_i64 = total_supply as i64;
+    let ledger_version = ledger_version as i64;
+    
+    // Start a transaction
+    let mut tx = pool.begin().await?;
+
+    // Update asset
+    let asset = sqlx::query_as!(
+        Asset,
+        r#"
+        UPDATE assets 
+        SET version = ?, total_supply = ?, ledger_version = ?, updated_at = ?
+        WHERE id = ?
+        RETURNING 
+            id as "id!", 
+            symbol as "symbol!", 
+            name as "name!", 
+            decimals as "decimals!: i32",
+            version as "version!",
+            address as "address",
+            total_supply as "total_supply!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        version_i64,
+        total_supply_i64,
+        ledger_version,
+        now,
+        asset_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to update asset state")?;
+
+    // Update balances for all holders
+    for holder in holders {
+        let balance_i64 = holder.balance as i64;
+        sqlx::query!(
+            r#"
+            INSERT INTO balances (asset_id, holder_address, amount, ledger_version, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (asset_id, holder_address) DO UPDATE
+            SET amount = excluded.amount,
+                ledger_version = excluded.ledger_version,
+                updated_at = excluded.updated_at
+            "#,
+            asset_id,
+            holder.address,
+            balance_i64,
+            ledger_version,
+            now,
+            now
+        )
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update holder balance")?;
+    }
+
+    // Commit transaction
+    tx.commit().await?;
+
+    Ok(asset)
+}
+
+pub async fn get_asset_by_id(
+    pool: &Pool<Sqlite>,
+    id: i64,
+) -> Result<Asset> {
+    Ok(sqlx::query_as!(
+        Asset,
+        r#"
+        SELECT 
+            id as "id!", 
+            symbol as "symbol!", 
+            name as "name!", 
+            decimals as "decimals!: i32",
+            version as "version!",
+            address,
+            total_supply as "total_supply!",
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM assets 
+        WHERE id = ?
+        "#,
+        id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to get asset")?)
+}
+
+// Event cursor operations
+pub async fn get_event_cursor<'e, E>(
+    executor: E,
+    stream: &str,
+) -> Result<u64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let next_sequence = sqlx::query_scalar!(
+        r#"
+        SELECT next_sequence as "next_sequence!"
+        FROM event_cursors
+        WHERE stream = ?
+        "#,
+        stream
+    )
+    .fetch_optional(executor)
+    .await
+    .context("Failed to get event cursor")?;
+
+    Ok(next_sequence.unwrap_or(0) as u64)
+}
+
+pub async fn advance_event_cursor<'e, E>(
+    executor: E,
+    stream: &str,
+    next_sequence: u64,
+    ledger_version: u64,
+) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = DbDateTime::now();
+    let next_sequence_i64 = next_sequence as i64;
+    let ledger_version = ledger_version as i64;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO event_cursors (stream, next_sequence, ledger_version, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (stream) DO UPDATE
+        SET next_sequence = excluded.next_sequence,
+            ledger_version = excluded.ledger_version,
+            updated_at = excluded.updated_at
+        "#,
+        stream,
+        next_sequence_i64,
+        ledger_version,
+        now,
+        now
+    )
+    .execute(executor)
+    .await
+    .context("Failed to advance event cursor")?;
+
+    Ok(())
+}
+
+// Processed event ledger operations
+pub async fn record_processed_event<'e, E>(
+    executor: E,
+    account_address: &str,
+    event_handle: &str,
+    field: &str,
+    event: &VersionedEvent,
+) -> Result<bool>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = DbDateTime::now();
+    let sequence_number_i64 = event.sequence_number.0 as i64;
+    let ledger_version_i64 = event.version.0 as i64;
+    let event_type = event.typ.to_string();
+    let data = event.data.to_string();
+
+    // Returns false when the event is already in the ledger
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO processed_events (
+            account_address, event_handle, field, sequence_number,
+            ledger_version, event_type, data, created_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (account_address, event_handle, field, sequence_number) DO NOTHING
+        "#,
+        account_address,
+        event_handle,
+        field,
+        sequence_number_i64,
+        ledger_version_i64,
+        event_type,
+        data,
+        now
+    )
+    .execute(executor)
+    .await
+    .context("Failed to record processed event")?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn get_recent_processed_events(
+    pool: &Pool<Sqlite>,
+    limit: i64,
+) -> Result<Vec<ProcessedEvent>> {
+    let events = sqlx::query_as!(
+        ProcessedEvent,
+        r#"
+        SELECT 
+            id as "id!", 
+            account_address as "account_address!", 
+            event_handle as "event_handle!", 
+            field as "field!", 
+            sequence_number as "sequence_number!", 
+            ledger_version as "ledger_version!", 
+            event_type as "event_type!", 
+            data as "data!", 
+            created_at as "created_at!"
+        FROM processed_events
+        ORDER BY id DESC
+        LIMIT ?
+        "#,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get processed events")?;
+
+    Ok(events)
+}
+
+// Dead letter operations
+pub async fn record_dead_letter_event<'e, E>(
+    executor: E,
+    account_address: &str,
+    event_handle: &str,
+    field: &str,
+    event: &VersionedEvent,
+    error: &str,
+) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = DbDateTime::now();
+    let sequence_number_i64 = event.sequence_number.0 as i64;
+    let ledger_version_i64 = event.version.0 as i64;
+    let event_type = event.typ.to_string();
+    let data = event.data.to_string();
+    let schema_version = EVENT_SCHEMA_VERSION as i64;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO dead_letter_events (
+            account_address, event_handle, field, sequence_number,
+            ledger_version, event_type, data, schema_version, error, created_at
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (account_address, event_handle, field, sequence_number) DO UPDATE
+        SET schema_version = excluded.schema_version, error = excluded.error
+        "#,
+        account_address,
+        event_handle,
+        field,
+        sequence_number_i64,
+        ledger_version_i64,
+        event_type,
+        data,
+        schema_version,
+        error,
+        now
+    )
+    .execute(executor)
+    .await
+    .context("Failed to record dead letter event")?;
+
+    Ok(())
+}
+
+pub async fn get_dead_letter_events(
+    pool: &Pool<Sqlite>,
+    limit: i64,
+) -> Result<Vec<DeadLetterEvent>> {
+    let events = sqlx::query_as!(
+        DeadLetterEvent,
+        r#"
+        SELECT 
+            id as "id!", 
+            account_address as "account_address!", 
+            event_handle as "event_handle!", 
+            field as "field!", 
+            sequence_number as "sequence_number!", 
+            ledger_version as "ledger_version!", 
+            event_type as "event_type!", 
+            data as "data!", 
+            schema_version as "schema_version!", 
+            error as "error!", 
+            created_at as "created_at!"
+        FROM dead_letter_events
+        ORDER BY id DESC
+        LIMIT ?
+        "#,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get dead letter events")?;
+
+    Ok(events)
+}
+
+// Sync checkpoint operations
+
+pub async fn get_sync_checkpoint(
+    pool: &Pool<Sqlite>,
+    name: &str,
+) -> Result<Option<SyncCheckpoint>> {
+    Ok(sqlx::query_as!(
+        SyncCheckpoint,
+        r#"
+        SELECT 
+            name as "name!", 
+            ledger_version as "ledger_version!", 
+            ledger_tip as "ledger_tip!", 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM sync_checkpoints
+        WHERE name = ?
+        "#,
+        name
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get sync checkpoint")?)
+}
+
+/// Records a successful pass at `ledger_version`, read while the node was
+/// at `ledger_tip`.
+pub async fn save_sync_checkpoint<'e, E>(
+    executor: E,
+    name: &str,
+    ledger_version: u64,
+    ledger_tip: u64,
+) -> Result<()>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let now = DbDateTime::now();
+    let ledger_version = ledger_version as i64;
+    let ledger_tip = ledger_tip as i64;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO sync_checkpoints (name, ledger_version, ledger_tip, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT (name) DO UPDATE
+        SET ledger_version = excluded.ledger_version,
+            ledger_tip = excluded.ledger_tip,
+            updated_at = excluded.updated_at
+        "#,
+        name,
+        ledger_version,
+        ledger_tip,
+        now,
+        now
+    )
+    .execute(executor)
+    .await
+    .context("Failed to save sync checkpoint")?;
+
+    Ok(())
+}
+
+// Ledger version operations
+
+/// Tables whose derived rows are journaled by the `ledger_journal_*` triggers.
+const JOURNALED_TABLES: &[&str] = &[
+    "funds",
+    "assets",
+    "balances",
+    "fund_members",
+    "proposals",
+    "votes",
+    "positions",
+    "position_shares",
+    "event_cursors",
+];
+
+/// Highest ledger version any derived state has been applied at.
+pub async fn get_applied_ledger_version(pool: &Pool<Sqlite>) -> Result<Option<u64>> {
+    let version: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT MAX(version) FROM (
+            SELECT MAX(ledger_version) AS version FROM ledger_journal
+            UNION ALL
+            SELECT MAX(ledger_version) AS version FROM processed_events
+        )
+        "#,
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to get applied ledger version")?;
+
+    Ok(version.map(|v| v as u64))
+}
+
+/// Reverts all derived state above `version`: journaled changes are undone
+/// newest first, and the processed and dead-lettered events above it are
+/// forgotten so the listener applies them again once the node has them.
+pub async fn rollback_to_version(
+    conn: &mut SqliteConnection,
+    version: u64,
+) -> Result<RollbackSummary> {
+    let version_i64 = version as i64;
+    let mut tx = conn.begin().await?;
+
+    let entries = sqlx::query_as::<_, LedgerJournalEntry>(
+        r#"
+        SELECT id, ledger_version, table_name, row_id, operation, previous, created_at
+        FROM ledger_journal
+        WHERE ledger_version > ?
+        ORDER BY id DESC
+        "#,
+    )
+    .bind(version_i64)
+    .fetch_all(&mut *tx)
+    .await
+    .context("Failed to read ledger journal")?;
+
+    for entry in &entries {
+        revert_journal_entry(&mut tx, entry).await?;
+    }
+
+    // Reverting inserts and deletes journals again; drop those entries too
+    sqlx::query("DELETE FROM ledger_journal WHERE ledger_version > ?")
+        .bind(version_i64)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to trim ledger journal")?;
+
+    let processed = sqlx::query("DELETE FROM processed_events WHERE ledger_version > ?")
+        .bind(version_i64)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to roll back processed events")?;
+
+    let dead_letters = sqlx::query("DELETE FROM dead_letter_events WHERE ledger_version > ?")
+        .bind(version_i64)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to roll back dead letter events")?;
+
+    // Checkpoints past the rollback point would skip the reverted range
+    sqlx::query("UPDATE sync_checkpoints SET ledger_version = ? WHERE ledger_version > ?")
+        .bind(version_i64)
+        .bind(version_i64)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to roll back sync checkpoints")?;
+
+    tx.commit().await?;
+
+    Ok(RollbackSummary {
+        version,
+        reverted_changes: entries.len() as u64,
+        removed_events: processed.rows_affected() + dead_letters.rows_affected(),
+    })
+}
+
+async fn revert_journal_entry(conn: &mut SqliteConnection, entry: &LedgerJournalEntry) -> Result<()> {
+    let table = entry.table_name.as_str();
+    if !JOURNALED_TABLES.contains(&table) {
+        return Err(AppError::internal(format!("Journal entry {} names unknown table {}", entry.id, table)));
+    }
+
+    let statement = match (entry.operation.as_str(), &entry.previous) {
+        ("insert", _) => format!("DELETE FROM {} WHERE rowid = ?2", table),
+        ("update", Some(previous)) => {
+            let assignments: Vec<String> = journaled_columns(entry.id, previous)?
+                .iter()
+                .map(|c| format!("{c} = json_extract(?1, '$.{c}')"))
+                .collect();
+            format!("UPDATE {} SET {} WHERE rowid = ?2", table, assignments.join(", "))
+        }
+        ("delete", Some(previous)) => {
+            let columns = journaled_columns(entry.id, previous)?;
+            let values: Vec<String> = columns
+                .iter()
+                .map(|c| format!("json_extract(?1, '$.{c}')"))
+                .collect();
+            format!(
+                "INSERT INTO {} (rowid, {}) VALUES (?2, {})",
+                table,
+                columns.join(", "),
+                values.join(", ")
+            )
+        }
+        (operation, _) => {
+            return Err(AppError::internal(format!(
+                "Journal entry {} has unexpected operation {}",
+                entry.id, operation
+            )));
+        }
+    };
+
+    sqlx::query(&statement)
+        .bind(entry.previous.as_deref())
+        .bind(entry.row_id)
+        .execute(conn)
+        .await
+        .context("Failed to revert journaled change")?;
+
+    Ok(())
+}
+
+/// Column names of a journaled row image, without the `id` rowid alias
+/// since rows are addressed by rowid. They come from the triggers, but are
+/// checked anyway since they end up in SQL.
+fn journaled_columns(entry_id: i64, previous: &str) -> Result<Vec<String>> {
+    let image: serde_json::Map<String, serde_json::Value> = serde_json::from_str(previous)
+        .map_err(|e| AppError::internal(format!("Journal entry {} is not a row image: {}", entry_id, e)))?;
+
+    let columns: Vec<String> = image.keys().filter(|c| *c != "id").cloned().collect();
+    if columns.iter().any(|c| c.is_empty() || !c.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_')) {
+        return Err(AppError::internal(format!("Journal entry {} has invalid column names", entry_id)));
+    }
+
+    Ok(columns)
+}
+
+/// Drops journal entries at or below `version`; changes that old can no
+/// longer be rolled back.
+pub async fn prune_ledger_journal<'e, E>(executor: E, version: u64) -> Result<u64>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let version = version as i64;
+
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM ledger_journal
+        WHERE ledger_version <= ?
+        "#,
+        version
+    )
+    .execute(executor)
+    .await
+    .context("Failed to prune ledger journal")?;
+
+    Ok(result.rows_affected())
+}
+
+// Auth nonce operations
+
+pub async fn create_auth_nonce(
+    pool: &Pool<Sqlite>,
+    address: &str,
+    nonce: &str,
+    expires_at: DbDateTime,
+) -> Result<AuthNonce> {
+    let now = DbDateTime::now();
+
+    // Expired challenges are never accepted, so drop them as new ones arrive
+    sqlx::query!(
+        r#"
+        DELETE FROM auth_nonces
+        WHERE expires_at <= ?
+        "#,
+        now
+    )
+    .execute(pool)
+    .await
+    .context("Failed to prune auth nonces")?;
+
+    Ok(sqlx::query_as!(
+        AuthNonce,
+        r#"
+        INSERT INTO auth_nonces (nonce, address, expires_at, created_at)
+        VALUES (?, ?, ?, ?)
+        RETURNING 
+            nonce as "nonce!", 
+            address as "address!", 
+            expires_at as "expires_at!", 
+            used_at, 
+            created_at as "created_at!"
+        "#,
+        nonce,
+        address,
+        expires_at,
+        now
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to create auth nonce")?)
+}
+
+/// Marks an unexpired, unused nonce issued to `address` as used. Returns
+/// None when there is no such nonce, so each one admits a single attempt.
+pub async fn consume_auth_nonce(
+    pool: &Pool<Sqlite>,
+    address: &str,
+    nonce: &str,
+) -> Result<Option<AuthNonce>> {
+    let now = DbDateTime::now();
+
+    Ok(sqlx::query_as!(
+        AuthNonce,
+        r#"
+        UPDATE auth_nonces
+        SET used_at = ?
+        WHERE nonce = ? AND address = ? AND used_at IS NULL AND expires_at > ?
+        RETURNING 
+            nonce as "nonce!", 
+            address as "address!", 
+            expires_at as "expires_at!", 
+            used_at, 
+            created_at as "created_at!"
+        "#,
+        now,
+        nonce,
+        address,
+        now
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to consume auth nonce")?)
+}
+
+// Proposal execution operations
+
+/// Passed proposals in the `GovernanceData` at `chain_address` that have
+/// no execution in flight or done and fewer than `max_attempts` failed ones.
+pub async fn get_executable_proposals(
+    pool: &Pool<Sqlite>,
+    chain_address: &str,
+    max_attempts: i64,
+) -> Result<Vec<Proposal>> {
+    let proposals = sqlx::query_as!(
+        Proposal,
+        r#"
+        SELECT 
+            id as "id!", 
+            fund_id, 
+            title as "title!", 
+            description as "description!", 
+            end_time as "end_time!", 
+            status as "status!", 
+            executed as "executed!", 
+            vetoed as "vetoed!", 
+            chain_id as "chain_id!", 
+            chain_address,
+            synced as "synced!",
+            proposer_address,
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM proposals p
+        WHERE p.status = 'passed' AND p.synced AND p.chain_address = ?
+            AND NOT p.executed AND NOT p.vetoed
+            AND NOT EXISTS (
+                SELECT 1 FROM proposal_executions e
+                WHERE e.proposal_id = p.id AND e.status IN ('pending', 'succeeded')
+            )
+            AND (SELECT COUNT(*) FROM proposal_executions e WHERE e.proposal_id = p.id) < ?
+        ORDER BY p.end_time, p.id
+        "#,
+        chain_address,
+        max_attempts
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get executable proposals")?;
+
+    Ok(proposals)
+}
+
+/// Starts the next execution attempt for a proposal. Returns None when
+/// an attempt is already pending or one has succeeded.
+pub async fn create_proposal_execution(
+    pool: &Pool<Sqlite>,
+    proposal_id: i64,
+    sender_address: &str,
+) -> Result<Option<ProposalExecution>> {
+    let now = DbDateTime::now();
+
+    Ok(sqlx::query_as!(
+        ProposalExecution,
+        r#"
+        INSERT INTO proposal_executions (
+            proposal_id, attempt, sender_address, status, created_at, updated_at
+        )
+        SELECT ?, (
+            SELECT COALESCE(MAX(attempt), 0) + 1 FROM proposal_executions WHERE proposal_id = ?
+        ), ?, 'pending', ?, ?
+        WHERE NOT EXISTS (
+            SELECT 1 FROM proposal_executions
+            WHERE proposal_id = ? AND status IN ('pending', 'succeeded')
+        )
+        RETURNING 
+            id as "id!", 
+            proposal_id as "proposal_id!", 
+            attempt as "attempt!", 
+            sender_address as "sender_address!", 
+            status as "status!", 
+            txn_hash, 
+            gas_used, 
+            vm_status, 
+            error, 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        proposal_id,
+        proposal_id,
+        sender_address,
+        now,
+        now,
+        proposal_id
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to create proposal execution")?)
+}
+
+/// Records the hash of the transaction an attempt is about to submit, so
+/// its outcome can be looked up if the backend stops before it commits.
+pub async fn set_execution_txn_hash(
+    pool: &Pool<Sqlite>,
+    execution_id: i64,
+    txn_hash: &str,
+) -> Result<()> {
+    let now = DbDateTime::now();
+
+    sqlx::query!(
+        r#"
+        UPDATE proposal_executions
+        SET txn_hash = ?, updated_at = ?
+        WHERE id = ?
+        "#,
+        txn_hash,
+        now,
+        execution_id
+    )
+    .execute(pool)
+    .await
+    .context("Failed to record execution transaction")?;
+
+    Ok(())
+}
+
+pub async fn complete_proposal_execution(
+    pool: &Pool<Sqlite>,
+    execution_id: i64,
+    status: &str,
+    gas_used: Option<i64>,
+    vm_status: Option<&str>,
+    error: Option<&str>,
+) -> Result<ProposalExecution> {
+    let now = DbDateTime::now();
+
+    Ok(sqlx::query_as!(
+        ProposalExecution,
+        r#"
+        UPDATE proposal_executions
+        SET status = ?, gas_used = ?, vm_status = ?, error = ?, updated_at = ?
+        WHERE id = ?
+        RETURNING 
+            id as "id!", 
+            proposal_id as "proposal_id!", 
+            attempt as "attempt!", 
+            sender_address as "sender_address!", 
+            status as "status!", 
+            txn_hash, 
+            gas_used, 
+            vm_status, 
+            error, 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        status,
+        gas_used,
+        vm_status,
+        error,
+        now,
+        execution_id
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to complete proposal execution")?)
+}
+
+pub async fn get_pending_executions(pool: &Pool<Sqlite>) -> Result<Vec<ProposalExecution>> {
+    Ok(sqlx::query_as!(
+        ProposalExecution,
+        r#"
+        SELECT 
+            id as "id!", 
+            proposal_id as "proposal_id!", 
+            attempt as "attempt!", 
+            sender_address as "sender_address!", 
+            status as "status!", 
+            txn_hash, 
+            gas_used, 
+            vm_status, 
+            error, 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM proposal_executions
+        WHERE status = 'pending'
+        ORDER BY id
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get pending executions")?)
+}
+
+pub async fn get_proposal_executions(
+    pool: &Pool<Sqlite>,
+    proposal_id: i64,
+) -> Result<Vec<ProposalExecution>> {
+    Ok(sqlx::query_as!(
+        ProposalExecution,
+        r#"
+        SELECT 
+            id as "id!", 
+            proposal_id as "proposal_id!", 
+            attempt as "attempt!", 
+            sender_address as "sender_address!", 
+            status as "status!", 
+            txn_hash, 
+            gas_used, 
+            vm_status, 
+            error, 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM proposal_executions
+        WHERE proposal_id = ?
+        ORDER BY attempt
+        "#,
+        proposal_id
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to get proposal executions")?)
+}
+
+// Submitted transaction operations
+
+/// Starts tracking a relayed transaction. Relaying the same transaction
+/// again keeps the existing record.
+pub async fn create_submitted_transaction(
+    pool: &Pool<Sqlite>,
+    hash: &str,
+    sender_address: &str,
+    sequence_number: u64,
+    function: Option<&str>,
+) -> Result<SubmittedTransaction> {
+    let now = DbDateTime::now();
+    let sequence_number = sequence_number as i64;
+
+    Ok(sqlx::query_as!(
+        SubmittedTransaction,
+        r#"
+        INSERT INTO submitted_transactions (
+            hash, sender_address, sequence_number, function, status, created_at, updated_at
+        )
+        VALUES (?, ?, ?, ?, 'pending', ?, ?)
+        ON CONFLICT (hash) DO UPDATE SET updated_at = excluded.updated_at
+        RETURNING 
+            hash as "hash!", 
+            sender_address as "sender_address!", 
+            sequence_number as "sequence_number!", 
+            function, 
+            status as "status!", 
+            version, 
+            gas_used, 
+            vm_status, 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        hash,
+        sender_address,
+        sequence_number,
+        function,
+        now,
+        now
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to record submitted transaction")?)
+}
+
+pub async fn get_submitted_transaction(
+    pool: &Pool<Sqlite>,
+    hash: &str,
+) -> Result<Option<SubmittedTransaction>> {
+    Ok(sqlx::query_as!(
+        SubmittedTransaction,
+        r#"
+        SELECT 
+            hash as "hash!", 
+            sender_address as "sender_address!", 
+            sequence_number as "sequence_number!", 
+            function, 
+            status as "status!", 
+            version, 
+            gas_used, 
+            vm_status, 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        FROM submitted_transactions
+        WHERE hash = ?
+        "#,
+        hash
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to get submitted transaction")?)
+}
+
+pub async fn complete_submitted_transaction(
+    pool: &Pool<Sqlite>,
+    hash: &str,
+    status: &str,
+    version: u64,
+    gas_used: u64,
+    vm_status: &str,
+) -> Result<SubmittedTransaction> {
+    let now = DbDateTime::now();
+    let version = version as i64;
+    let gas_used = gas_used as i64;
+
+    Ok(sqlx::query_as!(
+        SubmittedTransaction,
+        r#"
+        UPDATE submitted_transactions
+        SET status = ?, version = ?, gas_used = ?, vm_status = ?, updated_at = ?
+        WHERE hash = ?
+        RETURNING 
+            hash as "hash!", 
+            sender_address as "sender_address!", 
+            sequence_number as "sequence_number!", 
+            function, 
+            status as "status!", 
+            version, 
+            gas_used, 
+            vm_status, 
+            created_at as "created_at!", 
+            updated_at as "updated_at!"
+        "#,
+        status,
+        version,
+        gas_used,
+        vm_status,
+        now,
+        hash
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to complete submitted transaction")?)
+}
@@ -1,9 +1,6 @@
-use sqlx::sqlite::SqlitePool;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
-use anyhow::Context;
 use crate::db::types::DbDateTime;
-use crate::error::Result;
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Fund {
@@ -31,7 +28,10 @@ pub struct FundMember {
 pub struct FundWallet {
     pub id: i64,
     pub fund_id: i64,
+    /// Account holding the fund's `asset::FundWallet` resource.
     pub wallet_address: String,
+    /// Account allowed to invest and withdraw on the fund's behalf.
+    pub actuator_address: String,
     pub created_at: DbDateTime,
     pub updated_at: DbDateTime,
 }
@@ -58,6 +58,37 @@ pub struct Message {
     pub updated_at: DbDateTime,
 }
 
+pub const POSITION_OPEN: &str = "open";
+pub const POSITION_CLOSED: &str = "closed";
+pub const POSITION_LIQUIDATED: &str = "liquidated";
+
+pub const PROPOSAL_ACTIVE: &str = "active";
+pub const PROPOSAL_PASSED: &str = "passed";
+pub const PROPOSAL_FAILED: &str = "failed";
+pub const PROPOSAL_EXECUTED: &str = "executed";
+pub const PROPOSAL_VETOED: &str = "vetoed";
+
+/// Every proposal status. Active proposals become passed or failed once
+/// voting closes; vetoed and executed are final.
+pub const PROPOSAL_STATUSES: &[&str] = &[
+    PROPOSAL_ACTIVE,
+    PROPOSAL_PASSED,
+    PROPOSAL_FAILED,
+    PROPOSAL_EXECUTED,
+    PROPOSAL_VETOED,
+];
+
+pub const EXECUTION_PENDING: &str = "pending";
+pub const EXECUTION_SUCCEEDED: &str = "succeeded";
+pub const EXECUTION_FAILED: &str = "failed";
+
+pub const TRANSACTION_PENDING: &str = "pending";
+pub const TRANSACTION_SUCCEEDED: &str = "succeeded";
+pub const TRANSACTION_FAILED: &str = "failed";
+
+/// Status of a member who currently holds a stake in the fund.
+pub const MEMBER_ACTIVE: &str = "active";
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Position {
     pub id: i64,
@@ -66,19 +97,58 @@ pub struct Position {
     pub size: i64,
     pub entry_price: i64,
     pub is_long: bool,
+    pub chain_position_id: Option<i64>,
+    pub status: String,
+    pub exit_price: Option<i64>,
+    pub pnl: Option<i64>,
+    pub closed_at: Option<DbDateTime>,
     pub created_at: DbDateTime,
     pub updated_at: DbDateTime,
 }
 
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct PositionShare {
+    pub id: i64,
+    pub chain_position_id: i64,
+    pub holder_address: String,
+    pub shares: i64,
+    pub entry_timestamp: DbDateTime,
+    pub last_updated: DbDateTime,
+    pub created_at: DbDateTime,
+    pub updated_at: DbDateTime,
+}
+
+/// A member's shares in one of a fund's positions, with the position
+/// details needed to judge exposure.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct MemberPositionShare {
+    pub position_id: i64,
+    pub chain_position_id: i64,
+    pub asset_id: i64,
+    pub status: String,
+    pub size: i64,
+    pub entry_price: i64,
+    pub is_long: bool,
+    pub shares: i64,
+    pub entry_timestamp: DbDateTime,
+    pub last_updated: DbDateTime,
+}
+
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Proposal {
     pub id: i64,
+    /// None for proposals recorded before they were tied to funds.
+    pub fund_id: Option<i64>,
     pub title: String,
     pub description: String,
     pub end_time: DbDateTime,
+    pub status: String,
     pub executed: bool,
     pub vetoed: bool,
     pub chain_id: i64,
+    /// Address of the `GovernanceData` holding the proposal on chain. None
+    /// until the proposal is linked to the chain.
+    pub chain_address: Option<String>,
     pub synced: bool,
     pub proposer_address: Option<String>,
     pub created_at: DbDateTime,
@@ -91,6 +161,9 @@ pub struct Vote {
     pub proposal_id: i64,
     pub voter_address: String,
     pub vote_type: bool,
+    pub voted_at: Option<DbDateTime>,
+    pub is_veto: bool,
+    pub on_chain: bool,
     pub created_at: DbDateTime,
     pub updated_at: DbDateTime,
 }
@@ -118,166 +191,121 @@ pub struct Balance {
     pub updated_at: DbDateTime,
 }
 
-pub async fn initialize_database(pool: &SqlitePool) -> Result<()> {
-    // Enable foreign keys
-    sqlx::query!("PRAGMA foreign_keys = ON;")
-        .execute(pool)
-        .await
-        .context("Failed to enable foreign keys")?;
-
-    // Create tables in the correct order (parents first)
-    sqlx::query!(
-        r#"
-        CREATE TABLE IF NOT EXISTS funds (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL UNIQUE,
-            executor_address TEXT NOT NULL,
-            version INTEGER NOT NULL DEFAULT 0,
-            status TEXT NOT NULL DEFAULT 'active',
-            description TEXT NOT NULL DEFAULT '',
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-        );
-
-        CREATE TABLE IF NOT EXISTS assets (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            symbol TEXT NOT NULL UNIQUE,
-            name TEXT NOT NULL,
-            decimals INTEGER NOT NULL,
-            version INTEGER NOT NULL DEFAULT 0,
-            address TEXT,
-            total_supply INTEGER NOT NULL DEFAULT 0,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-        );
-
-        CREATE TABLE IF NOT EXISTS proposals (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            title TEXT NOT NULL,
-            description TEXT NOT NULL,
-            end_time DATETIME NOT NULL,
-            executed BOOLEAN NOT NULL DEFAULT FALSE,
-            vetoed BOOLEAN NOT NULL DEFAULT FALSE,
-            chain_id INTEGER NOT NULL DEFAULT 0,
-            synced BOOLEAN NOT NULL DEFAULT FALSE,
-            proposer_address TEXT,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-        );
-        "#
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create primary tables")?;
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct EventCursor {
+    pub stream: String,
+    pub next_sequence: i64,
+    pub created_at: DbDateTime,
+    pub updated_at: DbDateTime,
+}
 
-    // Create tables with foreign key constraints
-    sqlx::query!(
-        r#"
-        CREATE TABLE IF NOT EXISTS fund_members (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            fund_id INTEGER NOT NULL,
-            member_address TEXT NOT NULL,
-            share INTEGER NOT NULL DEFAULT 0,
-            status TEXT NOT NULL DEFAULT 'active',
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (fund_id) REFERENCES funds(id),
-            UNIQUE(fund_id, member_address)
-        );
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ProcessedEvent {
+    pub id: i64,
+    pub account_address: String,
+    pub event_handle: String,
+    pub field: String,
+    pub sequence_number: i64,
+    pub ledger_version: i64,
+    pub event_type: String,
+    pub data: String,
+    pub created_at: DbDateTime,
+}
 
-        CREATE TABLE IF NOT EXISTS fund_wallets (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            fund_id INTEGER NOT NULL,
-            wallet_address TEXT NOT NULL,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (fund_id) REFERENCES funds(id),
-            UNIQUE(fund_id)
-        );
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DeadLetterEvent {
+    pub id: i64,
+    pub account_address: String,
+    pub event_handle: String,
+    pub field: String,
+    pub sequence_number: i64,
+    pub ledger_version: i64,
+    pub event_type: String,
+    pub data: String,
+    pub schema_version: i64,
+    pub error: String,
+    pub created_at: DbDateTime,
+}
 
-        CREATE TABLE IF NOT EXISTS investments (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            fund_id INTEGER NOT NULL,
-            asset_id INTEGER NOT NULL,
-            amount INTEGER NOT NULL,
-            withdrawn_amount INTEGER NOT NULL DEFAULT 0,
-            investor_address TEXT NOT NULL,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (fund_id) REFERENCES funds(id),
-            FOREIGN KEY (asset_id) REFERENCES assets(id)
-        );
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct SyncCheckpoint {
+    pub name: String,
+    pub ledger_version: i64,
+    pub ledger_tip: i64,
+    pub created_at: DbDateTime,
+    pub updated_at: DbDateTime,
+}
 
-        CREATE TABLE IF NOT EXISTS messages (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            fund_id INTEGER NOT NULL,
-            sender_address TEXT NOT NULL,
-            content TEXT NOT NULL,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (fund_id) REFERENCES funds(id)
-        );
+/// A checkpoint together with how far it trails the node, in versions.
+/// `ledger_tip` and `lag` are None when the node could not be reached.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub checkpoint: Option<SyncCheckpoint>,
+    pub ledger_tip: Option<u64>,
+    pub lag: Option<u64>,
+}
 
-        CREATE TABLE IF NOT EXISTS positions (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            fund_id INTEGER NOT NULL,
-            asset_id INTEGER NOT NULL,
-            size INTEGER NOT NULL,
-            entry_price INTEGER NOT NULL,
-            is_long BOOLEAN NOT NULL,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (fund_id) REFERENCES funds(id),
-            FOREIGN KEY (asset_id) REFERENCES assets(id)
-        );
+/// A derived-state change recorded by the `ledger_journal_*` triggers.
+/// `previous` holds the row image before the change, or None for inserts.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct LedgerJournalEntry {
+    pub id: i64,
+    pub ledger_version: i64,
+    pub table_name: String,
+    pub row_id: i64,
+    pub operation: String,
+    pub previous: Option<String>,
+    pub created_at: DbDateTime,
+}
 
-        CREATE TABLE IF NOT EXISTS votes (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            proposal_id INTEGER NOT NULL,
-            voter_address TEXT NOT NULL,
-            vote_type BOOLEAN NOT NULL,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (proposal_id) REFERENCES proposals(id),
-            UNIQUE(proposal_id, voter_address)
-        );
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RollbackSummary {
+    pub version: u64,
+    pub reverted_changes: u64,
+    pub removed_events: u64,
+}
 
-        CREATE TABLE IF NOT EXISTS balances (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            asset_id INTEGER NOT NULL,
-            holder_address TEXT NOT NULL,
-            amount INTEGER NOT NULL,
-            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (asset_id) REFERENCES assets(id),
-            UNIQUE(asset_id, holder_address)
-        );
-        "#
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create tables with foreign keys")?;
+/// A login challenge issued to `address`. `used_at` is set once a login
+/// attempt has presented it, successful or not.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct AuthNonce {
+    pub nonce: String,
+    pub address: String,
+    pub expires_at: DbDateTime,
+    pub used_at: Option<DbDateTime>,
+    pub created_at: DbDateTime,
+}
 
-    // Create indices
-    sqlx::query!(
-        r#"
-        CREATE INDEX IF NOT EXISTS idx_funds_version ON funds(version);
-        CREATE INDEX IF NOT EXISTS idx_assets_version ON assets(version);
-        CREATE INDEX IF NOT EXISTS idx_fund_members_status ON fund_members(status);
-        CREATE INDEX IF NOT EXISTS idx_assets_address ON assets(address);
-        CREATE INDEX IF NOT EXISTS idx_fund_members_fund_id ON fund_members(fund_id);
-        CREATE INDEX IF NOT EXISTS idx_fund_wallets_fund_id ON fund_wallets(fund_id);
-        CREATE INDEX IF NOT EXISTS idx_investments_fund_id ON investments(fund_id);
-        CREATE INDEX IF NOT EXISTS idx_investments_asset_id ON investments(asset_id);
-        CREATE INDEX IF NOT EXISTS idx_messages_fund_id ON messages(fund_id, id DESC);
-        CREATE INDEX IF NOT EXISTS idx_positions_fund_id ON positions(fund_id);
-        CREATE INDEX IF NOT EXISTS idx_votes_proposal_id ON votes(proposal_id);
-        CREATE INDEX IF NOT EXISTS idx_balances_asset_id ON balances(asset_id);
-        "#
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create indices")?;
+/// One attempt at executing a proposal on chain. `txn_hash` is set once
+/// the transaction was submitted; `gas_used` and `vm_status` once it was
+/// committed.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ProposalExecution {
+    pub id: i64,
+    pub proposal_id: i64,
+    pub attempt: i64,
+    pub sender_address: String,
+    pub status: String,
+    pub txn_hash: Option<String>,
+    pub gas_used: Option<i64>,
+    pub vm_status: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DbDateTime,
+    pub updated_at: DbDateTime,
+}
 
-    Ok(())
-} 
+/// A wallet-signed transaction relayed to the chain. `version`,
+/// `gas_used` and `vm_status` are set once it has committed.
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct SubmittedTransaction {
+    pub hash: String,
+    pub sender_address: String,
+    pub sequence_number: i64,
+    pub function: Option<String>,
+    pub status: String,
+    pub version: Option<i64>,
+    pub gas_used: Option<i64>,
+    pub vm_status: Option<String>,
+    pub created_at: DbDateTime,
+    pub updated_at: DbDateTime,
+}
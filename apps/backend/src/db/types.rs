@@ -34,6 +34,12 @@ impl DbDateTime {
         Self(Utc::now().naive_utc())
     }
 
+    /// Converts an on-chain timestamp in microseconds.
+    pub fn from_timestamp_micros(micros: u64) -> Option<Self> {
+        let micros = i64::try_from(micros).ok()?;
+        NaiveDateTime::from_timestamp_micros(micros).map(Self)
+    }
+
     pub fn into_datetime(self) -> DateTime<Utc> {
         DateTime::from_naive_utc_and_offset(self.0, Utc)
     }
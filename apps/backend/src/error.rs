@@ -1,7 +1,11 @@
+use actix_web::{http::{header, StatusCode}, HttpResponse, ResponseError};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use url::ParseError;
 use sqlx::error::Error as SqlxError;
 use aptos_sdk::move_types::account_address::AccountAddressParseError;
+use aptos_sdk::rest_client::{aptos_api_types::AptosErrorCode, error::RestError};
+use std::time::Duration;
 
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -17,9 +21,75 @@ pub enum AppError {
     #[error("Unauthorized: {0}")]
     Unauthorized(&'static str),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Already exists: {0}")]
+    AlreadyExists(String),
+
+    /// The request does not fit the resource's current state.
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Insufficient {symbol} balance for {holder}: requested {requested}")]
+    InsufficientBalance {
+        holder: String,
+        symbol: String,
+        requested: u64,
+    },
+
+    #[error("Insufficient shares of position {position_id} for {holder}: requested {requested}")]
+    InsufficientShares {
+        holder: String,
+        position_id: u64,
+        requested: u64,
+    },
+
     #[error("Blockchain error: {0}")]
     Blockchain(String),
 
+    /// The node rejected a transaction's sequence number as already used
+    /// or too far ahead.
+    #[error("Sequence number rejected: {0}")]
+    SequenceNumber(String),
+
+    /// Simulation showed the transaction would fail, so it was not sent.
+    #[error("Transaction would fail: {0}")]
+    SimulationFailed(String),
+
+    /// A failure that may succeed on another attempt or another node.
+    #[error("Transient error: {message}")]
+    Transient {
+        message: String,
+        retry_after: Option<Duration>,
+        /// The node answered 429 or 503, so it may say how long to wait.
+        throttled: bool,
+    },
+
+    #[error("Not implemented: {0}")]
+    NotImplemented(&'static str),
+
+    #[error("Rate limit exceeded")]
+    RateLimited,
+
+    #[error("All nodes are unhealthy")]
+    NoHealthyNodes,
+
+    #[error("Chain ID mismatch: expected {expected}, got {actual}")]
+    ChainIdMismatch {
+        expected: u8,
+        actual: u8,
+    },
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Migration error: {0}")]
+    Migration(String),
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -43,12 +113,120 @@ impl AppError {
     pub fn internal(message: impl ToString) -> Self {
         AppError::Internal(message.to_string())
     }
+
+    pub fn transient(message: impl ToString) -> Self {
+        AppError::Transient {
+            message: message.to_string(),
+            retry_after: None,
+            throttled: false,
+        }
+    }
+
+    /// A 429 or 503 from a node, whose `Retry-After` is still to be read.
+    pub fn throttled(message: impl ToString) -> Self {
+        AppError::Transient {
+            message: message.to_string(),
+            retry_after: None,
+            throttled: true,
+        }
+    }
+
+    /// Whether retrying the operation can help.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, AppError::Transient { .. })
+    }
+
+    /// How long the server asked us to wait before retrying, if it did.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AppError::Transient { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Classifies a REST client failure. Timeouts, connection failures,
+    /// 5xx, 429 and an overloaded node are transient; missing data, bad
+    /// input and VM rejections are terminal. The REST client does not
+    /// surface response headers, so throttled responses are marked for the
+    /// caller to fetch `Retry-After`.
+    pub fn from_rest_error(context: &str, error: RestError) -> Self {
+        let message = format!("{}: {}", context, error);
+
+        match &error {
+            RestError::Api(response) => match response.error.error_code {
+                AptosErrorCode::AccountNotFound
+                | AptosErrorCode::ResourceNotFound
+                | AptosErrorCode::ModuleNotFound
+                | AptosErrorCode::StructFieldNotFound
+                | AptosErrorCode::VersionNotFound
+                | AptosErrorCode::TransactionNotFound
+                | AptosErrorCode::TableItemNotFound
+                | AptosErrorCode::BlockNotFound
+                | AptosErrorCode::StateValueNotFound => AppError::NotFound(message),
+                AptosErrorCode::InvalidInput
+                | AptosErrorCode::InvalidTransactionUpdate => AppError::InvalidInput(message),
+                AptosErrorCode::SequenceNumberTooOld => AppError::SequenceNumber(message),
+                AptosErrorCode::VmError if is_sequence_number_status(&message) => {
+                    AppError::SequenceNumber(message)
+                }
+                AptosErrorCode::VmError => AppError::Blockchain(message),
+                AptosErrorCode::MempoolIsFull
+                | AptosErrorCode::HealthCheckFailed
+                | AptosErrorCode::InternalError => AppError::transient(message),
+                _ => AppError::Blockchain(message),
+            },
+            RestError::Http(status, _) => {
+                let code = status.as_u16();
+                if code == 429 || code == 503 {
+                    AppError::throttled(message)
+                } else if code == 408 || status.is_server_error() {
+                    AppError::transient(message)
+                } else if code == 404 {
+                    AppError::NotFound(message)
+                } else {
+                    AppError::InvalidInput(message)
+                }
+            }
+            RestError::Timeout(_) => AppError::transient(message),
+            RestError::Unknown(source) => {
+                let network = source
+                    .downcast_ref::<reqwest::Error>()
+                    .is_some_and(|e| e.is_timeout() || e.is_connect() || e.is_request());
+
+                if network {
+                    AppError::transient(message)
+                } else {
+                    AppError::Internal(message)
+                }
+            }
+            _ => AppError::Internal(message),
+        }
+    }
+}
+
+/// Whether a VM status rejects the transaction's sequence number.
+pub fn is_sequence_number_status(status: &str) -> bool {
+    status.contains("SEQUENCE_NUMBER_TOO_OLD") || status.contains("SEQUENCE_NUMBER_TOO_NEW")
 }
 
 // Implement conversion from anyhow::Error
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
-        AppError::Internal(err.to_string())
+        // Keep the meaning of database errors wrapped with context
+        err.downcast_ref::<SqlxError>()
+            .and_then(|e| classify_sqlx_error(e, err.to_string()))
+            .unwrap_or_else(|| AppError::Internal(err.to_string()))
+    }
+}
+
+/// Database failures that are the caller's fault rather than ours.
+fn classify_sqlx_error(error: &SqlxError, message: String) -> Option<AppError> {
+    match error {
+        SqlxError::RowNotFound => Some(AppError::NotFound(message)),
+        SqlxError::Database(e) if e.is_unique_violation() => Some(AppError::AlreadyExists(message)),
+        // A dangling reference means the parent row does not exist
+        SqlxError::Database(e) if e.is_foreign_key_violation() => Some(AppError::NotFound(message)),
+        _ => None,
     }
 }
 
@@ -60,27 +238,27 @@ impl From<ParseError> for AppError {
 
 impl AppError {
     pub fn connection_error(message: &str) -> Self {
-        AppError::Internal(format!("Connection error: {}", message))
+        AppError::transient(format!("Connection error: {}", message))
     }
 
     pub fn transaction_error(message: &str) -> Self {
-        AppError::Internal(format!("Transaction error: {}", message))
+        AppError::Blockchain(format!("Transaction error: {}", message))
     }
 
     pub fn rate_limit_exceeded() -> Self {
-        AppError::Internal("Rate limit exceeded".to_string())
+        AppError::RateLimited
     }
 
     pub fn health_check_failed(message: &str) -> Self {
-        AppError::Internal(format!("Node health check failed: {}", message))
+        AppError::transient(format!("Node health check failed: {}", message))
     }
 
     pub fn no_healthy_nodes() -> Self {
-        AppError::Internal("All nodes are unhealthy".to_string())
+        AppError::NoHealthyNodes
     }
 
     pub fn config_error(message: &str) -> Self {
-        AppError::Internal(format!("Configuration error: {}", message))
+        AppError::Config(message.to_string())
     }
 
     pub fn database_error(message: &str) -> Self {
@@ -88,40 +266,140 @@ impl AppError {
     }
 
     pub fn invalid_request(message: &str) -> Self {
-        AppError::Internal(format!("Invalid request: {}", message))
+        AppError::InvalidInput(message.to_string())
     }
 
     pub fn network_error(message: &str) -> Self {
-        AppError::Internal(format!("Network error: {}", message))
+        AppError::transient(format!("Network error: {}", message))
     }
 
     pub fn serialization_error(message: &str) -> Self {
-        AppError::Internal(format!("Serialization error: {}", message))
+        AppError::Serialization(message.to_string())
     }
 
     pub fn deserialization_error(message: &str) -> Self {
-        AppError::Internal(format!("Deserialization error: {}", message))
+        AppError::Serialization(format!("Deserialization error: {}", message))
     }
 
     pub fn event_subscription_error(message: &str) -> Self {
-        AppError::Internal(format!("Event subscription error: {}", message))
+        AppError::Blockchain(format!("Event subscription error: {}", message))
     }
 
     pub fn chain_id_mismatch(expected: u8, actual: u8) -> Self {
-        AppError::Internal(format!("Chain ID mismatch: expected {}, got {}", expected, actual))
+        AppError::ChainIdMismatch { expected, actual }
     }
 
     pub fn account_error(message: &str) -> Self {
-        AppError::Internal(format!("Account error: {}", message))
+        AppError::Blockchain(format!("Account error: {}", message))
     }
 
     pub fn validation_error(message: &str) -> Self {
-        AppError::Internal(format!("Validation error: {}", message))
+        AppError::InvalidInput(message.to_string())
     }
 
     pub fn transaction_isolation_error(message: &str) -> Self {
         AppError::Internal(format!("Database transaction error: {}", message))
     }
+
+    pub fn migration_error(message: &str) -> Self {
+        AppError::Migration(message.to_string())
+    }
+
+    /// Message sent to clients. Raw database errors name tables and
+    /// constraints, so they are only logged.
+    fn public_message(&self) -> String {
+        match self {
+            AppError::Database(_) => match self.code() {
+                "not_found" => "Record not found".to_string(),
+                "already_exists" => "Record already exists".to_string(),
+                _ => "Internal database error".to_string(),
+            },
+            _ => self.to_string(),
+        }
+    }
+
+    /// Stable, machine-readable identifier of the error kind. Clients
+    /// match on this rather than on messages.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(e) => {
+                classify_sqlx_error(e, String::new()).map_or("database_error", |classified| classified.code())
+            }
+            AppError::NotFound(_) => "not_found",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::AlreadyExists(_) => "already_exists",
+            AppError::Conflict(_) => "conflict",
+            AppError::InsufficientBalance { .. } => "insufficient_balance",
+            AppError::InsufficientShares { .. } => "insufficient_shares",
+            AppError::Blockchain(_) => "blockchain_error",
+            AppError::SequenceNumber(_) => "sequence_number_rejected",
+            AppError::SimulationFailed(_) => "simulation_failed",
+            AppError::Transient { .. } => "node_unavailable",
+            AppError::NotImplemented(_) => "not_implemented",
+            AppError::RateLimited => "rate_limited",
+            AppError::NoHealthyNodes => "no_healthy_nodes",
+            AppError::ChainIdMismatch { .. } => "chain_id_mismatch",
+            AppError::Config(_) => "config_error",
+            AppError::Migration(_) => "migration_error",
+            AppError::Serialization(_) => "serialization_error",
+            AppError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+/// Body of every error response.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorEnvelope {
+    pub error: ErrorBody,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub code: String,
+    pub message: String,
+    pub request_id: Option<String>,
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self.code() {
+            "not_found" => StatusCode::NOT_FOUND,
+            "invalid_input" => StatusCode::BAD_REQUEST,
+            "unauthorized" => StatusCode::UNAUTHORIZED,
+            "forbidden" => StatusCode::FORBIDDEN,
+            "already_exists" | "conflict" | "sequence_number_rejected" => StatusCode::CONFLICT,
+            "insufficient_balance" | "insufficient_shares" | "simulation_failed" => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            "not_implemented" => StatusCode::NOT_IMPLEMENTED,
+            "rate_limited" => StatusCode::TOO_MANY_REQUESTS,
+            "node_unavailable" | "no_healthy_nodes" => StatusCode::SERVICE_UNAVAILABLE,
+            "blockchain_error" | "chain_id_mismatch" => StatusCode::BAD_GATEWAY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let status = self.status_code();
+        if status.is_server_error() {
+            log::error!("Request failed: {}", self);
+        }
+
+        let mut response = HttpResponse::build(status);
+        if let Some(retry_after) = self.retry_after() {
+            response.insert_header((header::RETRY_AFTER, retry_after.as_secs().max(1).to_string()));
+        }
+
+        response.json(ErrorEnvelope {
+            error: ErrorBody {
+                code: self.code().to_string(),
+                message: self.public_message(),
+                request_id: crate::api::middleware::request_id::current(),
+            },
+        })
+    }
 }
 
 impl From<std::io::Error> for AppError {
@@ -0,0 +1,270 @@
+use aptos_sdk::{
+    rest_client::Transaction,
+    types::{account_address::AccountAddress, LocalAccount},
+};
+use log::{error, info, warn};
+use tokio::time::sleep;
+use crate::{
+    AppState,
+    config::ExecutionConfig,
+    db::{
+        operations,
+        schema::{Proposal, ProposalExecution, EXECUTION_FAILED, EXECUTION_SUCCEEDED, PROPOSAL_PASSED},
+    },
+    error::{AppError, Result},
+    move_events,
+    transactions::{EntryFunctionCall, TransactionParams},
+};
+
+/// Submits `governance::execute_proposal` for proposals that passed,
+/// signed by the configured executor account.
+pub struct ProposalExecutor {
+    state: AppState,
+    config: ExecutionConfig,
+    /// None when no executor key is configured.
+    signer: Option<LocalAccount>,
+}
+
+impl ProposalExecutor {
+    pub fn new(state: AppState, config: ExecutionConfig) -> Result<Self> {
+        let signer = match &config.private_key {
+            Some(key) => {
+                let account = LocalAccount::from_private_key(key, 0)
+                    .map_err(|e| AppError::config_error(&format!("execution.private_key: {}", e)))?;
+                Some(account)
+            }
+            None => None,
+        };
+
+        Ok(Self { state, config, signer })
+    }
+
+    /// Account executions are sent from, if one is configured.
+    pub fn sender(&self) -> Option<AccountAddress> {
+        self.signer.as_ref().map(|signer| signer.address())
+    }
+
+    /// Where `GovernanceData`, and so every proposal, lives on chain.
+    fn governance_address(&self) -> String {
+        self.state.client.modules().module_address("governance").to_hex_literal()
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        let Some(sender) = self.sender() else {
+            warn!("No executor key configured; passed proposals will not be executed");
+            return Ok(());
+        };
+
+        info!("Starting proposal executor for {}", sender.to_hex_literal());
+        if let Err(e) = self.recover_pending().await {
+            error!("Error recovering pending executions: {}", e);
+        }
+
+        loop {
+            if let Err(e) = self.execute_due().await {
+                error!("Error executing proposals: {}", e);
+            }
+            sleep(self.config.execute_interval).await;
+        }
+    }
+
+    /// Executes every passed proposal that has attempts left. Returns how
+    /// many executions succeeded.
+    pub async fn execute_due(&self) -> Result<usize> {
+        let max_attempts = i64::from(self.config.max_attempts);
+        let proposals = operations::get_executable_proposals(
+            &self.state.db,
+            &self.governance_address(),
+            max_attempts,
+        ).await?;
+
+        let mut executed = 0;
+        for proposal in &proposals {
+            match self.execute(proposal).await {
+                Ok(execution) if execution.status == EXECUTION_SUCCEEDED => {
+                    info!("Proposal {} executed in {:?}", proposal.id, execution.txn_hash);
+                    executed += 1;
+                }
+                Ok(execution) => warn!(
+                    "Attempt {} to execute proposal {} failed: {}",
+                    execution.attempt,
+                    proposal.id,
+                    execution.vm_status.or(execution.error).unwrap_or_default()
+                ),
+                Err(e) => error!("Error executing proposal {}: {}", proposal.id, e),
+            }
+        }
+
+        Ok(executed)
+    }
+
+    /// Submits one execution attempt for `proposal` and waits for it to
+    /// commit. Failed transactions are recorded, not returned as errors.
+    pub async fn execute(&self, proposal: &Proposal) -> Result<ProposalExecution> {
+        let Some(signer) = &self.signer else {
+            return Err(AppError::NotImplemented("Proposal execution requires an executor key"));
+        };
+        if proposal.status != PROPOSAL_PASSED {
+            return Err(AppError::InvalidInput(format!(
+                "Proposal {} is {}, only passed proposals are executed",
+                proposal.id, proposal.status
+            )));
+        }
+        // Its chain id only means something in the governance it came from
+        let governance_address = self.governance_address();
+        if !proposal.synced || proposal.chain_address.as_deref() != Some(governance_address.as_str()) {
+            return Err(AppError::InvalidInput(format!(
+                "Proposal {} has not been created on chain",
+                proposal.id
+            )));
+        }
+
+        let execution = operations::create_proposal_execution(
+            &self.state.db,
+            proposal.id,
+            &signer.address().to_hex_literal(),
+        )
+        .await?
+        .ok_or_else(|| AppError::AlreadyExists(format!("Execution of proposal {}", proposal.id)))?;
+
+        match self.submit(signer, &execution, proposal.chain_id as u64).await {
+            Ok(txn) => self.record_committed(&execution, &txn).await,
+            Err(e) => {
+                operations::complete_proposal_execution(
+                    &self.state.db,
+                    execution.id,
+                    EXECUTION_FAILED,
+                    None,
+                    None,
+                    Some(&e.to_string()),
+                ).await
+            }
+        }
+    }
+
+    async fn submit(
+        &self,
+        signer: &LocalAccount,
+        execution: &ProposalExecution,
+        chain_proposal_id: u64,
+    ) -> Result<Transaction> {
+        let client = &self.state.client;
+        let sender = signer.address();
+        let call = EntryFunctionCall::ExecuteProposal { proposal_id: chain_proposal_id };
+        let params = TransactionParams::new(sender, 0, client.get_chain_id().await?)
+            .with_max_gas_amount(self.config.max_gas_amount)
+            .with_gas_unit_price(self.config.gas_unit_price)
+            .with_ttl(self.config.transaction_ttl);
+
+        let mut resynced = false;
+        let (pending, sequence_number) = loop {
+            let sequence_number = client
+                .reserve_sequence_number(sender, params.expiration_timestamp_secs)
+                .await?;
+            let raw = call.raw_transaction(client.modules(), &params.with_sequence_number(sequence_number))?;
+            let txn = signer.sign_transaction(raw);
+
+            let txn_hash = txn.committed_hash().to_hex_literal();
+            operations::set_execution_txn_hash(&self.state.db, execution.id, &txn_hash).await?;
+
+            match client.submit_simulated(txn).await {
+                Ok(pending) => break (pending, sequence_number),
+                // The node saw the number as already used, so the local
+                // count drifted; one retry with a number from the resynced
+                // count
+                Err(AppError::SequenceNumber(e)) if !resynced => {
+                    warn!(
+                        "Sequence number {} of {} rejected, resyncing: {}",
+                        sequence_number,
+                        sender.to_hex_literal(),
+                        e
+                    );
+                    client.sequence_numbers().resync(sender).await;
+                    resynced = true;
+                }
+                Err(e) => {
+                    client.release_sequence_number(sender, sequence_number, &e).await;
+                    return Err(e);
+                }
+            }
+        };
+
+        let committed = match client.wait_for_transaction(&pending).await {
+            Ok(txn) => Ok(txn),
+            // The REST client reports failed transactions as errors; their
+            // outcome is still worth recording
+            Err(e) => match client.get_transaction_status(&pending.hash.to_string()).await {
+                Ok(txn) if !txn.is_pending() => Ok(txn),
+                _ => Err(e),
+            },
+        };
+        // Aborted transactions use up their number too
+        if committed.is_ok() {
+            client.sequence_numbers().complete(sender, sequence_number).await;
+        }
+        committed
+    }
+
+    async fn record_committed(
+        &self,
+        execution: &ProposalExecution,
+        txn: &Transaction,
+    ) -> Result<ProposalExecution> {
+        let gas_used = txn
+            .transaction_info()
+            .map(|info| info.gas_used.0 as i64)
+            .ok();
+        // A committed call only means the proposal was executed if the
+        // chain's own quorum check passed; otherwise it stays executable
+        let (status, error) = if !txn.success() {
+            (EXECUTION_FAILED, None)
+        } else {
+            match move_events::proposal_executed(txn) {
+                Some(executed) if executed.success => (EXECUTION_SUCCEEDED, None),
+                Some(_) => (EXECUTION_FAILED, Some("Proposal did not pass the on-chain quorum check")),
+                None => (EXECUTION_FAILED, Some("Transaction emitted no ProposalExecutedEvent")),
+            }
+        };
+
+        operations::complete_proposal_execution(
+            &self.state.db,
+            execution.id,
+            status,
+            gas_used,
+            Some(&txn.vm_status()),
+            error,
+        ).await
+    }
+
+    /// Settles attempts left pending by a previous run: committed ones are
+    /// recorded, the rest are marked failed so they can be retried.
+    async fn recover_pending(&self) -> Result<()> {
+        for execution in operations::get_pending_executions(&self.state.db).await? {
+            let committed = match &execution.txn_hash {
+                Some(txn_hash) => match self.state.client.get_transaction_status(txn_hash).await {
+                    Ok(txn) if !txn.is_pending() => Some(txn),
+                    _ => None,
+                },
+                None => None,
+            };
+
+            match committed {
+                Some(txn) => {
+                    self.record_committed(&execution, &txn).await?;
+                }
+                None => {
+                    operations::complete_proposal_execution(
+                        &self.state.db,
+                        execution.id,
+                        EXECUTION_FAILED,
+                        None,
+                        None,
+                        Some("Interrupted before the transaction committed"),
+                    ).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod execution;
+pub mod tally;
+
+pub use execution::ProposalExecutor;
+pub use tally::{Outcome, Tally, TallyService};
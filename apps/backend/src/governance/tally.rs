@@ -0,0 +1,211 @@
+use aptos_sdk::types::account_address::AccountAddress;
+use log::{error, info};
+use serde::Serialize;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    str::FromStr,
+};
+use tokio::time::sleep;
+use crate::{
+    config::GovernanceConfig,
+    db::{
+        operations,
+        schema::{FundMember, Proposal, Vote, MEMBER_ACTIVE, PROPOSAL_ACTIVE, PROPOSAL_FAILED, PROPOSAL_PASSED},
+        types::DbDateTime,
+        Pool,
+    },
+    error::Result,
+};
+
+/// `EMERGENCY_VETO_THRESHOLD` in `governance.move`. The chain rejects
+/// vetoes below it whatever the configured veto threshold says.
+pub const EMERGENCY_VETO_THRESHOLD: u64 = 30;
+
+/// How a proposal ends once its voting closes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Passed,
+    Failed,
+}
+
+impl Outcome {
+    pub fn status(&self) -> &'static str {
+        match self {
+            Outcome::Passed => PROPOSAL_PASSED,
+            Outcome::Failed => PROPOSAL_FAILED,
+        }
+    }
+}
+
+/// Votes on a proposal weighted by each voter's share, in basis points.
+/// Only active members of the proposal's fund carry weight.
+#[derive(Debug, Clone, Serialize)]
+pub struct Tally {
+    pub proposal_id: i64,
+    pub status: String,
+    /// Combined share of the fund's active members.
+    pub eligible_weight: u64,
+    pub yes_weight: u64,
+    pub no_weight: u64,
+    pub counted_votes: usize,
+    /// Votes recorded for accounts that are not active members.
+    pub ignored_votes: usize,
+    /// Whether `end_time` has passed.
+    pub closed: bool,
+    /// The outcome if voting closed now, or the final one once it has.
+    pub projected: Outcome,
+    pub emergency_veto_allowed: bool,
+}
+
+impl Tally {
+    /// Weighs `votes` on `proposal` by the shares of `members`.
+    pub fn compute(
+        config: &GovernanceConfig,
+        proposal: &Proposal,
+        members: &[FundMember],
+        votes: &[Vote],
+    ) -> Self {
+        let weights: HashMap<AccountAddress, u64> = members
+            .iter()
+            .filter(|m| m.status == MEMBER_ACTIVE)
+            .filter_map(|m| Some((parse_address(&m.member_address)?, m.share.max(0) as u64)))
+            .collect();
+
+        // A voter may have a local and an indexed vote stored in different
+        // address forms; only one of them counts
+        let mut ballots: HashMap<AccountAddress, &Vote> = HashMap::new();
+        let mut ignored_votes = 0;
+        for vote in votes {
+            let Some(voter) = parse_address(&vote.voter_address) else {
+                ignored_votes += 1;
+                continue;
+            };
+
+            match ballots.entry(voter) {
+                Entry::Occupied(mut ballot) => {
+                    if supersedes(vote, ballot.get()) {
+                        ballot.insert(vote);
+                    }
+                }
+                Entry::Vacant(ballot) => {
+                    ballot.insert(vote);
+                }
+            }
+        }
+
+        let mut yes_weight = 0;
+        let mut no_weight = 0;
+        let mut counted_votes = 0;
+        for (voter, vote) in &ballots {
+            let Some(weight) = weights.get(voter) else {
+                ignored_votes += 1;
+                continue;
+            };
+
+            if vote.vote_type {
+                yes_weight += weight;
+            } else {
+                no_weight += weight;
+            }
+            counted_votes += 1;
+        }
+
+        let cast_weight = yes_weight + no_weight;
+        // Mirrors the pass check in `governance::execute_proposal`
+        let projected = if cast_weight > 0 && yes_weight * 100 > cast_weight * config.quorum_threshold {
+            Outcome::Passed
+        } else {
+            Outcome::Failed
+        };
+        // Mirrors the check in `governance::emergency_veto`
+        let veto_threshold = config.veto_threshold.max(EMERGENCY_VETO_THRESHOLD);
+
+        Self {
+            proposal_id: proposal.id,
+            status: proposal.status.clone(),
+            eligible_weight: weights.values().sum(),
+            yes_weight,
+            no_weight,
+            counted_votes,
+            ignored_votes,
+            closed: proposal.end_time.0 <= DbDateTime::now().0,
+            projected,
+            emergency_veto_allowed: no_weight * 100 >= cast_weight * veto_threshold,
+        }
+    }
+
+    /// Loads the fund's members and the proposal's votes and weighs them.
+    pub async fn for_proposal(pool: &Pool, config: &GovernanceConfig, proposal: &Proposal) -> Result<Self> {
+        let members = match proposal.fund_id {
+            Some(fund_id) => operations::get_fund_members(pool, fund_id).await?,
+            None => Vec::new(),
+        };
+        let votes = operations::get_proposal_votes(pool, proposal.id).await?;
+
+        Ok(Self::compute(config, proposal, &members, &votes))
+    }
+}
+
+fn parse_address(address: &str) -> Option<AccountAddress> {
+    AccountAddress::from_str(address).ok()
+}
+
+/// Whether `vote` replaces `other` from the same voter: the chain is
+/// authoritative, and otherwise the later vote wins.
+fn supersedes(vote: &Vote, other: &Vote) -> bool {
+    (vote.on_chain, vote.updated_at.0) > (other.on_chain, other.updated_at.0)
+}
+
+/// Settles a proposal whose voting closed as passed or failed. Returns
+/// None when it is still open or was already settled.
+pub async fn finalize_proposal(
+    pool: &Pool,
+    config: &GovernanceConfig,
+    proposal: &Proposal,
+) -> Result<Option<Proposal>> {
+    let tally = Tally::for_proposal(pool, config, proposal).await?;
+    if proposal.status != PROPOSAL_ACTIVE || !tally.closed {
+        return Ok(None);
+    }
+
+    operations::finalize_proposal(pool, proposal.id, tally.projected.status()).await
+}
+
+/// Periodically finalizes proposals whose voting has closed.
+pub struct TallyService {
+    db: Pool,
+    config: GovernanceConfig,
+}
+
+impl TallyService {
+    pub fn new(db: Pool, config: GovernanceConfig) -> Self {
+        Self { db, config }
+    }
+
+    pub async fn start(&self) -> Result<()> {
+        info!("Starting proposal tally service");
+        loop {
+            if let Err(e) = self.finalize_ended().await {
+                error!("Error finalizing proposals: {}", e);
+            }
+            sleep(self.config.tally_interval).await;
+        }
+    }
+
+    /// Finalizes every active proposal past its `end_time`. Returns how
+    /// many were settled.
+    pub async fn finalize_ended(&self) -> Result<usize> {
+        let proposals = operations::get_ended_active_proposals(&self.db, DbDateTime::now()).await?;
+
+        let mut finalized = 0;
+        for proposal in &proposals {
+            if let Some(settled) = finalize_proposal(&self.db, &self.config, proposal).await? {
+                info!("Proposal {} closed as {}", settled.id, settled.status);
+                finalized += 1;
+            }
+        }
+
+        Ok(finalized)
+    }
+}
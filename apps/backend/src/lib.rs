@@ -11,6 +11,10 @@ pub mod error;
 pub mod utils;
 pub mod config;
 pub mod sync;
+pub mod move_events;
+pub mod move_errors;
+pub mod governance;
+pub mod transactions;
 
 // Re-export commonly used types
 pub use aptos_sdk::types as aptos_types;
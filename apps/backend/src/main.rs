@@ -2,14 +2,18 @@ use actix_web::{web, App, HttpServer};
 use anyhow::Result;
 use dotenv::dotenv;
 use log::{info, error};
-use std::sync::Arc;
-use tokio::time::Duration;
+use std::{path::PathBuf, sync::Arc};
 
 use backend::{
-    api::{routes, events::EventListener},
-    db::{create_pool, schema::initialize_database},
-    config::ClientConfig,
-    sync::BlockchainSynchronizer,
+    api::{
+        self, routes, events::{self, EventListener},
+        middleware::{Authentication, JwtKeys, RequestId},
+    },
+    db::{create_pool, migrations::{run_migrations, latest_version}},
+    config::AppConfig,
+    governance::{ProposalExecutor, TallyService},
+    sync::{self, BlockchainSynchronizer},
+    transactions::EntryFunctionCall,
     Client,
     AppState,
 };
@@ -20,35 +24,68 @@ async fn main() -> Result<()> {
     dotenv().ok();
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
+    let args: Vec<String> = std::env::args().collect();
+    let config_path = args
+        .iter()
+        .position(|arg| arg == "--config")
+        .and_then(|i| args.get(i + 1))
+        .map(PathBuf::from);
+
+    let config = AppConfig::load(config_path.as_deref()).map_err(|e| anyhow::anyhow!(e))?;
+    if args.iter().any(|arg| arg == "--print-config") {
+        print!("{}", config.to_toml().map_err(|e| anyhow::anyhow!(e))?);
+        return config.validate().map_err(|e| anyhow::anyhow!(e));
+    }
+    if let Err(e) = config.validate() {
+        error!("Invalid configuration: {}", e);
+        return Err(anyhow::anyhow!(e));
+    }
+
     info!("Starting application initialization...");
 
     // Initialize database
     info!("Initializing database connection...");
-    let pool = create_pool().await.map_err(|e| anyhow::anyhow!(e))?;
+    let pool = create_pool(&config.database).await.map_err(|e| anyhow::anyhow!(e))?;
     
     info!("Running database migrations...");
-    if let Err(e) = initialize_database(&pool).await {
-        error!("Failed to initialize database: {}", e);
+    if let Err(e) = run_migrations(&pool).await {
+        error!("Failed to migrate database: {}", e);
         return Err(anyhow::anyhow!(e));
     }
-    info!("Database initialization completed successfully");
+    info!("Database schema is at version {}", latest_version());
 
     // Initialize Aptos client
     info!("Initializing Aptos client...");
-    let config = ClientConfig::default();
-    let client = Client::new(config).await.map_err(|e| anyhow::anyhow!(e))?;
+    let client = Client::new(config.client.clone()).await.map_err(|e| anyhow::anyhow!(e))?;
     info!("Aptos client initialized successfully");
 
+    info!("Verifying Windfall modules...");
+    let mut required_structs = events::event_resources();
+    required_structs.extend_from_slice(sync::RESOURCES);
+    if let Err(e) = client.verify_modules(EntryFunctionCall::MODULES, &required_structs).await {
+        error!("Module verification failed: {}", e);
+        return Err(anyhow::anyhow!(e));
+    }
+
+    // Start node health prober
+    let health_checker = client.health_checker().clone();
+    tokio::spawn(async move {
+        health_checker.run().await;
+    });
+
     // Create shared application state
     let state = Arc::new(AppState { 
         db: pool.clone(),
         client: client.clone(),
     });
 
+    let sync_config = config.sync.clone();
+
     // Start event listener
     let event_listener_state = state.clone();
+    let listener_config = sync_config.clone();
     tokio::spawn(async move {
-        let mut listener = EventListener::new(event_listener_state);
+        let mut listener = EventListener::new(event_listener_state, listener_config);
         if let Err(e) = listener.start().await {
             error!("Event listener error: {}", e);
         }
@@ -59,31 +96,56 @@ async fn main() -> Result<()> {
     tokio::spawn(async move {
         let synchronizer = BlockchainSynchronizer::new(
             (*sync_state).clone(),
-            Duration::from_secs(10), // More conservative sync interval
+            sync_config.sync_interval,
+            sync_config,
         );
         if let Err(e) = synchronizer.start().await {
             error!("Blockchain synchronizer error: {}", e);
         }
     });
 
-    info!("Starting server at http://127.0.0.1:8080");
+    // Start proposal tally service
+    let tally_service = TallyService::new(pool.clone(), config.governance.clone());
+    tokio::spawn(async move {
+        if let Err(e) = tally_service.start().await {
+            error!("Proposal tally service error: {}", e);
+        }
+    });
+
+    // Start proposal executor
+    let executor = ProposalExecutor::new((*state).clone(), config.execution.clone())
+        .map_err(|e| anyhow::anyhow!(e))?;
+    let executor = web::Data::new(executor);
+    let scheduler = executor.clone();
+    tokio::spawn(async move {
+        if let Err(e) = scheduler.start().await {
+            error!("Proposal executor error: {}", e);
+        }
+    });
+
+    let jwt_keys = JwtKeys::from_config(&config.auth);
+    let governance_config = config.governance.clone();
+    let auth_config = config.auth.clone();
+
+    let bind_address = config.server.bind_address;
+    info!("Starting server at http://{}", bind_address);
 
     // Start HTTP server
     HttpServer::new(move || {
         App::new()
+            .wrap(Authentication::new(jwt_keys.clone()))
+            .wrap(RequestId)
             .app_data(web::Data::new(state.clone()))
-            .configure(routes::configure)
-            .service(
-                web::scope("/api/v1")
-                    .service(routes::funds::scope())
-                    .service(routes::proposals::scope())
-                    .service(routes::assets::scope())
-                    .service(routes::members::scope())
-                    .service(routes::messages::scope())
-                    .service(routes::transactions::scope())
-            )
+            .app_data(web::Data::new(jwt_keys.clone()))
+            .app_data(web::Data::new(auth_config.clone()))
+            .app_data(web::Data::new(governance_config.clone()))
+            .app_data(executor.clone())
+            .configure(api::configure_extractors)
+            // Routes are only served under the versioned prefix, which the
+            // authentication middleware's public paths assume
+            .service(web::scope("/api/v1").configure(routes::configure))
     })
-    .bind("127.0.0.1:8080").map_err(|e| anyhow::anyhow!(e))?
+    .bind(bind_address).map_err(|e| anyhow::anyhow!(e))?
     .run()
     .await.map_err(|e| anyhow::anyhow!(e))?;
 
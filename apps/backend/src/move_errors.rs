@@ -0,0 +1,153 @@
+//! Decoding of Move aborts into the Windfall modules' error constants.
+//!
+//! The modules abort with `std::error` codes: the error category in bits
+//! 16-23 and the module's own `E*` constant in the low 16 bits. The VM
+//! reports an abort as `Move abort in <address>::<module>: <code>`, with
+//! the constant's name added when the package publishes its error map.
+
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::Serialize;
+use crate::config::ModuleConfig;
+
+/// The `E*` constants of the Windfall modules, as `(module, code, name,
+/// reason)`. Keep in sync with `contracts/sources`.
+pub const ABORT_CODES: &[(&str, u64, &str, &str)] = &[
+    ("asset", 1, "ENOT_INITIALIZED", "The asset module is not initialized"),
+    ("asset", 2, "EALREADY_INITIALIZED", "The asset module or fund wallet is already initialized"),
+    ("asset", 3, "ENOT_AUTHORIZED", "The sender is not authorized for this action"),
+    ("asset", 4, "ENOT_ACTUATOR", "Only the fund's actuator can do this"),
+    ("asset", 5, "EASSET_ALREADY_EXISTS", "An asset with this symbol already exists"),
+    ("asset", 6, "EASSET_NOT_FOUND", "No asset with this symbol exists"),
+    ("asset", 7, "EINSUFFICIENT_BALANCE", "The balance is too low for this amount"),
+    ("asset", 8, "EINVALID_AMOUNT", "The amount is invalid"),
+    ("asset", 9, "EUSER_NOT_VERIFIED", "The account is not verified in the registry"),
+    ("asset", 10, "EUSER_NOT_MEMBER", "The account is not a member of the fund"),
+    ("asset", 11, "EFUND_NOT_FOUND", "The fund does not exist"),
+    ("asset", 12, "EINVALID_SHARE_TOTAL", "Member shares must add up to 10000"),
+    ("governance", 1, "ENOT_INITIALIZED", "The governance module is not initialized"),
+    ("governance", 2, "EALREADY_INITIALIZED", "The governance module is already initialized"),
+    ("governance", 3, "ENOT_AUTHORIZED", "The sender is not authorized for this action"),
+    ("governance", 4, "ENOT_MEMBER", "The sender is not an active member"),
+    ("governance", 5, "EALREADY_VOTED", "The sender has already voted on this proposal"),
+    ("governance", 6, "EPROPOSAL_NOT_FOUND", "The proposal does not exist"),
+    ("governance", 7, "EPROPOSAL_EXPIRED", "The proposal's voting period does not allow this"),
+    ("governance", 8, "EPROPOSAL_ALREADY_EXECUTED", "The proposal was already executed or vetoed"),
+    ("governance", 9, "EINSUFFICIENT_VOTES", "The proposal does not have enough votes"),
+    ("governance", 10, "EINVALID_QUORUM", "The quorum threshold is invalid"),
+    ("position", 1, "ENOT_INITIALIZED", "The position module is not initialized"),
+    ("position", 2, "EALREADY_INITIALIZED", "The position module is already initialized"),
+    ("position", 3, "ENOT_AUTHORIZED", "The sender is not authorized for this action"),
+    ("position", 4, "EPOSITION_NOT_FOUND", "The position does not exist"),
+    ("position", 5, "EINVALID_SHARE_AMOUNT", "The share amount is invalid"),
+    ("position", 6, "EINVALID_POSITION_SIZE", "The position size is invalid"),
+    ("position", 7, "EINSUFFICIENT_SHARES", "The account holds too few shares"),
+    ("position", 8, "EINVALID_AMOUNT", "The amount is invalid"),
+    ("position", 9, "EINVALID_PRICE", "The price is invalid"),
+    ("position", 10, "EINSUFFICIENT_BALANCE", "The balance is too low for this amount"),
+    ("position", 11, "EPOSITION_CLOSED", "The position is closed"),
+    ("registry", 1, "ENOT_INITIALIZED", "The registry is not initialized"),
+    ("registry", 2, "EALREADY_INITIALIZED", "The registry is already initialized"),
+    ("registry", 3, "ENOT_AUTHORIZED", "The sender is not authorized for this action"),
+    ("registry", 4, "EUSER_NOT_FOUND", "The account is not registered"),
+    ("registry", 5, "EINVALID_VERIFICATION_LEVEL", "The verification level is invalid"),
+    ("registry", 6, "EUSER_ALREADY_REGISTERED", "The account is already registered"),
+    ("registry", 7, "ESECURITY_NOT_INITIALIZED", "The security module is not initialized"),
+    ("security", 1, "ENOT_ADMIN", "Only the security admin can do this"),
+    ("security", 2, "ECONTRACT_PAUSED", "The module is paused"),
+    ("security", 3, "EREENTRANCY", "Reentrant calls are not allowed"),
+    ("security", 4, "ENOT_INITIALIZED", "The security module is not initialized"),
+    ("security", 5, "EALREADY_INITIALIZED", "The security module is already initialized"),
+    ("storage", 1, "ENOT_FOUND", "The record does not exist"),
+    ("storage", 2, "EALREADY_EXISTS", "The record already exists"),
+    ("storage", 3, "ENOT_AUTHORIZED", "The sender is not authorized for this action"),
+    ("storage", 4, "EINVALID_STATE", "The record is in an invalid state"),
+];
+
+/// Name and reason of a Windfall module's error constant.
+pub fn lookup(module: &str, code: u64) -> Option<(&'static str, &'static str)> {
+    ABORT_CODES
+        .iter()
+        .find(|(m, c, _, _)| *m == module && *c == code)
+        .map(|(_, _, name, reason)| (*name, *reason))
+}
+
+/// Name of a `std::error` category.
+pub fn category_name(category: u64) -> Option<&'static str> {
+    let name = match category {
+        0x1 => "INVALID_ARGUMENT",
+        0x2 => "OUT_OF_RANGE",
+        0x3 => "INVALID_STATE",
+        0x4 => "UNAUTHENTICATED",
+        0x5 => "PERMISSION_DENIED",
+        0x6 => "NOT_FOUND",
+        0x7 => "ABORTED",
+        0x8 => "ALREADY_EXISTS",
+        0x9 => "RESOURCE_EXHAUSTED",
+        0xA => "CANCELLED",
+        0xB => "INTERNAL",
+        0xC => "NOT_IMPLEMENTED",
+        0xD => "UNAVAILABLE",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// A decoded Move abort.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MoveAbort {
+    /// Module that aborted, as `<address>::<module>`.
+    pub location: String,
+    /// The full abort code, category included.
+    pub abort_code: u64,
+    pub category: Option<&'static str>,
+    /// The module's error constant, e.g. `EALREADY_VOTED`.
+    pub name: Option<String>,
+    /// Why the transaction aborted, for people.
+    pub reason: String,
+}
+
+impl MoveAbort {
+    /// Decodes a VM status such as `Move abort in 0x1::governance: 0x30005`.
+    /// Returns None when the status is not a Move abort. Codes raised by
+    /// the Windfall modules are only named when `location` is where
+    /// `modules` says they are published.
+    pub fn from_vm_status(vm_status: &str, modules: &ModuleConfig) -> Option<Self> {
+        let rest = vm_status.strip_prefix("Move abort in ")?;
+        let (location, detail) = rest.split_once(": ")?;
+        let (address, module) = location.rsplit_once("::")?;
+
+        // `NAME(0x30005): description` with an error map, bare `0x30005` without
+        let (reported_name, code) = match detail.split_once('(') {
+            Some((name, code)) => (Some(name.to_string()), code.split(')').next()?),
+            None => (None, detail.split_whitespace().next()?),
+        };
+        let abort_code = parse_code(code)?;
+        let reason_code = abort_code & 0xFFFF;
+
+        let windfall = AccountAddress::from_hex_literal(address)
+            .is_ok_and(|address| address == modules.module_address(module));
+        let known = windfall.then(|| lookup(module, reason_code)).flatten();
+
+        let name = known.map(|(name, _)| name.to_string()).or(reported_name);
+        let reason = match (known, &name) {
+            (Some((_, reason)), _) => reason.to_string(),
+            (None, Some(name)) => format!("{} aborted with {}", location, name),
+            (None, None) => format!("{} aborted with code {}", location, abort_code),
+        };
+
+        Some(Self {
+            location: location.to_string(),
+            abort_code,
+            category: category_name((abort_code >> 16) & 0xFF),
+            name,
+            reason,
+        })
+    }
+}
+
+fn parse_code(code: &str) -> Option<u64> {
+    match code.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => code.parse().ok(),
+    }
+}
@@ -0,0 +1,447 @@
+//! Typed decoding of the events emitted by the Windfall Move modules.
+//!
+//! The REST API returns event payloads as JSON with `u64` fields encoded as
+//! strings. Every payload is decoded into [`WindfallEvent`] based on the
+//! struct name in its Move type tag. There is a single layout per struct,
+//! matching the deployed modules.
+
+use aptos_sdk::rest_client::{
+    aptos_api_types::{TransactionPayload, VersionedEvent},
+    Transaction,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+
+/// Version of the event layouts below, stored with dead letters so they
+/// can be told apart from events a newer decoder would accept. Bump it
+/// when a layout changes; older layouts are not kept.
+pub const EVENT_SCHEMA_VERSION: u16 = 1;
+
+#[derive(Error, Debug)]
+pub enum DecodeError {
+    #[error("Unknown event type: {0}")]
+    UnknownType(String),
+
+    #[error("Malformed event type tag: {0}")]
+    MalformedType(String),
+
+    #[error("Failed to decode {event_type} (schema v{schema_version}): {source}")]
+    Payload {
+        event_type: String,
+        schema_version: u16,
+        source: serde_json::Error,
+    },
+}
+
+/// A decoded event together with its position on chain.
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedEvent {
+    pub sequence_number: u64,
+    pub version: u64,
+    pub timestamp: u64,
+    pub schema_version: u16,
+    pub event: WindfallEvent,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data")]
+pub enum WindfallEvent {
+    // asset
+    AssetCreation(AssetCreationEvent),
+    Transfer(TransferEvent),
+    Mint(MintEvent),
+    Burn(BurnEvent),
+    Investment(InvestmentEvent),
+    Withdrawal(WithdrawalEvent),
+    MemberUpdate(MemberUpdateEvent),
+    // governance
+    ProposalCreated(ProposalCreatedEvent),
+    Vote(VoteEvent),
+    ProposalExecuted(ProposalExecutedEvent),
+    EmergencyVeto(EmergencyVetoEvent),
+    // position
+    PositionOpened(PositionOpenedEvent),
+    PositionClosed(PositionClosedEvent),
+    ShareAllocation(ShareAllocationEvent),
+    ShareTransfer(ShareTransferEvent),
+    // registry
+    Registration(RegistrationEvent),
+    Deactivation(DeactivationEvent),
+}
+
+impl WindfallEvent {
+    /// On-chain timestamp carried in the payload, in microseconds.
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            WindfallEvent::AssetCreation(e) => e.creation_time,
+            WindfallEvent::Transfer(e) => e.transfer_time,
+            WindfallEvent::Mint(e) => e.mint_time,
+            WindfallEvent::Burn(e) => e.burn_time,
+            WindfallEvent::Investment(e) => e.timestamp,
+            WindfallEvent::Withdrawal(e) => e.timestamp,
+            WindfallEvent::MemberUpdate(e) => e.timestamp,
+            WindfallEvent::ProposalCreated(e) => e.timestamp,
+            WindfallEvent::Vote(e) => e.timestamp,
+            WindfallEvent::ProposalExecuted(e) => e.timestamp,
+            WindfallEvent::EmergencyVeto(e) => e.timestamp,
+            WindfallEvent::PositionOpened(e) => e.timestamp,
+            WindfallEvent::PositionClosed(e) => e.timestamp,
+            WindfallEvent::ShareAllocation(e) => e.timestamp,
+            WindfallEvent::ShareTransfer(e) => e.timestamp,
+            WindfallEvent::Registration(e) => e.registration_time,
+            WindfallEvent::Deactivation(e) => e.deactivation_time,
+        }
+    }
+}
+
+// asset module
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetCreationEvent {
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    #[serde(deserialize_with = "de_u64")]
+    pub total_supply: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub creation_time: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferEvent {
+    pub symbol: String,
+    pub from: String,
+    pub to: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub amount: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub transfer_time: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintEvent {
+    pub symbol: String,
+    pub to: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub amount: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub mint_time: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BurnEvent {
+    pub symbol: String,
+    pub from: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub amount: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub burn_time: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvestmentEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub fund_id: u64,
+    pub target_address: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub amount: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub fund_id: u64,
+    pub to_address: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub amount: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemberUpdateEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub fund_id: u64,
+    pub member_address: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub new_share: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+// governance module
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposalCreatedEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub proposal_id: u64,
+    pub proposer: String,
+    pub proposal_type: u8,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub proposal_id: u64,
+    pub voter: String,
+    pub vote: bool,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposalExecutedEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub proposal_id: u64,
+    pub executed_by: String,
+    pub success: bool,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyVetoEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub proposal_id: u64,
+    pub initiator: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+// position module
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionOpenedEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub position_id: u64,
+    pub asset_symbol: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub size: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub entry_price: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionClosedEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub position_id: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub exit_price: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub pnl: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareAllocationEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub position_id: u64,
+    pub user_address: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub shares: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareTransferEvent {
+    #[serde(deserialize_with = "de_u64")]
+    pub position_id: u64,
+    pub from_address: String,
+    pub to_address: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub shares: u64,
+    #[serde(deserialize_with = "de_u64")]
+    pub timestamp: u64,
+}
+
+// registry module
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrationEvent {
+    pub user_address: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub registration_time: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeactivationEvent {
+    pub user_address: String,
+    #[serde(deserialize_with = "de_u64")]
+    pub deactivation_time: u64,
+}
+
+/// Move `u64` values arrive as JSON strings; plain numbers are accepted too.
+pub(crate) fn de_u64<'de, D>(deserializer: D) -> std::result::Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(u64),
+    }
+
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s.parse().map_err(serde::de::Error::custom),
+        StringOrNumber::Number(n) => Ok(n),
+    }
+}
+
+/// Splits a type tag such as `0x1::asset::TransferEvent` into its module
+/// and struct name. Generic parameters are not used by Windfall events.
+pub fn parse_event_type(event_type: &str) -> std::result::Result<(&str, &str), DecodeError> {
+    let base = event_type.split('<').next().unwrap_or(event_type);
+    let mut parts = base.rsplit("::");
+    match (parts.next(), parts.next()) {
+        (Some(name), Some(module)) if !name.is_empty() && !module.is_empty() => Ok((module, name)),
+        _ => Err(DecodeError::MalformedType(event_type.to_string())),
+    }
+}
+
+fn payload<T: DeserializeOwned>(
+    event_type: &str,
+    data: &serde_json::Value,
+) -> std::result::Result<T, DecodeError> {
+    serde_json::from_value(data.clone()).map_err(|source| DecodeError::Payload {
+        event_type: event_type.to_string(),
+        schema_version: EVENT_SCHEMA_VERSION,
+        source,
+    })
+}
+
+/// Decodes a raw payload given its Move type tag.
+pub fn decode_payload(
+    event_type: &str,
+    data: &serde_json::Value,
+) -> std::result::Result<WindfallEvent, DecodeError> {
+    let (module, name) = parse_event_type(event_type)?;
+
+    let event = match (module, name) {
+        ("asset", "AssetCreationEvent") => WindfallEvent::AssetCreation(payload(event_type, data)?),
+        ("asset", "TransferEvent") => WindfallEvent::Transfer(payload(event_type, data)?),
+        ("asset", "MintEvent") => WindfallEvent::Mint(payload(event_type, data)?),
+        ("asset", "BurnEvent") => WindfallEvent::Burn(payload(event_type, data)?),
+        ("asset", "InvestmentEvent") => WindfallEvent::Investment(payload(event_type, data)?),
+        ("asset", "WithdrawalEvent") => WindfallEvent::Withdrawal(payload(event_type, data)?),
+        ("asset", "MemberUpdateEvent") => WindfallEvent::MemberUpdate(payload(event_type, data)?),
+        ("governance", "ProposalCreatedEvent") => WindfallEvent::ProposalCreated(payload(event_type, data)?),
+        ("governance", "VoteEvent") => WindfallEvent::Vote(payload(event_type, data)?),
+        ("governance", "ProposalExecutedEvent") => WindfallEvent::ProposalExecuted(payload(event_type, data)?),
+        ("governance", "EmergencyVetoEvent") => WindfallEvent::EmergencyVeto(payload(event_type, data)?),
+        ("position", "PositionOpenedEvent") => WindfallEvent::PositionOpened(payload(event_type, data)?),
+        ("position", "PositionClosedEvent") => WindfallEvent::PositionClosed(payload(event_type, data)?),
+        ("position", "ShareAllocationEvent") => WindfallEvent::ShareAllocation(payload(event_type, data)?),
+        ("position", "ShareTransferEvent") => WindfallEvent::ShareTransfer(payload(event_type, data)?),
+        ("registry", "RegistrationEvent") => WindfallEvent::Registration(payload(event_type, data)?),
+        ("registry", "DeactivationEvent") => WindfallEvent::Deactivation(payload(event_type, data)?),
+        _ => return Err(DecodeError::UnknownType(event_type.to_string())),
+    };
+
+    Ok(event)
+}
+
+/// Decodes an event returned by the node.
+pub fn decode_event(event: &VersionedEvent) -> std::result::Result<DecodedEvent, DecodeError> {
+    let event_type = event.typ.to_string();
+    let decoded = decode_payload(&event_type, &event.data)?;
+
+    Ok(DecodedEvent {
+        sequence_number: event.sequence_number.0,
+        version: event.version.0,
+        timestamp: decoded.timestamp(),
+        schema_version: EVENT_SCHEMA_VERSION,
+        event: decoded,
+    })
+}
+
+/// The entry function call of the transaction that emitted an event.
+#[derive(Debug, Clone)]
+pub struct EntryCall {
+    pub sender: String,
+    pub module: String,
+    pub function: String,
+    pub arguments: Vec<serde_json::Value>,
+}
+
+impl EntryCall {
+    /// Reads a `u64` argument, which the REST API encodes as a string.
+    pub fn u64_argument(&self, index: usize) -> Option<u64> {
+        match self.arguments.get(index)? {
+            serde_json::Value::String(s) => s.parse().ok(),
+            value => value.as_u64(),
+        }
+    }
+
+    pub fn bool_argument(&self, index: usize) -> Option<bool> {
+        self.arguments.get(index)?.as_bool()
+    }
+}
+
+/// Extracts the entry function call from a user transaction. Returns None
+/// for system transactions and script payloads.
+pub fn entry_call(txn: &Transaction) -> Option<EntryCall> {
+    let Transaction::UserTransaction(user_txn) = txn else {
+        return None;
+    };
+    let TransactionPayload::EntryFunctionPayload(payload) = &user_txn.request.payload else {
+        return None;
+    };
+
+    Some(EntryCall {
+        sender: user_txn.request.sender.inner().to_hex_literal(),
+        module: payload.function.module.name.0.as_str().to_string(),
+        function: payload.function.name.0.as_str().to_string(),
+        arguments: payload.arguments.clone(),
+    })
+}
+
+/// The `ProposalExecutedEvent` emitted by a committed transaction, if any.
+/// `execute_proposal` emits one per call, whether or not the proposal
+/// passed the on-chain quorum check.
+pub fn proposal_executed(txn: &Transaction) -> Option<ProposalExecutedEvent> {
+    let Transaction::UserTransaction(user_txn) = txn else {
+        return None;
+    };
+
+    user_txn.events.iter().find_map(|event| {
+        match decode_payload(&event.typ.to_string(), &event.data) {
+            Ok(WindfallEvent::ProposalExecuted(executed)) => Some(executed),
+            _ => None,
+        }
+    })
+}
+
+/// What a `PositionClosedEvent` stands for. `close_position`,
+/// `modify_position` and `liquidate_position` all emit it, so the entry
+/// function that emitted it decides. A modification carries the new entry
+/// price as `exit_price`, but the new size only as a call argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionEffect {
+    Closed,
+    Liquidated,
+    Modified { size: Option<u64>, entry_price: u64 },
+}
+
+pub fn position_effect(event: &PositionClosedEvent, call: Option<&EntryCall>) -> PositionEffect {
+    let Some(call) = call else {
+        return PositionEffect::Closed;
+    };
+
+    match call.function.as_str() {
+        "liquidate_position" => PositionEffect::Liquidated,
+        "modify_position" => PositionEffect::Modified {
+            size: call.u64_argument(1),
+            entry_price: event.exit_price,
+        },
+        _ => PositionEffect::Closed,
+    }
+}
@@ -0,0 +1,82 @@
+use crate::{
+    AppState,
+    config::SyncConfig,
+    db::operations,
+    error::Result,
+};
+use log::{debug, warn};
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Consecutive reads that must all trail the applied version before local
+/// state is rolled back. Reads may be served by different nodes, so a single
+/// lagging answer is not enough.
+const ROLLBACK_CONFIRMATIONS: u32 = 3;
+/// Pause between the reads that confirm a rollback.
+const ROLLBACK_RECHECK_DELAY: Duration = Duration::from_secs(2);
+
+/// Where the node's ledger stands relative to what has been applied locally.
+#[derive(Debug, Clone, Copy)]
+pub struct LedgerPosition {
+    /// The node's latest ledger version.
+    pub tip: u64,
+    /// Highest version whose changes may be applied.
+    pub confirmed: u64,
+}
+
+/// Reads the node's ledger version and reconciles local state with it.
+///
+/// If the ledger stays behind what has already been applied across
+/// several reads, everything derived above the highest version seen is
+/// rolled back so it is re-applied from the nodes' view. A single lagging
+/// read, such as one served by a fallback node, is ignored.
+pub async fn check_ledger(state: &AppState, config: &SyncConfig) -> Result<LedgerPosition> {
+    let mut tip = state.client.get_ledger_version().await?;
+
+    if let Some(applied) = operations::get_applied_ledger_version(&state.db).await? {
+        if applied > tip {
+            tip = recheck_tip(state, applied, tip).await?;
+        }
+        if applied > tip {
+            warn!(
+                "Ledger version {} is behind applied version {} across {} reads; rolling back",
+                tip, applied, ROLLBACK_CONFIRMATIONS
+            );
+            let mut conn = state.db.acquire().await?;
+            let summary = operations::rollback_to_version(&mut conn, tip).await?;
+            warn!(
+                "Rolled back to version {}: {} changes reverted, {} events removed",
+                summary.version, summary.reverted_changes, summary.removed_events
+            );
+        }
+    }
+
+    if let Some(horizon) = tip.checked_sub(config.rollback_window) {
+        operations::prune_ledger_journal(&state.db, horizon).await?;
+    }
+
+    Ok(LedgerPosition {
+        tip,
+        confirmed: tip.saturating_sub(config.confirmation_depth),
+    })
+}
+
+/// Re-reads the ledger version after a read that trailed `applied`. Returns
+/// the first read that has caught up, or the highest version seen if all
+/// `ROLLBACK_CONFIRMATIONS` reads stayed behind.
+async fn recheck_tip(state: &AppState, applied: u64, first: u64) -> Result<u64> {
+    let mut highest = first;
+
+    for _ in 1..ROLLBACK_CONFIRMATIONS {
+        sleep(ROLLBACK_RECHECK_DELAY).await;
+
+        let tip = state.client.get_ledger_version().await?;
+        if tip >= applied {
+            debug!("Ledger version {} behind applied version {} did not hold", first, applied);
+            return Ok(tip);
+        }
+        highest = highest.max(tip);
+    }
+
+    Ok(highest)
+}
@@ -1,102 +1,107 @@
+pub mod ledger;
+
 use crate::{
     AppState,
-    error::Result,
-    db::operations,
+    config::SyncConfig,
+    error::{AppError, Result},
+    db::{operations, schema::MEMBER_ACTIVE},
+    move_events::de_u64,
 };
 use aptos_sdk::types::account_address::AccountAddress;
 use log::{info, error, warn};
 use tokio::time::{sleep, Duration};
 use std::str::FromStr;
 
+/// Name of the synchronizer's row in `sync_checkpoints`.
+pub const CHECKPOINT: &str = "blockchain_synchronizer";
+
 pub struct BlockchainSynchronizer {
     state: AppState,
     sync_interval: Duration,
+    config: SyncConfig,
 }
 
 impl BlockchainSynchronizer {
-    pub fn new(state: AppState, sync_interval: Duration) -> Self {
+    pub fn new(state: AppState, sync_interval: Duration, config: SyncConfig) -> Self {
         Self {
             state,
             sync_interval,
+            config,
         }
     }
 
     pub async fn start(&self) -> Result<()> {
         info!("Starting blockchain synchronizer");
         loop {
-            match self.sync_state().await {
+            let interval = match self.sync_state().await {
+                Ok(lag) if lag > self.config.catch_up_threshold => {
+                    info!("Synchronizer is {} versions behind, catching up", lag);
+                    self.config.catch_up_interval
+                }
                 Ok(_) => {
                     info!("State synchronization completed successfully");
+                    self.sync_interval
                 }
                 Err(e) => {
                     error!("Error during state synchronization: {}", e);
+                    self.sync_interval
                 }
-            }
-            sleep(self.sync_interval).await;
+            };
+            sleep(interval).await;
         }
     }
 
-    async fn sync_state(&self) -> Result<()> {
-        // Sync fund states
-        self.sync_fund_states().await?;
+    /// Runs one pass with every resource read at the same confirmed ledger
+    /// version, then checkpoints that version. Returns how many versions
+    /// the previous checkpoint trailed the node by when the pass started.
+    async fn sync_state(&self) -> Result<u64> {
+        // Roll back first if the node is behind what was already applied
+        let ledger = ledger::check_ledger(&self.state, &self.config).await?;
+        let checkpoint = operations::get_sync_checkpoint(&self.state.db, CHECKPOINT).await?;
+        let lag = checkpoint
+            .as_ref()
+            .map_or(ledger.tip, |c| ledger.tip.saturating_sub(c.ledger_version as u64));
+
+        // Nothing new has been confirmed since the last pass
+        if checkpoint.is_some_and(|c| ledger.confirmed <= c.ledger_version as u64) {
+            return Ok(lag);
+        }
+
+        let version = ledger.confirmed;
         // Sync member states
-        self.sync_member_states().await?;
+        self.sync_member_states(version).await?;
         // Sync asset states
-        self.sync_asset_states().await?;
-        Ok(())
-    }
-
-    async fn sync_fund_states(&self) -> Result<()> {
-        info!("Syncing fund states with blockchain");
-        // Get all funds from database
-        let funds = operations::get_all_funds(&self.state.db).await?;
-        
-        for fund in funds {
-            // Get fund state from blockchain
-            let fund_address = AccountAddress::from_str(&fund.executor_address)?;
-            let fund_resource = self.state.client
-                .get_resource::<FundResource>(fund_address, "0x1::windfall::fund::FundResource")
-                .await;
+        self.sync_asset_states(version).await?;
 
-            match fund_resource {
-                Ok(resource) => {
-                    // Update local state if needed
-                    if resource.version > fund.version as u64 {
-                        operations::update_fund_state(
-                            &self.state.db,
-                            fund.id,
-                            resource.version,
-                            resource.status,
-                        ).await?;
-                    }
-                }
-                Err(e) => {
-                    // Only log as warning for resource not found, as this is expected for new funds
-                    if e.to_string().contains("ResourceNotFound") {
-                        warn!("Fund resource not found for {}: {}", fund.id, e);
-                    } else {
-                        error!("Failed to get fund resource for {}: {}", fund.id, e);
-                    }
-                    continue;
-                }
-            }
-        }
-        Ok(())
+        operations::save_sync_checkpoint(&self.state.db, CHECKPOINT, version, ledger.tip).await?;
+        Ok(lag)
     }
 
-    async fn sync_member_states(&self) -> Result<()> {
+    async fn sync_member_states(&self, ledger_version: u64) -> Result<()> {
         info!("Syncing member states with blockchain");
         // Get all funds from database
         let funds = operations::get_all_funds(&self.state.db).await?;
         
+        let wallet_type = self.state.client.modules().type_name("asset", "FundWallet");
+
         for fund in funds {
-            // Get member list from blockchain
-            let fund_address = AccountAddress::from_str(&fund.executor_address)?;
-            let members_resource = self.state.client
-                .get_resource::<MembersResource>(fund_address, "0x1::windfall::fund::MembersResource")
+            // Members are kept in the fund's wallet
+            let wallet = match operations::get_fund_wallet(&self.state.db, fund.id).await {
+                Ok(wallet) => wallet,
+                Err(AppError::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            // Wallets carried over from before the address was recorded
+            if wallet.wallet_address.is_empty() {
+                warn!("Fund wallet address not recorded for fund {}", fund.id);
+                continue;
+            }
+            let wallet_address = AccountAddress::from_str(&wallet.wallet_address)?;
+            let wallet_resource = self.state.client
+                .get_resource_at_version::<FundWalletResource>(wallet_address, &wallet_type, ledger_version)
                 .await;
 
-            match members_resource {
+            match wallet_resource {
                 Ok(resource) => {
                     // Update local member states
                     for member in resource.members {
@@ -104,17 +109,18 @@ impl BlockchainSynchronizer {
                             &self.state.db,
                             fund.id,
                             &member.address,
-                            member.share,
-                            member.status,
+                            member.ownership_share,
+                            MEMBER_ACTIVE.to_string(),
+                            ledger_version,
                         ).await?;
                     }
                 }
                 Err(e) => {
-                    // Only log as warning for resource not found
-                    if e.to_string().contains("ResourceNotFound") {
-                        warn!("Members resource not found for fund {}: {}", fund.id, e);
+                    // Only log as warning for resource not found, as this is expected for new funds
+                    if matches!(e, AppError::NotFound(_)) {
+                        warn!("Fund wallet not found for fund {}: {}", fund.id, e);
                     } else {
-                        error!("Failed to get members resource for fund {}: {}", fund.id, e);
+                        error!("Failed to get fund wallet for fund {}: {}", fund.id, e);
                     }
                     continue;
                 }
@@ -123,42 +129,50 @@ impl BlockchainSynchronizer {
         Ok(())
     }
 
-    async fn sync_asset_states(&self) -> Result<()> {
+    async fn sync_asset_states(&self, ledger_version: u64) -> Result<()> {
         info!("Syncing asset states with blockchain");
         // Get all assets from database
         let assets = operations::get_all_assets(&self.state.db).await?;
         
+        let asset_info = format!(
+            "{}::asset::get_asset_info",
+            self.state.client.modules().module_address("asset").to_hex_literal()
+        );
+
         for asset in assets {
-            // Skip assets without addresses
-            let Some(address) = &asset.address else {
-                continue;
-            };
-            
-            // Get asset state from blockchain
-            let asset_address = AccountAddress::from_str(address)?;
-            let asset_resource = self.state.client
-                .get_resource::<AssetResource>(asset_address, "0x1::windfall::asset::AssetResource")
-                .await;
+            // Assets are kept in a table in `AssetData`, read through its view function
+            let total_supply = self.state.client
+                .view_at_version(&asset_info, vec![serde_json::json!(asset.symbol)], ledger_version)
+                .await
+                .and_then(|values| {
+                    // Returns symbol, name, decimals, total supply and whether it is active
+                    values
+                        .get(3)
+                        .and_then(|supply| supply.as_str()?.parse::<u64>().ok())
+                        .ok_or_else(|| AppError::deserialization_error("Failed to decode asset info"))
+                });
 
-            match asset_resource {
-                Ok(resource) => {
-                    // Update local state if needed
-                    if resource.version > asset.version as u64 {
+            match total_supply {
+                Ok(total_supply) => {
+                    // Balances are in a table that cannot be listed; transfer
+                    // events keep them current
+                    if total_supply != asset.total_supply as u64 {
                         operations::update_asset_state(
                             &self.state.db,
                             asset.id,
-                            resource.version,
-                            resource.total_supply,
-                            resource.holders,
+                            asset.version as u64,
+                            total_supply,
+                            Vec::new(),
+                            ledger_version,
                         ).await?;
                     }
                 }
                 Err(e) => {
                     // Only log as warning for resource not found
-                    if e.to_string().contains("ResourceNotFound") {
-                        warn!("Asset resource not found for {}: {}", asset.id, e);
+                    if matches!(e, AppError::NotFound(_)) {
+                        warn!("Asset {} not found on chain: {}", asset.symbol, e);
                     } else {
-                        error!("Failed to get asset resource for {}: {}", asset.id, e);
+                        error!("Failed to get asset info for {}: {}", asset.symbol, e);
                     }
                     continue;
                 }
@@ -168,29 +182,20 @@ impl BlockchainSynchronizer {
     }
 }
 
-#[derive(serde::Deserialize)]
-struct FundResource {
-    version: u64,
-    status: String,
-}
+/// Resources the synchronizer reads, as `(module, struct)`.
+pub const RESOURCES: &[(&str, &str)] = &[("asset", "AssetData"), ("asset", "FundWallet")];
 
+/// `asset::FundWallet`.
 #[derive(serde::Deserialize)]
-struct MembersResource {
-    members: Vec<MemberInfo>,
+struct FundWalletResource {
+    members: Vec<WalletMember>,
 }
 
 #[derive(serde::Deserialize)]
-struct MemberInfo {
+struct WalletMember {
     address: String,
-    share: u64,
-    status: String,
-}
-
-#[derive(serde::Deserialize)]
-struct AssetResource {
-    version: u64,
-    total_supply: u64,
-    holders: Vec<HolderInfo>,
+    #[serde(deserialize_with = "de_u64")]
+    ownership_share: u64,
 }
 
 #[derive(serde::Deserialize)]
@@ -0,0 +1,590 @@
+//! Typed construction of transactions calling the Windfall Move entry
+//! functions.
+//!
+//! Every public entry function is an [`EntryFunctionCall`] variant whose
+//! fields are the function's arguments after the signer, in declaration
+//! order. Arguments are BCS-encoded with the layout of their Move type:
+//! `String` and `vector<T>` as length-prefixed sequences, `address` as 32
+//! raw bytes. Wallets that sign on the client side get the same call as a
+//! JSON payload instead.
+
+use aptos_sdk::{
+    bcs,
+    move_types::{identifier::Identifier, language_storage::ModuleId},
+    rest_client::aptos_api_types::UserTransaction,
+    types::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{EntryFunction, RawTransaction, TransactionPayload},
+    },
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::time::Duration;
+use crate::{
+    config::ModuleConfig,
+    error::{AppError, Result},
+    move_errors::MoveAbort,
+};
+
+pub const DEFAULT_MAX_GAS_AMOUNT: u64 = 20_000;
+pub const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
+pub const DEFAULT_TRANSACTION_TTL: Duration = Duration::from_secs(60);
+
+/// Everything in a transaction besides its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionParams {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub chain_id: ChainId,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    pub expiration_timestamp_secs: u64,
+}
+
+impl TransactionParams {
+    /// Parameters with the default gas settings, expiring
+    /// `DEFAULT_TRANSACTION_TTL` from now.
+    pub fn new(sender: AccountAddress, sequence_number: u64, chain_id: ChainId) -> Self {
+        Self {
+            sender,
+            sequence_number,
+            chain_id,
+            max_gas_amount: DEFAULT_MAX_GAS_AMOUNT,
+            gas_unit_price: DEFAULT_GAS_UNIT_PRICE,
+            expiration_timestamp_secs: 0,
+        }
+        .with_ttl(DEFAULT_TRANSACTION_TTL)
+    }
+
+    pub fn with_sequence_number(mut self, sequence_number: u64) -> Self {
+        self.sequence_number = sequence_number;
+        self
+    }
+
+    pub fn with_max_gas_amount(mut self, max_gas_amount: u64) -> Self {
+        self.max_gas_amount = max_gas_amount;
+        self
+    }
+
+    pub fn with_gas_unit_price(mut self, gas_unit_price: u64) -> Self {
+        self.gas_unit_price = gas_unit_price;
+        self
+    }
+
+    /// Expires the transaction `ttl` from now.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.expiration_timestamp_secs = chrono::Utc::now().timestamp() as u64 + ttl.as_secs();
+        self
+    }
+}
+
+/// An entry function argument, typed as its Move parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveArg {
+    U8(u8),
+    U64(u64),
+    Bool(bool),
+    Address(AccountAddress),
+    String(String),
+    AddressVector(Vec<AccountAddress>),
+    StringVector(Vec<String>),
+}
+
+impl MoveArg {
+    pub fn to_bcs(&self) -> Result<Vec<u8>> {
+        let bytes = match self {
+            MoveArg::U8(value) => bcs::to_bytes(value),
+            MoveArg::U64(value) => bcs::to_bytes(value),
+            MoveArg::Bool(value) => bcs::to_bytes(value),
+            MoveArg::Address(value) => bcs::to_bytes(value),
+            MoveArg::String(value) => bcs::to_bytes(value),
+            MoveArg::AddressVector(value) => bcs::to_bytes(value),
+            MoveArg::StringVector(value) => bcs::to_bytes(value),
+        };
+
+        bytes.map_err(|e| AppError::serialization_error(&e.to_string()))
+    }
+
+    /// The argument as the REST API and wallets expect it: `u64` as a
+    /// decimal string, addresses as hex literals.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            MoveArg::U8(value) => json!(value),
+            MoveArg::U64(value) => json!(value.to_string()),
+            MoveArg::Bool(value) => json!(value),
+            MoveArg::Address(value) => json!(value.to_hex_literal()),
+            MoveArg::String(value) => json!(value),
+            MoveArg::AddressVector(value) => {
+                json!(value.iter().map(AccountAddress::to_hex_literal).collect::<Vec<_>>())
+            }
+            MoveArg::StringVector(value) => json!(value),
+        }
+    }
+}
+
+impl From<&u8> for MoveArg {
+    fn from(value: &u8) -> Self {
+        MoveArg::U8(*value)
+    }
+}
+
+impl From<&u64> for MoveArg {
+    fn from(value: &u64) -> Self {
+        MoveArg::U64(*value)
+    }
+}
+
+impl From<&bool> for MoveArg {
+    fn from(value: &bool) -> Self {
+        MoveArg::Bool(*value)
+    }
+}
+
+impl From<&AccountAddress> for MoveArg {
+    fn from(value: &AccountAddress) -> Self {
+        MoveArg::Address(*value)
+    }
+}
+
+impl From<&String> for MoveArg {
+    fn from(value: &String) -> Self {
+        MoveArg::String(value.clone())
+    }
+}
+
+impl From<&Vec<AccountAddress>> for MoveArg {
+    fn from(value: &Vec<AccountAddress>) -> Self {
+        MoveArg::AddressVector(value.clone())
+    }
+}
+
+impl From<&Vec<String>> for MoveArg {
+    fn from(value: &Vec<String>) -> Self {
+        MoveArg::StringVector(value.clone())
+    }
+}
+
+/// An entry function payload in the JSON form the REST API and browser
+/// wallets take.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonEntryFunctionPayload {
+    #[serde(rename = "type")]
+    pub payload_type: String,
+    /// Fully qualified function, e.g. `0x1::governance::vote`.
+    pub function: String,
+    pub type_arguments: Vec<String>,
+    pub arguments: Vec<serde_json::Value>,
+}
+
+/// A call to one of the Windfall entry functions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "function", rename_all = "snake_case")]
+pub enum EntryFunctionCall {
+    // asset
+    CreateAsset {
+        symbol: String,
+        name: String,
+        decimals: u8,
+        initial_supply: u64,
+    },
+    Transfer {
+        to: AccountAddress,
+        symbol: String,
+        amount: u64,
+    },
+    Mint {
+        to: AccountAddress,
+        symbol: String,
+        amount: u64,
+    },
+    Burn {
+        from: AccountAddress,
+        symbol: String,
+        amount: u64,
+    },
+    CreateFund {
+        name: String,
+        description: String,
+        executor: AccountAddress,
+        initial_members: Vec<AccountAddress>,
+        metadata_keys: Vec<String>,
+        metadata_values: Vec<String>,
+    },
+    ExecuteTransaction {
+        fund_id: u64,
+    },
+    Invest {
+        fund_addr: AccountAddress,
+        target: AccountAddress,
+        amount: u64,
+    },
+    WithdrawProfits {
+        fund_addr: AccountAddress,
+        amount: u64,
+    },
+    UpdateMemberShare {
+        fund_addr: AccountAddress,
+        member_addr: AccountAddress,
+        new_share: u64,
+    },
+    // governance
+    CreateActuatorProposal {
+        new_actuator: AccountAddress,
+        description: String,
+    },
+    Vote {
+        proposal_id: u64,
+        vote: bool,
+    },
+    EmergencyVeto {
+        proposal_id: u64,
+    },
+    CreateTradeProposal {
+        asset_symbol: String,
+        size: u64,
+        price: u64,
+        is_entry: bool,
+        description: String,
+    },
+    ExecuteProposal {
+        proposal_id: u64,
+    },
+    // position
+    SetActuator {
+        new_actuator: AccountAddress,
+    },
+    OpenPosition {
+        asset_id: u64,
+        size: u64,
+        entry_price: u64,
+        is_long: bool,
+    },
+    AllocateShares {
+        position_id: u64,
+        user_address: AccountAddress,
+        shares: u64,
+    },
+    TransferShares {
+        to: AccountAddress,
+        position_id: u64,
+        shares: u64,
+    },
+    ClosePosition {
+        position_id: u64,
+        exit_price: u64,
+    },
+    ModifyPosition {
+        position_id: u64,
+        new_size: u64,
+        new_price: u64,
+    },
+    LiquidatePosition {
+        position_id: u64,
+        liquidation_price: u64,
+    },
+    // registry
+    RegisterUser {
+        user_address: AccountAddress,
+        verification_level: u8,
+    },
+    UpdateVerificationLevel {
+        user_address: AccountAddress,
+        new_level: u8,
+    },
+    DeactivateUser {
+        user_address: AccountAddress,
+    },
+    ReactivateUser {
+        user_address: AccountAddress,
+    },
+    // security
+    PauseModule {
+        module_id: u8,
+    },
+    UnpauseModule {
+        module_id: u8,
+    },
+    PauseAll,
+    UnpauseAll,
+    TransferAdmin {
+        new_admin: AccountAddress,
+    },
+}
+
+impl EntryFunctionCall {
+    /// Modules the calls below are made to.
+    pub const MODULES: &'static [&'static str] = &["asset", "governance", "position", "registry", "security"];
+
+    /// Module and function called, and the arguments.
+    pub fn entry_function(&self) -> Result<(&'static str, &'static str, Vec<MoveArg>)> {
+        use EntryFunctionCall::*;
+
+        Ok(match self {
+            CreateAsset { symbol, name, decimals, initial_supply } => (
+                "asset",
+                "create_asset",
+                vec![arg(symbol), arg(name), arg(decimals), arg(initial_supply)],
+            ),
+            Transfer { to, symbol, amount } => (
+                "asset",
+                "transfer",
+                vec![arg(to), arg(symbol), arg(amount)],
+            ),
+            Mint { to, symbol, amount } => (
+                "asset",
+                "mint",
+                vec![arg(to), arg(symbol), arg(amount)],
+            ),
+            Burn { from, symbol, amount } => (
+                "asset",
+                "burn",
+                vec![arg(from), arg(symbol), arg(amount)],
+            ),
+            CreateFund {
+                name,
+                description,
+                executor,
+                initial_members,
+                metadata_keys,
+                metadata_values,
+            } => {
+                if metadata_keys.len() != metadata_values.len() {
+                    return Err(AppError::invalid_input(
+                        "Fund metadata keys and values differ in length",
+                    ));
+                }
+                (
+                    "asset",
+                    "create_fund",
+                    vec![
+                        arg(name),
+                        arg(description),
+                        arg(executor),
+                        arg(initial_members),
+                        arg(metadata_keys),
+                        arg(metadata_values),
+                    ],
+                )
+            }
+            ExecuteTransaction { fund_id } => ("asset", "execute_transaction", vec![arg(fund_id)]),
+            Invest { fund_addr, target, amount } => (
+                "asset",
+                "invest",
+                vec![arg(fund_addr), arg(target), arg(amount)],
+            ),
+            WithdrawProfits { fund_addr, amount } => (
+                "asset",
+                "withdraw_profits",
+                vec![arg(fund_addr), arg(amount)],
+            ),
+            UpdateMemberShare { fund_addr, member_addr, new_share } => (
+                "asset",
+                "update_member_share",
+                vec![arg(fund_addr), arg(member_addr), arg(new_share)],
+            ),
+            CreateActuatorProposal { new_actuator, description } => (
+                "governance",
+                "create_actuator_proposal",
+                vec![arg(new_actuator), arg(description)],
+            ),
+            Vote { proposal_id, vote } => (
+                "governance",
+                "vote",
+                vec![arg(proposal_id), arg(vote)],
+            ),
+            EmergencyVeto { proposal_id } => (
+                "governance",
+                "emergency_veto",
+                vec![arg(proposal_id)],
+            ),
+            CreateTradeProposal { asset_symbol, size, price, is_entry, description } => (
+                "governance",
+                "create_trade_proposal",
+                vec![
+                    arg(asset_symbol),
+                    arg(size),
+                    arg(price),
+                    arg(is_entry),
+                    arg(description),
+                ],
+            ),
+            ExecuteProposal { proposal_id } => (
+                "governance",
+                "execute_proposal",
+                vec![arg(proposal_id)],
+            ),
+            SetActuator { new_actuator } => ("position", "set_actuator", vec![arg(new_actuator)]),
+            OpenPosition { asset_id, size, entry_price, is_long } => (
+                "position",
+                "open_position",
+                vec![arg(asset_id), arg(size), arg(entry_price), arg(is_long)],
+            ),
+            AllocateShares { position_id, user_address, shares } => (
+                "position",
+                "allocate_shares",
+                vec![arg(position_id), arg(user_address), arg(shares)],
+            ),
+            TransferShares { to, position_id, shares } => (
+                "position",
+                "transfer_shares",
+                vec![arg(to), arg(position_id), arg(shares)],
+            ),
+            ClosePosition { position_id, exit_price } => (
+                "position",
+                "close_position",
+                vec![arg(position_id), arg(exit_price)],
+            ),
+            ModifyPosition { position_id, new_size, new_price } => (
+                "position",
+                "modify_position",
+                vec![arg(position_id), arg(new_size), arg(new_price)],
+            ),
+            LiquidatePosition { position_id, liquidation_price } => (
+                "position",
+                "liquidate_position",
+                vec![arg(position_id), arg(liquidation_price)],
+            ),
+            RegisterUser { user_address, verification_level } => (
+                "registry",
+                "register_user",
+                vec![arg(user_address), arg(verification_level)],
+            ),
+            UpdateVerificationLevel { user_address, new_level } => (
+                "registry",
+                "update_verification_level",
+                vec![arg(user_address), arg(new_level)],
+            ),
+            DeactivateUser { user_address } => (
+                "registry",
+                "deactivate_user",
+                vec![arg(user_address)],
+            ),
+            ReactivateUser { user_address } => (
+                "registry",
+                "reactivate_user",
+                vec![arg(user_address)],
+            ),
+            PauseModule { module_id } => ("security", "pause_module", vec![arg(module_id)]),
+            UnpauseModule { module_id } => ("security", "unpause_module", vec![arg(module_id)]),
+            PauseAll => ("security", "pause_all", vec![]),
+            UnpauseAll => ("security", "unpause_all", vec![]),
+            TransferAdmin { new_admin } => ("security", "transfer_admin", vec![arg(new_admin)]),
+        })
+    }
+
+    /// Payload calling the function in the module published where
+    /// `modules` says.
+    pub fn payload(&self, modules: &ModuleConfig) -> Result<TransactionPayload> {
+        let (module, function, args) = self.entry_function()?;
+        let args = args.iter().map(MoveArg::to_bcs).collect::<Result<_>>()?;
+
+        Ok(TransactionPayload::EntryFunction(EntryFunction::new(
+            ModuleId::new(modules.module_address(module), identifier(module)?),
+            identifier(function)?,
+            vec![],
+            args,
+        )))
+    }
+
+    /// The call as a JSON payload for a wallet to sign.
+    pub fn json_payload(&self, modules: &ModuleConfig) -> Result<JsonEntryFunctionPayload> {
+        let (module, function, args) = self.entry_function()?;
+
+        Ok(JsonEntryFunctionPayload {
+            payload_type: "entry_function_payload".to_string(),
+            function: format!("{}::{}::{}", modules.module_address(module).to_hex_literal(), module, function),
+            type_arguments: vec![],
+            arguments: args.iter().map(MoveArg::to_json).collect(),
+        })
+    }
+
+    /// Unsigned transaction making the call.
+    pub fn raw_transaction(
+        &self,
+        modules: &ModuleConfig,
+        params: &TransactionParams,
+    ) -> Result<RawTransaction> {
+        Ok(RawTransaction::new(
+            params.sender,
+            params.sequence_number,
+            self.payload(modules)?,
+            params.max_gas_amount,
+            params.gas_unit_price,
+            params.expiration_timestamp_secs,
+            params.chain_id,
+        ))
+    }
+}
+
+/// Outcome of dry-running a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Simulation {
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: u64,
+    pub gas_unit_price: u64,
+    pub max_gas_amount: u64,
+    /// `gas_used * gas_unit_price`, in octas.
+    pub estimated_fee: u64,
+    /// Why the transaction would abort, if it would.
+    pub abort: Option<MoveAbort>,
+}
+
+impl Simulation {
+    pub fn new(
+        success: bool,
+        vm_status: String,
+        gas_used: u64,
+        gas_unit_price: u64,
+        max_gas_amount: u64,
+        modules: &ModuleConfig,
+    ) -> Self {
+        let abort = if success { None } else { MoveAbort::from_vm_status(&vm_status, modules) };
+
+        Self {
+            success,
+            vm_status,
+            gas_used,
+            gas_unit_price,
+            max_gas_amount,
+            estimated_fee: gas_used.saturating_mul(gas_unit_price),
+            abort,
+        }
+    }
+
+    pub fn from_user_transaction(txn: &UserTransaction, modules: &ModuleConfig) -> Self {
+        Self::new(
+            txn.info.success,
+            txn.info.vm_status.clone(),
+            txn.info.gas_used.0,
+            txn.request.gas_unit_price.0,
+            txn.request.max_gas_amount.0,
+            modules,
+        )
+    }
+
+    /// Why the transaction would fail, or None if it would succeed.
+    pub fn failure_reason(&self) -> Option<String> {
+        if self.success {
+            return None;
+        }
+
+        Some(match &self.abort {
+            Some(MoveAbort { name: Some(name), reason, .. }) => format!("{} ({})", reason, name),
+            Some(abort) => abort.reason.clone(),
+            None => self.vm_status.clone(),
+        })
+    }
+}
+
+fn arg<'a, T>(value: &'a T) -> MoveArg
+where
+    MoveArg: From<&'a T>,
+{
+    MoveArg::from(value)
+}
+
+fn identifier(name: &str) -> Result<Identifier> {
+    Identifier::new(name).map_err(AppError::internal)
+}
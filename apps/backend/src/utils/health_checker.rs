@@ -1,115 +1,480 @@
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use std::collections::HashMap;
-use tokio::time::{Duration, Instant};
+use tokio::time::{sleep, timeout, Duration, Instant};
 use aptos_sdk::rest_client::Client as AptosRestClient;
+use serde::Serialize;
 use url::Url;
 use crate::error::{AppError, Result};
 use crate::config::NodeConfig;
+use log::{info, warn};
 
-const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
 const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+/// How long an open circuit rejects traffic before a trial probe.
+const CIRCUIT_OPEN_COOLDOWN: Duration = Duration::from_secs(30);
+/// How often the prober wakes to look for nodes that are due.
+const PROBE_TICK: Duration = Duration::from_secs(1);
+/// Weight of the previous latency estimate in the moving average.
+const LATENCY_SMOOTHING: f64 = 0.7;
+/// Score penalty, in milliseconds, per version behind the best node. A
+/// hundred versions of lag costs as much as half a second of latency, so a
+/// node that is behind loses to a slower one that is caught up.
+const LAG_PENALTY_PER_VERSION: f64 = 5.0;
+/// Score penalty, in milliseconds, per second the ledger timestamp trails
+/// the wall clock.
+const STALENESS_PENALTY_PER_SEC: f64 = 100.0;
+
+/// Circuit breaker state of a node. Open circuits take no traffic until
+/// the cooldown passes; a half-open node is trusted again after one
+/// successful probe or request. A node found serving another chain is
+/// rejected for good.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+    Rejected,
+}
+
+/// Health of a node as exposed by `/api/v1/health/nodes`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeStatus {
+    pub url: String,
+    pub circuit: CircuitState,
+    pub consecutive_failures: u32,
+    pub latency_ms: Option<f64>,
+    pub ledger_version: Option<u64>,
+    pub lag: Option<u64>,
+    pub staleness_secs: Option<f64>,
+    pub chain_id: Option<u8>,
+    pub score: Option<f64>,
+    pub last_error: Option<String>,
+}
+
+/// Outcome of a single probe, gathered without holding the node lock.
+struct Probe {
+    latency: Duration,
+    ledger_version: u64,
+    ledger_timestamp_usecs: u64,
+    chain_id: u8,
+}
 
 #[derive(Debug)]
 struct NodeHealth {
     client: AptosRestClient,
-    last_check: Instant,
-    healthy: bool,
+    /// Insertion order; breaks score ties in favour of the primary node.
+    priority: usize,
+    check_interval: Duration,
+    timeout: Duration,
+    last_check: Option<Instant>,
+    circuit: CircuitState,
+    opened_at: Option<Instant>,
     consecutive_failures: u32,
+    latency: Option<Duration>,
+    ledger_version: Option<u64>,
+    staleness: Option<Duration>,
+    chain_id: Option<u8>,
+    last_error: Option<String>,
+}
+
+impl NodeHealth {
+    fn record_probe(&mut self, probe: Probe) {
+        let latency = match self.latency {
+            Some(previous) => previous.mul_f64(LATENCY_SMOOTHING) + probe.latency.mul_f64(1.0 - LATENCY_SMOOTHING),
+            None => probe.latency,
+        };
+        let now_usecs = chrono::Utc::now().timestamp_micros().max(0) as u64;
+
+        self.latency = Some(latency);
+        self.ledger_version = Some(probe.ledger_version);
+        self.staleness = Some(Duration::from_micros(now_usecs.saturating_sub(probe.ledger_timestamp_usecs)));
+        self.chain_id = Some(probe.chain_id);
+        self.record_success();
+    }
+
+    fn record_success(&mut self) {
+        if self.circuit == CircuitState::Rejected {
+            return;
+        }
+        self.circuit = CircuitState::Closed;
+        self.opened_at = None;
+        self.consecutive_failures = 0;
+        self.last_error = None;
+    }
+
+    fn record_failure(&mut self, error: String) {
+        if self.circuit == CircuitState::Rejected {
+            return;
+        }
+        self.consecutive_failures += 1;
+        self.last_error = Some(error);
+
+        // A failed trial reopens the circuit straight away
+        if self.circuit == CircuitState::HalfOpen || self.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+            self.trip();
+        }
+    }
+
+    fn trip(&mut self) {
+        self.circuit = CircuitState::Open;
+        self.opened_at = Some(Instant::now());
+    }
+
+    /// Takes the node out of rotation for good. Unlike an open circuit it
+    /// is never probed again, since a node on another chain does not
+    /// recover by waiting.
+    fn reject(&mut self, error: String) {
+        self.circuit = CircuitState::Rejected;
+        self.opened_at = None;
+        self.last_error = Some(error);
+    }
+
+    /// Moves an open circuit to half-open once its cooldown has passed.
+    fn refresh_circuit(&mut self) {
+        if self.circuit == CircuitState::Open
+            && self.opened_at.map_or(true, |at| at.elapsed() >= CIRCUIT_OPEN_COOLDOWN)
+        {
+            self.circuit = CircuitState::HalfOpen;
+        }
+    }
+
+    fn is_due(&self) -> bool {
+        match self.circuit {
+            CircuitState::Open | CircuitState::Rejected => false,
+            CircuitState::HalfOpen => true,
+            CircuitState::Closed => self.last_check.map_or(true, |at| at.elapsed() >= self.check_interval),
+        }
+    }
+
+    fn lag(&self, best_version: Option<u64>) -> Option<u64> {
+        Some(best_version?.saturating_sub(self.ledger_version?))
+    }
+
+    /// Lower is better. Nodes never probed successfully have no score.
+    fn score(&self, best_version: Option<u64>) -> Option<f64> {
+        let latency_ms = self.latency?.as_secs_f64() * 1000.0;
+        let lag = self.lag(best_version).unwrap_or(0) as f64;
+        let staleness = self.staleness.unwrap_or_default().as_secs_f64();
+
+        Some(latency_ms + lag * LAG_PENALTY_PER_VERSION + staleness * STALENESS_PENALTY_PER_SEC)
+    }
 }
 
 pub struct HealthChecker {
     node_health: Arc<RwLock<HashMap<String, NodeHealth>>>,
+    /// Chain every node must report. Unset until the first node is added
+    /// when no chain ID is configured.
+    expected_chain_id: Arc<RwLock<Option<u8>>>,
 }
 
 impl HealthChecker {
     pub fn new() -> Self {
         Self {
             node_health: Arc::new(RwLock::new(HashMap::new())),
+            expected_chain_id: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// A checker that rejects nodes serving any chain but `chain_id`.
+    pub fn with_chain_id(chain_id: u8) -> Self {
+        Self {
+            node_health: Arc::new(RwLock::new(HashMap::new())),
+            expected_chain_id: Arc::new(RwLock::new(Some(chain_id))),
+        }
+    }
+
+    pub async fn expected_chain_id(&self) -> Option<u8> {
+        *self.expected_chain_id.read().await
+    }
+
+    /// Adds a node after probing it. Fails if the node serves a different
+    /// chain than the one pinned; unreachable nodes are added with an open
+    /// circuit.
     pub async fn add_node(&self, config: &NodeConfig) -> Result<()> {
         let client = AptosRestClient::new(config.url.clone());
-        
-        // Perform initial health check
-        let initial_health = self.check_node_health_internal(&client).await?;
-        
-        let node_health = NodeHealth {
+
+        {
+            // Without a configured chain ID the first reachable node pins it
+            let mut expected = self.expected_chain_id.write().await;
+            if expected.is_none() {
+                if let Ok(info) = client.get_ledger_information().await {
+                    *expected = Some(info.into_inner().chain_id);
+                }
+            }
+        }
+
+        // Nodes on another chain are rejected outright
+        let probe = self.probe(&client, config.timeout).await;
+        if let Ok(probe) = &probe {
+            self.check_chain(probe.chain_id).await?;
+        }
+
+        let mut node = NodeHealth {
             client,
-            last_check: Instant::now(),
-            healthy: initial_health,
+            priority: 0,
+            check_interval: config.health_check_interval,
+            timeout: config.timeout,
+            last_check: Some(Instant::now()),
+            circuit: CircuitState::Closed,
+            opened_at: None,
             consecutive_failures: 0,
+            latency: None,
+            ledger_version: None,
+            staleness: None,
+            chain_id: None,
+            last_error: None,
         };
 
+        match probe {
+            Ok(probe) => node.record_probe(probe),
+            Err(e) => {
+                warn!("Node {} failed its initial health check: {}", config.url, e);
+                node.last_error = Some(e.to_string());
+                node.trip();
+            }
+        }
+
         let mut health = self.node_health.write().await;
-        health.insert(config.url.to_string(), node_health);
+        node.priority = health.len();
+        health.insert(config.url.to_string(), node);
         Ok(())
     }
 
+    /// Probes a single node now, regardless of its schedule.
     pub async fn check_node(&self, node_url: &str) -> Result<()> {
-        let now = Instant::now();
-        let mut health = self.node_health.write().await;
-        
-        match health.get(node_url) {
-            Some(node) if now.duration_since(node.last_check) < Duration::from_secs(60) && node.healthy => {
-                Ok(())
-            }
-            _ => {
-                // Perform health check
-                health.insert(node_url.to_string(), NodeHealth {
-                    client: AptosRestClient::new(Url::parse(node_url).map_err(|e| AppError::config_error(&e.to_string()))?),
-                    healthy: true,
-                    last_check: now,
-                    consecutive_failures: 0,
-                });
-                Ok(())
-            }
+        let target = {
+            let health = self.node_health.read().await;
+            health.get(node_url).map(|node| (node.client.clone(), node.timeout))
+        };
+
+        let Some((client, node_timeout)) = target else {
+            Url::parse(node_url).map_err(|e| AppError::config_error(&e.to_string()))?;
+            return Err(AppError::health_check_failed(&format!("unknown node {}", node_url)));
+        };
+
+        let probe = self.checked_probe(&client, node_timeout).await;
+        let failed = probe.as_ref().err().map(|e| e.to_string());
+        self.record(node_url, probe).await;
+
+        match failed {
+            Some(e) => Err(AppError::health_check_failed(&e)),
+            None => Ok(()),
         }
     }
 
+    /// Reports a failed request against a node.
     pub async fn mark_unhealthy(&self, node_url: &str) {
         let mut health = self.node_health.write().await;
         if let Some(node) = health.get_mut(node_url) {
-            node.healthy = false;
-            node.consecutive_failures += 1;
+            node.record_failure("request failed".to_string());
         }
     }
 
-    pub async fn get_healthy_client(&self) -> Result<AptosRestClient> {
-        let now = Instant::now();
+    /// Reports a successful request against a node, closing a half-open
+    /// circuit.
+    pub async fn record_success(&self, node_url: &str) {
         let mut health = self.node_health.write().await;
-        
-        for (_url, node) in health.iter_mut() {
-            // Check if we need to perform a health check
-            if node.last_check.elapsed() >= HEALTH_CHECK_INTERVAL {
-                node.healthy = self.check_node_health_internal(&node.client).await.unwrap_or(false);
-                node.last_check = now;
-                
-                if !node.healthy {
-                    node.consecutive_failures += 1;
-                } else {
-                    node.consecutive_failures = 0;
-                }
+        if let Some(node) = health.get_mut(node_url) {
+            if node.circuit == CircuitState::HalfOpen {
+                info!("Node {} recovered", node_url);
+                node.record_success();
             }
+        }
+    }
 
-            // Skip nodes with too many consecutive failures
-            if node.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
-                continue;
-            }
+    pub async fn get_healthy_client(&self) -> Result<AptosRestClient> {
+        self.get_healthy_node().await.map(|(_url, client)| client)
+    }
+
+    /// The best-scored node with a closed circuit, also returning its URL so
+    /// callers can report back on it. Half-open nodes are only used when no
+    /// closed node is left.
+    pub async fn get_healthy_node(&self) -> Result<(String, AptosRestClient)> {
+        self.get_healthy_node_excluding(&[]).await
+    }
+
+    /// Like `get_healthy_node`, preferring nodes not in `exclude`. Excluded
+    /// nodes are still returned when nothing else is available.
+    pub async fn get_healthy_node_excluding(&self, exclude: &[String]) -> Result<(String, AptosRestClient)> {
+        let mut health = self.node_health.write().await;
+        for node in health.values_mut() {
+            node.refresh_circuit();
+        }
+
+        let best_version = best_ledger_version(&health);
+        let rank = |node: &NodeHealth| {
+            (node.score(best_version).unwrap_or(f64::INFINITY), node.priority)
+        };
 
-            if node.healthy {
-                return Ok(node.client.clone());
+        let pick = |circuit: CircuitState, allow_excluded: bool| {
+            health
+                .iter()
+                .filter(|(url, node)| node.circuit == circuit && (allow_excluded || !exclude.contains(url)))
+                .min_by(|(_, a), (_, b)| rank(a).partial_cmp(&rank(b)).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(url, node)| (url.clone(), node.client.clone()))
+        };
+
+        pick(CircuitState::Closed, false)
+            .or_else(|| pick(CircuitState::HalfOpen, false))
+            .or_else(|| pick(CircuitState::Closed, true))
+            .or_else(|| pick(CircuitState::HalfOpen, true))
+            .ok_or_else(AppError::no_healthy_nodes)
+    }
+
+    /// Re-reads the chain ID of a node right before it is trusted with a
+    /// write. A node found on another chain is rejected.
+    pub async fn verify_chain(&self, node_url: &str) -> Result<()> {
+        let Some(expected) = self.expected_chain_id().await else {
+            return Ok(());
+        };
+
+        let client = {
+            let health = self.node_health.read().await;
+            health
+                .get(node_url)
+                .map(|node| node.client.clone())
+                .ok_or_else(|| AppError::health_check_failed(&format!("unknown node {}", node_url)))?
+        };
+
+        let info = client
+            .get_ledger_information()
+            .await
+            .map_err(|e| AppError::network_error(&format!("Failed to get chain ID: {}", e)))?;
+
+        let actual = info.into_inner().chain_id;
+        if actual != expected {
+            warn!("Node {} reports chain {} instead of {}", node_url, actual, expected);
+            let mut health = self.node_health.write().await;
+            if let Some(node) = health.get_mut(node_url) {
+                node.reject(format!("serves chain {}", actual));
             }
+            return Err(AppError::chain_id_mismatch(expected, actual));
+        }
+
+        Ok(())
+    }
+
+    /// Probes every node that is due. Network calls run without holding the
+    /// node lock, so request routing is never blocked by a slow node.
+    pub async fn probe_due_nodes(&self) {
+        let due: Vec<(String, AptosRestClient, Duration)> = {
+            let mut health = self.node_health.write().await;
+            health
+                .iter_mut()
+                .filter_map(|(url, node)| {
+                    node.refresh_circuit();
+                    node.is_due().then(|| (url.clone(), node.client.clone(), node.timeout))
+                })
+                .collect()
+        };
+
+        for (url, client, node_timeout) in due {
+            let probe = self.checked_probe(&client, node_timeout).await;
+            self.record(&url, probe).await;
+        }
+    }
+
+    /// Runs the background prober until the task is dropped.
+    pub async fn run(&self) {
+        loop {
+            self.probe_due_nodes().await;
+            sleep(PROBE_TICK).await;
         }
+    }
+
+    pub async fn node_statuses(&self) -> Vec<NodeStatus> {
+        let health = self.node_health.read().await;
+        let best_version = best_ledger_version(&health);
+
+        let mut nodes: Vec<(&String, &NodeHealth)> = health.iter().collect();
+        nodes.sort_by_key(|(_, node)| node.priority);
 
-        Err(AppError::no_healthy_nodes())
+        nodes
+            .into_iter()
+            .map(|(url, node)| NodeStatus {
+                url: url.clone(),
+                circuit: node.circuit,
+                consecutive_failures: node.consecutive_failures,
+                latency_ms: node.latency.map(|l| l.as_secs_f64() * 1000.0),
+                ledger_version: node.ledger_version,
+                lag: node.lag(best_version),
+                staleness_secs: node.staleness.map(|s| s.as_secs_f64()),
+                chain_id: node.chain_id,
+                score: node.score(best_version),
+                last_error: node.last_error.clone(),
+            })
+            .collect()
     }
 
-    async fn check_node_health_internal(&self, client: &AptosRestClient) -> Result<bool> {
-        match client.get_ledger_information().await {
-            Ok(_) => Ok(true),
-            Err(_) => Ok(false),
+    async fn record(&self, node_url: &str, probe: Result<Probe>) {
+        let mut health = self.node_health.write().await;
+        let Some(node) = health.get_mut(node_url) else {
+            return;
+        };
+
+        node.last_check = Some(Instant::now());
+        match probe {
+            Ok(probe) => {
+                if matches!(node.circuit, CircuitState::Open | CircuitState::HalfOpen) {
+                    info!("Node {} recovered", node_url);
+                }
+                node.record_probe(probe);
+            }
+            Err(e @ AppError::ChainIdMismatch { .. }) => {
+                warn!("Rejecting node {}: {}", node_url, e);
+                node.reject(e.to_string());
+            }
+            Err(e) => {
+                warn!("Health check of {} failed: {}", node_url, e);
+                node.record_failure(e.to_string());
+            }
         }
     }
-} 
+
+    async fn check_chain(&self, chain_id: u8) -> Result<()> {
+        match self.expected_chain_id().await {
+            Some(expected) if expected != chain_id => Err(AppError::chain_id_mismatch(expected, chain_id)),
+            _ => Ok(()),
+        }
+    }
+
+    /// A probe that also fails when the node serves the wrong chain.
+    async fn checked_probe(&self, client: &AptosRestClient, node_timeout: Duration) -> Result<Probe> {
+        let probe = self.probe(client, node_timeout).await?;
+        self.check_chain(probe.chain_id).await?;
+        Ok(probe)
+    }
+
+    /// Reads ledger information from a node.
+    async fn probe(&self, client: &AptosRestClient, node_timeout: Duration) -> Result<Probe> {
+        let started = Instant::now();
+        let info = timeout(node_timeout, client.get_ledger_information())
+            .await
+            .map_err(|_| AppError::network_error("health check timed out"))?
+            .map_err(|e| AppError::network_error(&e.to_string()))?
+            .into_inner();
+
+        Ok(Probe {
+            latency: started.elapsed(),
+            ledger_version: info.version,
+            ledger_timestamp_usecs: info.timestamp_usecs,
+            chain_id: info.chain_id,
+        })
+    }
+}
+
+impl Default for HealthChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Highest ledger version among nodes that are taking traffic.
+fn best_ledger_version(health: &HashMap<String, NodeHealth>) -> Option<u64> {
+    health
+        .values()
+        .filter(|node| matches!(node.circuit, CircuitState::Closed | CircuitState::HalfOpen))
+        .filter_map(|node| node.ledger_version)
+        .max()
+}
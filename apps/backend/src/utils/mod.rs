@@ -1,5 +1,7 @@
 mod rate_limiter;
 mod health_checker;
+mod sequence_numbers;
 
 pub use rate_limiter::RateLimiter;
-pub use health_checker::HealthChecker; 
\ No newline at end of file
+pub use health_checker::{CircuitState, HealthChecker, NodeStatus};
+pub use sequence_numbers::SequenceNumbers; 
\ No newline at end of file
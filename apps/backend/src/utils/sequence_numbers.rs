@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use aptos_sdk::types::account_address::AccountAddress;
+use crate::error::Result;
+
+/// Numbers handed out for one account since it was last synced.
+#[derive(Debug, Default)]
+struct AccountSequence {
+    /// Whether `next` is known to be ahead of the chain.
+    synced: bool,
+    next: u64,
+    /// Reserved numbers not yet known to be committed, with the expiration
+    /// of the transaction using them.
+    reserved: BTreeMap<u64, u64>,
+}
+
+impl AccountSequence {
+    fn has_expired(&self, now: u64) -> bool {
+        self.reserved.values().any(|expiration| *expiration <= now)
+    }
+
+    /// Catches up with `on_chain`, the number the chain expects next.
+    fn sync(&mut self, on_chain: u64, now: u64) {
+        // Everything below it has committed
+        self.reserved = self.reserved.split_off(&on_chain);
+
+        // A reservation that expired without committing left a gap no later
+        // transaction can get past, so numbering restarts from the chain
+        if !self.synced || self.has_expired(now) {
+            self.reserved.clear();
+            self.next = on_chain;
+        } else {
+            self.next = self.next.max(on_chain);
+        }
+        self.synced = true;
+    }
+}
+
+/// Hands out sequence numbers for server-signed accounts locally, so
+/// concurrent submissions from one account never share a number. The
+/// chain is only asked when an account is first used, after a
+/// reservation expired, or after the node rejected a number.
+#[derive(Debug, Default)]
+pub struct SequenceNumbers {
+    accounts: std::sync::Mutex<HashMap<AccountAddress, Arc<Mutex<AccountSequence>>>>,
+}
+
+impl SequenceNumbers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn account(&self, address: AccountAddress) -> Arc<Mutex<AccountSequence>> {
+        let mut accounts = self.accounts.lock().unwrap_or_else(|e| e.into_inner());
+        accounts.entry(address).or_default().clone()
+    }
+
+    /// Reserves the next number of `address` for a transaction expiring at
+    /// `expiration_timestamp_secs`. `on_chain` fetches the account's
+    /// current sequence number when a resync is due.
+    pub async fn reserve<F, Fut>(
+        &self,
+        address: AccountAddress,
+        expiration_timestamp_secs: u64,
+        on_chain: F,
+    ) -> Result<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<u64>>,
+    {
+        let account = self.account(address);
+        let mut account = account.lock().await;
+
+        let now = now_secs();
+        if !account.synced || account.has_expired(now) {
+            account.sync(on_chain().await?, now);
+        }
+
+        let sequence_number = account.next;
+        account.next += 1;
+        account.reserved.insert(sequence_number, expiration_timestamp_secs);
+        Ok(sequence_number)
+    }
+
+    /// Marks `sequence_number` as committed.
+    pub async fn complete(&self, address: AccountAddress, sequence_number: u64) {
+        self.account(address).lock().await.reserved.remove(&sequence_number);
+    }
+
+    /// Returns a number whose transaction never reached the mempool. Only
+    /// the latest number can be reused; an earlier one leaves a gap that is
+    /// recovered once the transactions after it expire.
+    pub async fn release(&self, address: AccountAddress, sequence_number: u64) {
+        let account = self.account(address);
+        let mut account = account.lock().await;
+
+        if account.reserved.remove(&sequence_number).is_some() && account.next == sequence_number + 1 {
+            account.next = sequence_number;
+        }
+    }
+
+    /// Forgets what was handed out for `address`, so the next reservation
+    /// starts again from the chain. Used when the node reports a sequence
+    /// number as too old or too new.
+    pub async fn resync(&self, address: AccountAddress) {
+        let account = self.account(address);
+        let mut account = account.lock().await;
+
+        account.synced = false;
+        account.reserved.clear();
+    }
+
+    /// Numbers reserved for `address` and not yet committed.
+    pub async fn in_flight(&self, address: AccountAddress) -> Vec<u64> {
+        self.account(address).lock().await.reserved.keys().copied().collect()
+    }
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
@@ -0,0 +1,40 @@
+use super::*;
+use actix_web::test;
+use backend::db::schema::ProcessedEvent;
+
+#[tokio::test]
+async fn test_get_processed_events_empty() {
+    let (state, _) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/admin/events?limit=10")
+        .insert_header(super::auth_header(TEST_ADMIN))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let events: Vec<ProcessedEvent> = test::read_body_json(resp).await;
+    assert!(events.is_empty());
+}
+
+#[tokio::test]
+async fn test_admin_routes_require_admin() {
+    let (state, _) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    for uri in ["/api/v1/admin/events", "/api/v1/admin/dead-letters", "/api/v1/admin/sync"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 401, "{} without a token", uri);
+
+        // Signed in, but not a platform admin
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .insert_header(super::auth_header("0x1"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 403, "{} as a non-admin", uri);
+    }
+}
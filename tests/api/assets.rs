@@ -1,38 +1,27 @@
 use super::*;
 use actix_web::test;
-use backend::api::routes::assets::{
-    CreateFundWalletRequest,
-    MemberInput,
-    InvestmentRequest,
-    WithdrawRequest,
-    UpdateShareRequest,
-};
 
 #[tokio::test]
 async fn test_create_fund_wallet() {
-    let (state, _) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund first
-    let fund = crate::test_helpers::create_test_fund(&app.app_data::<web::Data<AppState>>().unwrap().db, "Test Fund").await.unwrap();
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
 
     // Create fund wallet request
-    let req = CreateFundWalletRequest {
-        actuator_address: "0x123".to_string(),
-        members: vec![
-            MemberInput {
-                address: "0x456".to_string(),
-                ownership_share: 5000, // 50%
-            },
-            MemberInput {
-                address: "0x789".to_string(),
-                ownership_share: 5000, // 50%
-            },
+    let req = serde_json::json!({
+        "wallet_address": "0x1",
+        "actuator_address": "0x123",
+        "members": [
+            { "address": "0x456", "ownership_share": 5000 }, // 50%
+            { "address": "0x789", "ownership_share": 5000 }, // 50%
         ],
-    };
+    });
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/wallet", fund.id))
+        .insert_header(super::auth_header("0x1"))
         .set_json(&req)
         .to_request();
 
@@ -42,29 +31,25 @@ async fn test_create_fund_wallet() {
 
 #[tokio::test]
 async fn test_create_fund_wallet_invalid_shares() {
-    let (state, _) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund first
-    let fund = crate::test_helpers::create_test_fund(&app.app_data::<web::Data<AppState>>().unwrap().db, "Test Fund").await.unwrap();
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
 
     // Create fund wallet request with invalid shares (not 100%)
-    let req = CreateFundWalletRequest {
-        actuator_address: "0x123".to_string(),
-        members: vec![
-            MemberInput {
-                address: "0x456".to_string(),
-                ownership_share: 3000, // 30%
-            },
-            MemberInput {
-                address: "0x789".to_string(),
-                ownership_share: 3000, // 30%
-            },
+    let req = serde_json::json!({
+        "wallet_address": "0x1",
+        "actuator_address": "0x123",
+        "members": [
+            { "address": "0x456", "ownership_share": 3000 }, // 30%
+            { "address": "0x789", "ownership_share": 3000 }, // 30%
         ],
-    };
+    });
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/wallet", fund.id))
+        .insert_header(super::auth_header("0x1"))
         .set_json(&req)
         .to_request();
 
@@ -75,21 +60,22 @@ async fn test_create_fund_wallet_invalid_shares() {
 #[tokio::test]
 async fn test_invest_in_fund() {
     let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund and asset
     let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
     let asset = crate::test_helpers::create_test_asset(&pool).await.unwrap();
 
     // Create investment request
-    let req = InvestmentRequest {
-        target_address: "0x123".to_string(),
-        amount: 1000,
-        asset_id: asset.id,
-    };
+    let req = serde_json::json!({
+        "target_address": "0x123",
+        "amount": 1000,
+        "asset_id": asset.id,
+    });
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/wallet/invest", fund.id))
+        .insert_header(super::auth_header("0x1"))
         .set_json(&req)
         .to_request();
 
@@ -100,20 +86,19 @@ async fn test_invest_in_fund() {
 #[tokio::test]
 async fn test_withdraw_profits() {
     let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund and investment
     let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
     let asset = crate::test_helpers::create_test_asset(&pool).await.unwrap();
-    let investment = crate::test_helpers::create_test_investment(&pool, fund.id, asset.id).await.unwrap();
+    crate::test_helpers::create_test_investment(&pool, fund.id, asset.id).await.unwrap();
 
     // Create withdrawal request
-    let req = WithdrawRequest {
-        amount: 500, // Withdraw half
-    };
+    let req = serde_json::json!({ "amount": 500 }); // Withdraw half
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/wallet/withdraw", fund.id))
+        .insert_header(super::auth_header("0x1"))
         .set_json(&req)
         .to_request();
 
@@ -124,22 +109,69 @@ async fn test_withdraw_profits() {
 #[tokio::test]
 async fn test_update_member_share() {
     let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund and member
     let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
     let member = crate::test_helpers::create_test_member(&pool, fund.id, 5000).await.unwrap();
 
     // Create share update request
-    let req = UpdateShareRequest {
-        new_share: 6000,
-    };
+    let req = serde_json::json!({ "new_share": 6000 });
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/wallet/members/{}/share", fund.id, member.member_address))
+        .insert_header(super::auth_header(TEST_ADMIN))
         .set_json(&req)
         .to_request();
 
     let resp = test::call_service(&app, req).await;
     assert!(resp.status().is_success());
-} 
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_update_member_share_requires_admin() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    let member = crate::test_helpers::create_test_member(&pool, fund.id, 5000).await.unwrap();
+
+    // Neither the executor nor the member may change a share
+    for address in ["0x1", member.member_address.as_str()] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/funds/{}/wallet/members/{}/share", fund.id, member.member_address))
+            .insert_header(super::auth_header(address))
+            .set_json(&serde_json::json!({ "new_share": 10000 }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 403);
+    }
+}
+
+#[tokio::test]
+async fn test_invest_payload() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_fund_wallet(&pool, fund.id).await.unwrap();
+
+    // Requested by the actuator, for the wallet it acts for
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/wallet/invest/payload", fund.id))
+        .insert_header(super::auth_header("0x6"))
+        .set_json(&serde_json::json!({
+            "target_address": "0x123",
+            "amount": 1000,
+            "asset_id": 1,
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    let payload: serde_json::Value = test::read_body_json(resp).await;
+    assert!(payload["function"].as_str().unwrap().ends_with("::asset::invest"));
+    assert_eq!(payload["type_arguments"], serde_json::json!([]));
+    assert_eq!(payload["arguments"], serde_json::json!(["0x2", "0x123", "1000"]));
+}
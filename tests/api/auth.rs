@@ -0,0 +1,113 @@
+use super::*;
+use actix_web::test;
+use aptos_sdk::crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    SigningKey, Uniform, ValidCryptoMaterial,
+};
+use aptos_sdk::types::transaction::authenticator::AuthenticationKey;
+use backend::api::routes::auth::login_message;
+
+async fn request_nonce<S, B>(app: &S, address: &str) -> serde_json::Value
+where
+    S: actix_web::dev::Service<actix_http::Request, Response = actix_web::dev::ServiceResponse<B>, Error = actix_web::Error>,
+    B: actix_web::body::MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri("/api/v1/auth/nonce")
+        .set_json(&serde_json::json!({ "address": address }))
+        .to_request();
+
+    let resp = test::call_service(app, req).await;
+    assert!(resp.status().is_success());
+    test::read_body_json(resp).await
+}
+
+#[tokio::test]
+async fn test_request_nonce() {
+    let (state, _) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let body = request_nonce(&app, "0x123").await;
+    let address = AccountAddress::from_hex_literal("0x123").unwrap();
+    let nonce = body["nonce"].as_str().unwrap();
+
+    assert_eq!(body["address"], "0x123");
+    assert_eq!(body["message"], login_message(&address, nonce));
+
+    // Every request gets a fresh nonce
+    let other = request_nonce(&app, "0x123").await;
+    assert_ne!(other["nonce"], body["nonce"]);
+}
+
+#[tokio::test]
+async fn test_login_rejects_bad_signature_and_burns_nonce() {
+    let (state, _) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let public_key = Ed25519PublicKey::from(&private_key);
+    let address = AuthenticationKey::ed25519(&public_key).account_address();
+
+    let body = request_nonce(&app, &address.to_hex_literal()).await;
+    let nonce = body["nonce"].as_str().unwrap().to_string();
+    let message = body["message"].as_str().unwrap().to_string();
+
+    // Signed by a different key than the one presented
+    let other_key = Ed25519PrivateKey::generate_for_testing();
+    let forged = other_key.sign_arbitrary_message(message.as_bytes());
+    let req = test::TestRequest::post()
+        .uri("/api/v1/auth/login")
+        .set_json(&serde_json::json!({
+            "address": address.to_hex_literal(),
+            "nonce": nonce,
+            "public_key": hex::encode(public_key.to_bytes()),
+            "signature": hex::encode(forged.to_bytes()),
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 401);
+
+    // The nonce was consumed by the failed attempt
+    let signature = private_key.sign_arbitrary_message(message.as_bytes());
+    let req = test::TestRequest::post()
+        .uri("/api/v1/auth/login")
+        .set_json(&serde_json::json!({
+            "address": address.to_hex_literal(),
+            "nonce": nonce,
+            "public_key": hex::encode(public_key.to_bytes()),
+            "signature": hex::encode(signature.to_bytes()),
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn test_login_rejects_unrelated_full_message() {
+    let (state, _) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let private_key = Ed25519PrivateKey::generate_for_testing();
+    let public_key = Ed25519PublicKey::from(&private_key);
+    let address = AuthenticationKey::ed25519(&public_key).account_address();
+
+    let body = request_nonce(&app, &address.to_hex_literal()).await;
+    let full_message = "APTOS\nmessage: something else\nnonce: 1";
+    let signature = private_key.sign_arbitrary_message(full_message.as_bytes());
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/auth/login")
+        .set_json(&serde_json::json!({
+            "address": address.to_hex_literal(),
+            "nonce": body["nonce"],
+            "public_key": hex::encode(public_key.to_bytes()),
+            "signature": hex::encode(signature.to_bytes()),
+            "full_message": full_message,
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 401);
+}
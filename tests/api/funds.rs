@@ -1,24 +1,18 @@
 use super::*;
 use actix_web::test;
-use backend::api::routes::funds::{
-    CreateFundRequest,
-    AddMemberRequest,
-    RemoveMemberRequest,
-};
+use backend::{api::middleware::RequestId, error::ErrorEnvelope};
 
 #[tokio::test]
 async fn test_create_fund() {
     let (state, _) = create_test_app_state().await;
     let app = create_test_app(web::Data::new(state)).await;
 
-    let req = CreateFundRequest {
-        name: "Test Fund".to_string(),
-        executor_address: "0x123".to_string(),
-    };
-
     let req = test::TestRequest::post()
         .uri("/api/v1/funds")
-        .set_json(&req)
+        .set_json(&serde_json::json!({
+            "name": "Test Fund",
+            "executor_address": "0x123",
+        }))
         .to_request();
 
     let resp = test::call_service(&app, req).await;
@@ -44,40 +38,15 @@ async fn test_get_fund() {
 #[tokio::test]
 async fn test_add_member() {
     let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund first
     let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
 
-    let req = AddMemberRequest {
-        member_address: "0x123".to_string(),
-    };
-
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/members", fund.id))
-        .set_json(&req)
-        .to_request();
-
-    let resp = test::call_service(&app, req).await;
-    assert!(resp.status().is_success());
-}
-
-#[tokio::test]
-async fn test_remove_member() {
-    let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
-
-    // Create test fund and member
-    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
-    let member = crate::test_helpers::create_test_member(&pool, fund.id, 5000).await.unwrap();
-
-    let req = RemoveMemberRequest {
-        member_address: member.member_address.clone(),
-    };
-
-    let req = test::TestRequest::delete()
-        .uri(&format!("/api/v1/funds/{}/members", fund.id))
-        .set_json(&req)
+        .insert_header(super::auth_header("0x1"))
+        .set_json(&serde_json::json!({ "member_address": "0x123" }))
         .to_request();
 
     let resp = test::call_service(&app, req).await;
@@ -106,37 +75,51 @@ async fn test_create_fund_duplicate_name() {
     let _ = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
 
     // Try to create another fund with same name
-    let req = CreateFundRequest {
-        name: "Test Fund".to_string(),
-        executor_address: "0x123".to_string(),
-    };
-
     let req = test::TestRequest::post()
         .uri("/api/v1/funds")
-        .set_json(&req)
+        .set_json(&serde_json::json!({
+            "name": "Test Fund",
+            "executor_address": "0x123",
+        }))
         .to_request();
 
     let resp = test::call_service(&app, req).await;
-    assert_eq!(resp.status().as_u16(), 400); // Bad request - duplicate name
+    assert_eq!(resp.status().as_u16(), 409); // Conflict - duplicate name
 }
 
 #[tokio::test]
-async fn test_remove_nonexistent_member() {
-    let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+async fn test_error_envelope() {
+    let (state, _) = create_test_app_state().await;
+    let app = test::init_service(
+        App::new()
+            .wrap(RequestId)
+            .app_data(web::Data::new(state))
+            .configure(backend::api::configure_extractors)
+            .service(web::scope("/api/v1").service(routes::funds::scope()))
+    ).await;
 
-    // Create test fund
-    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    let req = test::TestRequest::get()
+        .uri("/api/v1/funds/999")
+        .insert_header(("x-request-id", "req-123"))
+        .to_request();
 
-    let req = RemoveMemberRequest {
-        member_address: "0x999".to_string(),
-    };
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 404);
+    assert_eq!(resp.headers().get("x-request-id").unwrap(), "req-123");
 
-    let req = test::TestRequest::delete()
-        .uri(&format!("/api/v1/funds/{}/members", fund.id))
-        .set_json(&req)
+    let body: ErrorEnvelope = test::read_body_json(resp).await;
+    assert_eq!(body.error.code, "not_found");
+    assert_eq!(body.error.request_id.as_deref(), Some("req-123"));
+
+    // Extractor failures use the same envelope, with a generated ID
+    let req = test::TestRequest::get()
+        .uri("/api/v1/funds/not-a-number")
         .to_request();
 
     let resp = test::call_service(&app, req).await;
-    assert_eq!(resp.status().as_u16(), 404); // Not found
-} 
\ No newline at end of file
+    assert_eq!(resp.status().as_u16(), 400);
+
+    let body: ErrorEnvelope = test::read_body_json(resp).await;
+    assert_eq!(body.error.code, "invalid_input");
+    assert!(body.error.request_id.is_some());
+}
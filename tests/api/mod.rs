@@ -1,19 +1,23 @@
+pub mod admin;
 pub mod assets;
+pub mod auth;
 pub mod funds;
 pub mod proposals;
-pub mod investments;
+pub mod positions;
+pub mod transactions;
 
 use actix_web::{test, web, App};
+use aptos_sdk::types::account_address::AccountAddress;
 use backend::{
     AppState,
-    api::routes,
+    api::{middleware::{Authentication, JwtKeys}, routes},
+    config::{AuthConfig, ExecutionConfig, GovernanceConfig},
     db::operations,
-    error::Result,
+    governance::ProposalExecutor,
     Client,
 };
 use sqlx::sqlite::SqlitePool;
-use crate::{setup_test_db, AptosRestClientInterface};
-use mockall::Mock;
+use crate::setup_test_db;
 
 // Helper function to create test app state
 pub async fn create_test_app_state() -> (AppState, SqlitePool) {
@@ -28,7 +32,7 @@ pub async fn create_test_app_state() -> (AppState, SqlitePool) {
     (state, pool)
 }
 
-// Helper function to create test app
+// Helper function to create a test app without authentication, mounted like main.rs
 pub async fn create_test_app(state: web::Data<AppState>) -> impl actix_web::dev::Service<
     actix_http::Request,
     Response = actix_web::dev::ServiceResponse,
@@ -37,6 +41,44 @@ pub async fn create_test_app(state: web::Data<AppState>) -> impl actix_web::dev:
     test::init_service(
         App::new()
             .app_data(state)
-            .configure(routes::configure)
+            .service(web::scope("/api/v1").configure(routes::configure))
     ).await
+}
+
+// Helper function to create a test app that requires tokens, mounted like main.rs
+pub async fn create_authenticated_test_app(state: web::Data<AppState>) -> impl actix_web::dev::Service<
+    actix_http::Request,
+    Response = actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
+    Error = actix_web::Error,
+> {
+    // No executor key, so proposals are never submitted on chain
+    let executor = ProposalExecutor::new(state.get_ref().clone(), ExecutionConfig::default()).unwrap();
+
+    test::init_service(
+        App::new()
+            .wrap(Authentication::new(test_jwt_keys()))
+            .app_data(state)
+            .app_data(web::Data::new(test_jwt_keys()))
+            .app_data(web::Data::new(AuthConfig {
+                admins: vec![AccountAddress::from_hex_literal(TEST_ADMIN).unwrap()],
+                ..AuthConfig::default()
+            }))
+            .app_data(web::Data::new(GovernanceConfig::default()))
+            .app_data(web::Data::new(executor))
+            .service(web::scope("/api/v1").configure(routes::configure))
+    ).await
+}
+
+// Platform admin configured in the authenticated test app
+pub const TEST_ADMIN: &str = "0xad";
+
+pub fn test_jwt_keys() -> JwtKeys {
+    JwtKeys::new(&[7u8; 32], std::time::Duration::from_secs(3600))
+}
+
+// Helper function to authenticate a request as `address`
+pub fn auth_header(address: &str) -> (&'static str, String) {
+    let address = AccountAddress::from_hex_literal(address).unwrap();
+    let (token, _) = test_jwt_keys().issue(&address).unwrap();
+    ("Authorization", format!("Bearer {}", token))
 } 
\ No newline at end of file
@@ -0,0 +1,39 @@
+use super::*;
+use actix_web::test;
+use backend::db::schema::Position;
+
+#[tokio::test]
+async fn test_get_fund_positions() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    let asset = crate::test_helpers::create_test_asset(&pool).await.unwrap();
+    operations::create_position(&pool, fund.id, asset.id, 100, 10, true).await.unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/funds/{}/positions?status=open", fund.id))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let positions: Vec<Position> = test::read_body_json(resp).await;
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0].status, "open");
+}
+
+#[tokio::test]
+async fn test_get_fund_positions_invalid_status() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/funds/{}/positions?status=pending", fund.id))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
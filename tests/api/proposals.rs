@@ -1,54 +1,77 @@
 use super::*;
 use actix_web::test;
-use backend::api::routes::proposals::{
-    CreateProposalRequest,
-    VoteRequest,
-    EmergencyVetoRequest,
-};
+use aptos_sdk::types::account_address::AccountAddress;
+use backend::api::middleware::JwtKeys;
+use backend::db::schema::{Proposal, PROPOSAL_FAILED, PROPOSAL_PASSED};
 use chrono::{Utc, Duration};
 
+// Roles held in the fund created by `create_test_fund` and `create_test_member`
+const EXECUTOR: &str = "0x1";
+const MEMBER: &str = "0x3";
+
 #[tokio::test]
 async fn test_create_proposal() {
-    let (state, _) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
-    // Create test fund first
-    let fund = crate::test_helpers::create_test_fund(&app.app_data::<web::Data<AppState>>().unwrap().db, "Test Fund").await.unwrap();
+    // Create test fund and member first
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
 
     // Create proposal request
-    let req = CreateProposalRequest {
-        title: "Test Proposal".to_string(),
-        description: "Test Description".to_string(),
-        proposer_address: "0x123".to_string(),
-        end_time: Utc::now() + Duration::days(7),
-    };
+    let req = serde_json::json!({
+        "title": "Test Proposal",
+        "description": "Test Description",
+        "end_time": Utc::now() + Duration::days(7),
+    });
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/proposals", fund.id))
+        .insert_header(super::auth_header(MEMBER))
         .set_json(&req)
         .to_request();
 
     let resp = test::call_service(&app, req).await;
     assert!(resp.status().is_success());
+    let created: Proposal = test::read_body_json(resp).await;
+    assert!(!created.synced);
+    assert_eq!(created.proposer_address.as_deref(), Some(MEMBER));
+
+    // Its creation on chain links it rather than indexing another proposal
+    let mut conn = pool.acquire().await.unwrap();
+    let end_time = (Utc::now() + Duration::days(1)).into();
+    let linked = operations::sync_chain_proposal(
+        &mut conn,
+        &crate::test_helpers::governance_address(),
+        0,
+        None,
+        MEMBER,
+        "Test Description",
+        end_time,
+        1,
+    ).await
+        .unwrap();
+    assert_eq!(linked.id, created.id);
+    assert!(linked.synced);
+    assert_eq!(linked.fund_id, Some(fund.id));
 }
 
 #[tokio::test]
 async fn test_vote_on_proposal() {
     let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund and proposal
     let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
-    let proposal = crate::test_helpers::create_test_proposal(&pool, "Test Proposal").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
 
     // Create vote request
-    let req = VoteRequest {
-        voter_address: "0x123".to_string(),
-        vote_type: true, // Yes vote
-    };
+    let req = serde_json::json!({ "vote_type": true }); // Yes vote
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/proposals/{}/votes", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
         .set_json(&req)
         .to_request();
 
@@ -59,20 +82,15 @@ async fn test_vote_on_proposal() {
 #[tokio::test]
 async fn test_emergency_veto() {
     let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund and proposal
     let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
-    let proposal = crate::test_helpers::create_test_proposal(&pool, "Test Proposal").await.unwrap();
-
-    // Create veto request
-    let req = EmergencyVetoRequest {
-        initiator_address: "0x123".to_string(),
-    };
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/proposals/{}/emergency-veto", fund.id, proposal.id))
-        .set_json(&req)
+        .insert_header(super::auth_header(EXECUTOR))
         .to_request();
 
     let resp = test::call_service(&app, req).await;
@@ -81,19 +99,29 @@ async fn test_emergency_veto() {
     // Verify proposal is vetoed
     let vetoed_proposal = operations::get_proposal_by_id(&pool, proposal.id).await.unwrap();
     assert!(vetoed_proposal.vetoed);
+
+    // A vetoed proposal cannot be vetoed again
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/emergency-veto", fund.id, proposal.id))
+        .insert_header(super::auth_header(EXECUTOR))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 409);
 }
 
 #[tokio::test]
 async fn test_get_proposal() {
     let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund and proposal
     let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
-    let proposal = crate::test_helpers::create_test_proposal(&pool, "Test Proposal").await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
 
     let req = test::TestRequest::get()
         .uri(&format!("/api/v1/funds/{}/proposals/{}", fund.id, proposal.id))
+        .insert_header(super::auth_header(EXECUTOR))
         .to_request();
 
     let resp = test::call_service(&app, req).await;
@@ -103,19 +131,18 @@ async fn test_get_proposal() {
 #[tokio::test]
 async fn test_vote_on_nonexistent_proposal() {
     let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund
     let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
 
     // Try to vote on nonexistent proposal
-    let req = VoteRequest {
-        voter_address: "0x123".to_string(),
-        vote_type: true,
-    };
+    let req = serde_json::json!({ "vote_type": true });
 
     let req = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/proposals/999/votes", fund.id))
+        .insert_header(super::auth_header(MEMBER))
         .set_json(&req)
         .to_request();
 
@@ -126,20 +153,19 @@ async fn test_vote_on_nonexistent_proposal() {
 #[tokio::test]
 async fn test_double_vote() {
     let (state, pool) = create_test_app_state().await;
-    let app = create_test_app(web::Data::new(state)).await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
 
     // Create test fund and proposal
     let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
-    let proposal = crate::test_helpers::create_test_proposal(&pool, "Test Proposal").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
 
     // First vote
-    let req = VoteRequest {
-        voter_address: "0x123".to_string(),
-        vote_type: true,
-    };
+    let req = serde_json::json!({ "vote_type": true });
 
     let first_vote = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/proposals/{}/votes", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
         .set_json(&req)
         .to_request();
 
@@ -149,9 +175,314 @@ async fn test_double_vote() {
     // Try to vote again
     let second_vote = test::TestRequest::post()
         .uri(&format!("/api/v1/funds/{}/proposals/{}/votes", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
         .set_json(&req)
         .to_request();
 
     let resp = test::call_service(&app, second_vote).await;
-    assert_eq!(resp.status().as_u16(), 400); // Bad request - already voted
-} 
\ No newline at end of file
+    assert_eq!(resp.status().as_u16(), 409); // Conflict - already voted
+}
+
+#[tokio::test]
+async fn test_vote_requires_token() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/votes", fund.id, proposal.id))
+        .set_json(&serde_json::json!({ "vote_type": true }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 401);
+
+    // A token signed with another secret is rejected as well
+    let (token, _) = JwtKeys::new(&[1u8; 32], std::time::Duration::from_secs(60))
+        .issue(&AccountAddress::from_hex_literal("0x123").unwrap())
+        .unwrap();
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/votes", fund.id, proposal.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(&serde_json::json!({ "vote_type": true }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn test_vote_requires_active_membership() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
+
+    // Neither outsiders, the executor nor admins vote on a member's behalf
+    for address in ["0x123", EXECUTOR, TEST_ADMIN] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/funds/{}/proposals/{}/votes", fund.id, proposal.id))
+            .insert_header(super::auth_header(address))
+            .set_json(&serde_json::json!({ "vote_type": true }))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status().as_u16(), 403);
+    }
+
+    // Members who left the fund lose their vote
+    sqlx::query("UPDATE fund_members SET status = 'removed' WHERE fund_id = ?")
+        .bind(fund.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/votes", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .set_json(&serde_json::json!({ "vote_type": true }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn test_emergency_veto_requires_executor() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/emergency-veto", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Admins pass executor checks
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/emergency-veto", fund.id, proposal.id))
+        .insert_header(super::auth_header(TEST_ADMIN))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+}
+
+#[tokio::test]
+async fn test_proposals_are_scoped_to_their_fund() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    let other = crate::test_helpers::create_test_fund(&pool, "Other Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    crate::test_helpers::create_test_member(&pool, other.id, 100).await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, other.id, "Other Proposal").await.unwrap();
+
+    // Reachable through its own fund only
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 404);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/votes", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .set_json(&serde_json::json!({ "vote_type": true }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn test_list_proposals_by_status() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    let active = crate::test_helpers::create_test_proposal(&pool, fund.id, "Active").await.unwrap();
+    let vetoed = crate::test_helpers::create_test_proposal(&pool, fund.id, "Vetoed").await.unwrap();
+    operations::emergency_veto_proposal(&pool, fund.id, vetoed.id).await.unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/funds/{}/proposals?status=active", fund.id))
+        .insert_header(super::auth_header(EXECUTOR))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    let proposals: Vec<serde_json::Value> = test::read_body_json(resp).await;
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(proposals[0]["id"], active.id);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/funds/{}/proposals", fund.id))
+        .insert_header(super::auth_header(EXECUTOR))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    let proposals: Vec<serde_json::Value> = test::read_body_json(resp).await;
+    assert_eq!(proposals.len(), 2);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/funds/{}/proposals?status=pending", fund.id))
+        .insert_header(super::auth_header(EXECUTOR))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_proposal_tally() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
+    operations::vote_on_proposal(&pool, proposal.id, AccountAddress::from_hex_literal(MEMBER).unwrap(), true).await.unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/tally", fund.id, proposal.id))
+        .insert_header(super::auth_header(EXECUTOR))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    let tally: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(tally["yes_weight"], 100);
+    assert_eq!(tally["no_weight"], 0);
+    assert_eq!(tally["closed"], false);
+    assert_eq!(tally["projected"], "passed");
+    assert_eq!(tally["status"], "active");
+}
+
+#[tokio::test]
+async fn test_closed_proposal_is_finalized() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    let proposal = operations::create_proposal(
+        &pool,
+        fund.id,
+        "Closed Proposal",
+        "Voting already ended",
+        (Utc::now() - Duration::hours(1)).into(),
+    ).await.unwrap();
+
+    // Late votes are rejected
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/votes", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .set_json(&serde_json::json!({ "vote_type": true }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 400);
+
+    // Without votes the proposal fails once tallied
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/tally", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    let tally: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(tally["closed"], true);
+    assert_eq!(tally["status"], "failed");
+
+    let proposal = operations::get_proposal_by_id(&pool, proposal.id).await.unwrap();
+    assert_eq!(proposal.status, PROPOSAL_FAILED);
+}
+
+#[tokio::test]
+async fn test_execute_proposal_requires_executor_key() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    let proposal = crate::test_helpers::create_chain_proposal(&pool, fund.id, 0).await.unwrap();
+    operations::finalize_proposal(&pool, proposal.id, PROPOSAL_PASSED).await.unwrap();
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/execute", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/execute", fund.id, proposal.id))
+        .insert_header(super::auth_header(EXECUTOR))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 501);
+
+    // Nothing was attempted
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/executions", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    let executions: Vec<serde_json::Value> = test::read_body_json(resp).await;
+    assert!(executions.is_empty());
+}
+
+#[tokio::test]
+async fn test_vote_payload() {
+    let (state, pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
+
+    // Not on chain yet, so there is nothing to vote on
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/votes/payload", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .set_json(&serde_json::json!({ "vote_type": false }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 400);
+
+    let proposal = crate::test_helpers::create_chain_proposal(&pool, fund.id, 0).await.unwrap();
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/funds/{}/proposals/{}/votes/payload", fund.id, proposal.id))
+        .insert_header(super::auth_header(MEMBER))
+        .set_json(&serde_json::json!({ "vote_type": false }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    let payload: serde_json::Value = test::read_body_json(resp).await;
+    assert!(payload["function"].as_str().unwrap().ends_with("::governance::vote"));
+    assert_eq!(payload["arguments"], serde_json::json!([proposal.chain_id.to_string(), false]));
+
+    // Building the payload casts no vote
+    let votes = operations::get_proposal_votes(&pool, proposal.id).await.unwrap();
+    assert!(votes.is_empty());
+}
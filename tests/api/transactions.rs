@@ -0,0 +1,84 @@
+use super::*;
+use actix_web::test;
+use aptos_sdk::{
+    bcs,
+    crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform},
+    types::{chain_id::ChainId, transaction::{authenticator::AuthenticationKey, SignedTransaction}},
+};
+use backend::{
+    config::ModuleConfig,
+    transactions::{EntryFunctionCall, TransactionParams},
+};
+
+fn signed_vote() -> SignedTransaction {
+    let key = Ed25519PrivateKey::generate_for_testing();
+    let sender = AuthenticationKey::ed25519(&key.public_key()).account_address();
+    let params = TransactionParams::new(sender, 0, ChainId::new(4));
+
+    EntryFunctionCall::Vote { proposal_id: 1, vote: true }
+        .raw_transaction(&ModuleConfig::default(), &params)
+        .unwrap()
+        .sign(&key, key.public_key())
+        .unwrap()
+        .into_inner()
+}
+
+#[tokio::test]
+async fn test_submit_rejects_malformed_transactions() {
+    let (state, _pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/transactions")
+        .insert_header(super::auth_header("0x3"))
+        .set_json(&serde_json::json!({ "signed_transaction": "0xnot-hex" }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 400);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/transactions")
+        .insert_header(super::auth_header("0x3"))
+        .set_json(&serde_json::json!({ "signed_transaction": "0x0102" }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn test_submit_only_relays_own_transactions() {
+    let (state, _pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let txn = signed_vote();
+    let req = test::TestRequest::post()
+        .uri("/api/v1/transactions")
+        .insert_header(super::auth_header("0x3"))
+        .set_json(&serde_json::json!({
+            "signed_transaction": hex::encode(bcs::to_bytes(&txn).unwrap()),
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn test_simulate_rejects_invalid_public_key() {
+    let (state, _pool) = create_test_app_state().await;
+    let app = create_authenticated_test_app(web::Data::new(state)).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/transactions/simulate")
+        .insert_header(super::auth_header("0x3"))
+        .set_json(&serde_json::json!({
+            "public_key": "0x0102",
+            "payload": { "function": "vote", "proposal_id": 1, "vote": true },
+        }))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status().as_u16(), 400);
+}
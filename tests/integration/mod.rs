@@ -1,22 +1,10 @@
-use backend::{
-    Client,
-    db::{operations, schema::*},
-    error::Result,
-};
-use aptos_sdk::{
-    types::{
-        account_address::AccountAddress,
-        transaction::{SignedTransaction, TransactionPayload},
-    },
-};
-use crate::{setup_test_db, test_helpers::create_mock_pending_transaction};
-use sqlx::sqlite::SqlitePool;
+use backend::db::operations;
+use aptos_sdk::types::account_address::AccountAddress;
+use crate::setup_test_db;
 
 #[tokio::test]
 async fn test_fund_creation_with_members() {
     let pool = setup_test_db().await;
-    let mock_client = Client::mock();
-    
     // Create fund
     let fund = operations::create_fund(
         &pool,
@@ -58,8 +46,6 @@ async fn test_fund_creation_with_members() {
 #[tokio::test]
 async fn test_proposal_lifecycle() {
     let pool = setup_test_db().await;
-    let mock_client = Client::mock();
-    
     // Create fund
     let fund = operations::create_fund(
         &pool,
@@ -72,31 +58,33 @@ async fn test_proposal_lifecycle() {
     // Create proposal
     let proposal = operations::create_proposal(
         &pool,
+        fund.id,
         "Test Proposal",
         "Test Description",
-        chrono::Utc::now(),
+        chrono::Utc::now().into(),
     )
     .await
     .expect("Failed to create proposal");
     
     // Add votes
-    let voters = vec![
+    let voters = [
         ("0x5678", true),
         ("0x9abc", true),
         ("0xdef0", false),
     ];
     
     for (voter, vote_type) in voters {
+        let voter = AccountAddress::from_hex_literal(voter).unwrap();
         let vote = operations::vote_on_proposal(
             &pool,
             proposal.id,
-            AccountAddress::from_hex_literal(voter).unwrap(),
+            voter,
             vote_type,
         )
         .await
         .expect("Failed to create vote");
         
-        assert_eq!(vote.voter_address, voter);
+        assert_eq!(vote.voter_address, voter.to_hex_literal());
         assert_eq!(vote.vote_type, vote_type);
     }
     
@@ -3,34 +3,11 @@ pub mod integration;
 pub mod unit;
 
 use sqlx::sqlite::SqlitePool;
-use aptos_sdk::{
-    rest_client::{Client as AptosRestClient, Response, PendingTransaction},
-    types::{
-        account_address::AccountAddress,
-        transaction::{SignedTransaction, TransactionPayload},
-        HashValue,
-    },
-    crypto::{ed25519::Ed25519PrivateKey, Uniform},
-};
-use mockall::automock;
 use backend::{
-    AppState,
-    db::{operations, schema::*},
+    db::{migrations, operations, schema::*},
     error::Result,
-    Client,
 };
 
-// Mock interfaces
-#[automock]
-pub trait AptosRestClientInterface {
-    async fn get_account_balance(&self, address: AccountAddress) -> Result<u64>;
-    async fn get_sequence_number(&self, address: AccountAddress) -> Result<u64>;
-    async fn get_account_resources(&self, address: AccountAddress) -> Result<Vec<serde_json::Value>>;
-    async fn get_account_modules(&self, address: AccountAddress) -> Result<Vec<String>>;
-    async fn get_events(&self, key: String, start: u64, limit: u64) -> Result<Vec<serde_json::Value>>;
-    async fn submit_transaction(&self, txn: SignedTransaction) -> Result<Response<PendingTransaction>>;
-}
-
 // Test utilities
 pub async fn setup_test_db() -> SqlitePool {
     let pool = SqlitePool::connect("sqlite::memory:")
@@ -38,8 +15,7 @@ pub async fn setup_test_db() -> SqlitePool {
         .expect("Failed to create test database");
     
     // Run migrations
-    sqlx::migrate!("./migrations")
-        .run(&pool)
+    migrations::run_migrations(&pool)
         .await
         .expect("Failed to run migrations");
     
@@ -49,7 +25,7 @@ pub async fn setup_test_db() -> SqlitePool {
 // Helper functions for creating test data
 pub mod test_helpers {
     use super::*;
-    use chrono::Utc;
+    use chrono::{Duration, Utc};
 
     pub async fn create_test_fund(pool: &SqlitePool, name: &str) -> Result<Fund> {
         operations::create_fund(
@@ -64,6 +40,7 @@ pub mod test_helpers {
             pool,
             fund_id,
             "0x2",
+            "0x6",
         ).await
     }
 
@@ -76,12 +53,33 @@ pub mod test_helpers {
         ).await
     }
 
-    pub async fn create_test_proposal(pool: &SqlitePool, title: &str) -> Result<Proposal> {
+    pub async fn create_test_proposal(pool: &SqlitePool, fund_id: i64, title: &str) -> Result<Proposal> {
         operations::create_proposal(
             pool,
+            fund_id,
             title,
             "Test description",
-            Utc::now(),
+            (Utc::now() + Duration::days(1)).into(),
+        ).await
+    }
+
+    /// Address of the `GovernanceData` proposals are created in by default.
+    pub fn governance_address() -> String {
+        backend::config::ModuleConfig::default().module_address("governance").to_hex_literal()
+    }
+
+    /// A proposal as indexed from its ProposalCreatedEvent.
+    pub async fn create_chain_proposal(pool: &SqlitePool, fund_id: i64, chain_id: u64) -> Result<Proposal> {
+        let mut conn = pool.acquire().await?;
+        operations::sync_chain_proposal(
+            &mut conn,
+            &governance_address(),
+            chain_id,
+            Some(fund_id),
+            "0x3",
+            "Chain description",
+            (Utc::now() + Duration::days(1)).into(),
+            1,
         ).await
     }
 
@@ -107,11 +105,4 @@ pub mod test_helpers {
             "0x4",
         ).await
     }
-
-    pub fn create_mock_pending_transaction() -> PendingTransaction {
-        PendingTransaction {
-            hash: HashValue::zero(),
-            request: UserTransactionRequest::default(),
-        }
-    }
 } 
\ No newline at end of file
@@ -0,0 +1,192 @@
+use backend::config::{
+    AppConfig, ClientConfig, ModuleConfig, NodeConfig, DEFAULT_MODULE_ADDRESS, MIN_JWT_SECRET_LEN,
+    PRIVATE_KEY_LEN,
+};
+use aptos_sdk::types::account_address::AccountAddress;
+use std::time::Duration;
+use url::Url;
+
+#[test]
+fn test_default_module_address() {
+    let config = ClientConfig::default();
+    let expected = AccountAddress::from_hex_literal(DEFAULT_MODULE_ADDRESS).unwrap();
+
+    assert_eq!(config.modules.address, expected);
+    assert_eq!(config.modules.module_address("governance"), expected);
+}
+
+#[test]
+fn test_module_type_names() {
+    let mut modules = ModuleConfig {
+        address: AccountAddress::from_hex_literal("0xcafe").unwrap(),
+        overrides: Default::default(),
+    };
+    modules.overrides.insert(
+        "position".to_string(),
+        AccountAddress::from_hex_literal("0xbeef").unwrap(),
+    );
+
+    assert_eq!(modules.type_name("asset", "AssetEvents"), "0xcafe::asset::AssetEvents");
+    assert_eq!(
+        modules.type_name("position", "PositionEvents"),
+        "0xbeef::position::PositionEvents"
+    );
+}
+
+/// The defaults with the settings they leave for deployments to provide.
+fn valid_config() -> AppConfig {
+    let mut config = AppConfig::default();
+    config.auth.jwt_secret = Some("s".repeat(MIN_JWT_SECRET_LEN));
+    config
+}
+
+#[test]
+fn test_default_config_is_valid() {
+    assert!(valid_config().validate().is_ok());
+}
+
+#[test]
+fn test_jwt_secret_is_required_outside_dev_networks() {
+    let mut config = AppConfig::default();
+    assert!(config.validate().is_err());
+
+    config.client.primary_node = NodeConfig::new(Url::parse("http://localhost:8080").unwrap());
+    config.client.fallback_nodes.clear();
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_toml_layers_over_defaults() {
+    let config = AppConfig::from_toml(
+        r#"
+        [server]
+        bind_address = "0.0.0.0:9000"
+
+        [client.retry_config]
+        max_attempts = 7
+
+        [[client.fallback_nodes]]
+        url = "https://fullnode.mainnet.example.com"
+        timeout = "3s"
+
+        [sync]
+        sync_interval = "30s"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.server.bind_address.port(), 9000);
+    assert_eq!(config.client.retry_config.max_attempts, 7);
+    // Untouched keys keep their defaults
+    assert_eq!(config.client.retry_config.max_delay, Duration::from_secs(5));
+    assert_eq!(config.database.max_connections, 5);
+
+    assert_eq!(config.client.fallback_nodes.len(), 1);
+    assert_eq!(config.client.fallback_nodes[0].timeout, Duration::from_secs(3));
+    assert_eq!(config.client.fallback_nodes[0].health_check_interval, Duration::from_secs(30));
+    assert_eq!(config.sync.sync_interval, Duration::from_secs(30));
+}
+
+#[test]
+fn test_env_overrides_file() {
+    let mut config = AppConfig::from_toml("[database]\nmax_connections = 2\n").unwrap();
+    let vars = [
+        ("WINDFALL_DB_MAX_CONNECTIONS", "12"),
+        ("WINDFALL_FALLBACK_NODES", "https://a.mainnet.example.com, https://b.mainnet.example.com"),
+        ("WINDFALL_EVENT_INTERVAL", "250ms"),
+        ("WINDFALL_MODULE_ADDRESS", "0xcafe"),
+        ("PATH", "/usr/bin"),
+    ];
+
+    config
+        .apply_env(vars.iter().map(|(k, v)| (k.to_string(), v.to_string())))
+        .unwrap();
+
+    assert_eq!(config.database.max_connections, 12);
+    assert_eq!(config.client.fallback_nodes.len(), 2);
+    assert_eq!(config.sync.event_interval, Duration::from_millis(250));
+    assert_eq!(
+        config.client.modules.address,
+        AccountAddress::from_hex_literal("0xcafe").unwrap()
+    );
+
+    let invalid = [("WINDFALL_RATE_LIMIT_RPS".to_string(), "fast".to_string())];
+    assert!(config.apply_env(invalid).is_err());
+}
+
+#[test]
+fn test_rejects_mixed_networks() {
+    let mut config = valid_config();
+    config.client.fallback_nodes = vec![NodeConfig::new(
+        Url::parse("https://fullnode.testnet.aptoslabs.com").unwrap(),
+    )];
+
+    assert!(config.validate().is_err());
+
+    // Hosts that do not name a network are not second-guessed
+    config.client.fallback_nodes = vec![NodeConfig::new(
+        Url::parse("https://aptos.internal.example.com").unwrap(),
+    )];
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_print_config_round_trips() {
+    let mut config = AppConfig::default();
+    config.sync.confirmation_depth = 42;
+
+    let rendered = config.to_toml().unwrap();
+    let parsed = AppConfig::from_toml(&rendered).unwrap();
+
+    assert_eq!(parsed.sync.confirmation_depth, 42);
+    assert_eq!(parsed.server.bind_address, config.server.bind_address);
+}
+
+#[test]
+fn test_chain_id_must_match_primary_network() {
+    let mut config = valid_config();
+    assert_eq!(config.client.chain_id, Some(1));
+
+    config.client.chain_id = Some(2);
+    assert!(config.validate().is_err());
+
+    let vars = [("WINDFALL_CHAIN_ID".to_string(), "1".to_string())];
+    config.apply_env(vars).unwrap();
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_jwt_secret_is_validated_and_never_printed() {
+    let mut config = AppConfig::default();
+    assert!(config.auth.jwt_secret.is_none());
+
+    let vars = [("WINDFALL_JWT_SECRET".to_string(), "too-short".to_string())];
+    config.apply_env(vars).unwrap();
+    assert!(config.validate().is_err());
+
+    let secret = "a".repeat(MIN_JWT_SECRET_LEN);
+    config.auth.jwt_secret = Some(secret.clone());
+    assert!(config.validate().is_ok());
+
+    let rendered = config.to_toml().unwrap();
+    assert!(!rendered.contains(&secret));
+    assert!(!format!("{:?}", config).contains(&secret));
+}
+
+#[test]
+fn test_executor_key_is_validated_and_never_printed() {
+    let mut config = valid_config();
+    assert!(config.execution.private_key.is_none());
+
+    let vars = [("WINDFALL_EXECUTOR_PRIVATE_KEY".to_string(), "0x1234".to_string())];
+    config.apply_env(vars).unwrap();
+    assert!(config.validate().is_err());
+
+    let key = format!("0x{}", "ab".repeat(PRIVATE_KEY_LEN));
+    config.execution.private_key = Some(key.clone());
+    assert!(config.validate().is_ok());
+
+    let rendered = config.to_toml().unwrap();
+    assert!(!rendered.contains(&key));
+    assert!(!format!("{:?}", config).contains(&key));
+}
@@ -1,6 +1,7 @@
 use super::*;
 use chrono::Utc;
 use backend::db::{operations, schema::*};
+use backend::error::AppError;
 
 #[tokio::test]
 async fn test_create_fund() {
@@ -34,29 +35,55 @@ async fn test_create_fund_wallet() {
         &pool,
         fund.id,
         "0x5678",
+        "0x9abc",
     )
     .await
     .expect("Failed to create fund wallet");
     
     assert_eq!(wallet.fund_id, fund.id);
     assert_eq!(wallet.wallet_address, "0x5678");
+    assert_eq!(wallet.actuator_address, "0x9abc");
+
+    let result = operations::create_fund_wallet(&pool, fund.id, "0xdef0", "0x9abc").await;
+    assert!(matches!(result, Err(AppError::AlreadyExists(_))));
+}
+
+#[tokio::test]
+async fn test_create_fund_wallet_fills_unrecorded_address() {
+    let pool = setup_test_db().await;
+
+    let fund = operations::create_fund(&pool, "Test Fund".to_string(), "0x1234".to_string())
+        .await
+        .expect("Failed to create fund");
+    operations::create_fund_wallet(&pool, fund.id, "", "0x9abc")
+        .await
+        .expect("Failed to create fund wallet");
+
+    let wallet = operations::create_fund_wallet(&pool, fund.id, "0x5678", "0x9abc")
+        .await
+        .expect("Failed to fill in fund wallet");
+    assert_eq!(wallet.wallet_address, "0x5678");
 }
 
 #[tokio::test]
 async fn test_create_proposal() {
     let pool = setup_test_db().await;
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
     
     let proposal = operations::create_proposal(
         &pool,
+        fund.id,
         "Test Proposal",
         "Test Description",
-        Utc::now(),
+        Utc::now().into(),
     )
     .await
     .expect("Failed to create proposal");
     
+    assert_eq!(proposal.fund_id, Some(fund.id));
     assert_eq!(proposal.title, "Test Proposal");
     assert_eq!(proposal.description, "Test Description");
+    assert_eq!(proposal.status, "active");
     assert!(!proposal.executed);
     assert!(!proposal.vetoed);
 }
@@ -64,12 +91,14 @@ async fn test_create_proposal() {
 #[tokio::test]
 async fn test_vote_on_proposal() {
     let pool = setup_test_db().await;
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
     
     let proposal = operations::create_proposal(
         &pool,
+        fund.id,
         "Test Proposal",
         "Test Description",
-        Utc::now(),
+        Utc::now().into(),
     )
     .await
     .expect("Failed to create proposal");
@@ -87,4 +116,318 @@ async fn test_vote_on_proposal() {
     assert_eq!(vote.proposal_id, proposal.id);
     assert_eq!(vote.voter_address, "0x1234");
     assert!(vote.vote_type);
-} 
\ No newline at end of file
+} 
+#[tokio::test]
+async fn test_event_cursor_persists_and_advances() {
+    let pool = setup_test_db().await;
+
+    let start = operations::get_event_cursor(&pool, "asset_transfer_events")
+        .await
+        .expect("Failed to get cursor");
+    assert_eq!(start, 0);
+
+    let mut tx = pool.begin().await.unwrap();
+    operations::advance_event_cursor(&mut *tx, "asset_transfer_events", 42, 1)
+        .await
+        .expect("Failed to advance cursor");
+    tx.commit().await.unwrap();
+
+    // A rolled back transaction must not move the cursor
+    let mut tx = pool.begin().await.unwrap();
+    operations::advance_event_cursor(&mut *tx, "asset_transfer_events", 100, 2)
+        .await
+        .expect("Failed to advance cursor");
+    tx.rollback().await.unwrap();
+
+    let next = operations::get_event_cursor(&pool, "asset_transfer_events")
+        .await
+        .expect("Failed to get cursor");
+    assert_eq!(next, 42);
+}
+
+#[tokio::test]
+async fn test_update_balances_mint_transfer_burn() {
+    let pool = setup_test_db().await;
+    let asset = crate::test_helpers::create_test_asset(&pool).await.unwrap();
+    let zero = "0x0";
+    let mut conn = pool.acquire().await.unwrap();
+
+    // Mint creates the holder row and grows the supply
+    operations::update_balances(&mut conn, "TEST", zero, "0xa", 1000, 1)
+        .await
+        .expect("Failed to mint");
+
+    // Transfer to a holder without a row creates it
+    operations::update_balances(&mut conn, "TEST", "0xa", "0xb", 400, 2)
+        .await
+        .expect("Failed to transfer");
+
+    // Burn shrinks the supply
+    operations::update_balances(&mut conn, "TEST", "0xb", zero, 100, 3)
+        .await
+        .expect("Failed to burn");
+
+    let balances = operations::get_asset_balances(&pool, asset.id).await.unwrap();
+    let amount_of = |holder: &str| {
+        balances.iter().find(|b| b.holder_address == holder).map(|b| b.amount)
+    };
+    assert_eq!(amount_of("0xa"), Some(600));
+    assert_eq!(amount_of("0xb"), Some(300));
+
+    let asset = operations::get_asset_by_id(&pool, asset.id).await.unwrap();
+    assert_eq!(asset.total_supply, 900);
+}
+
+#[tokio::test]
+async fn test_update_balances_rejects_overdraft() {
+    let pool = setup_test_db().await;
+    let asset = crate::test_helpers::create_test_asset(&pool).await.unwrap();
+    let holder = AccountAddress::from_hex_literal("0xa").unwrap();
+    operations::create_balance(&pool, asset.id, holder, 50).await.unwrap();
+    let mut conn = pool.acquire().await.unwrap();
+
+    let result = operations::update_balances(&mut conn, "TEST", &holder.to_string(), "0xb", 100, 1).await;
+    assert!(matches!(result, Err(AppError::InsufficientBalance { .. })));
+
+    // Nothing moved, and the receiver row was never created
+    let balances = operations::get_asset_balances(&pool, asset.id).await.unwrap();
+    assert_eq!(balances.len(), 1);
+    assert_eq!(balances[0].amount, 50);
+}
+
+#[tokio::test]
+async fn test_sync_vote_and_veto_upsert() {
+    let pool = setup_test_db().await;
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    let local = crate::test_helpers::create_test_proposal(&pool, fund.id, "Local Proposal").await.unwrap();
+
+    // The chain numbers proposals from 0 independently of local ids
+    let chain_id = local.id as u64;
+    let governance = crate::test_helpers::governance_address();
+    let end_time = (Utc::now() + chrono::Duration::days(1)).into();
+    let mut conn = pool.acquire().await.unwrap();
+    let proposal = operations::sync_chain_proposal(&mut conn, &governance, chain_id, Some(fund.id), "0xabc", "Chain", end_time, 1)
+        .await
+        .expect("Failed to sync proposal");
+    assert_ne!(proposal.id, local.id);
+    assert!(proposal.synced);
+    assert_eq!(proposal.fund_id, Some(fund.id));
+    assert_eq!(proposal.description, "Chain");
+
+    // Replays refresh the indexed proposal instead of adding another
+    let replayed = operations::sync_chain_proposal(&mut conn, &governance, chain_id, None, "0xabc", "", end_time, 2)
+        .await
+        .unwrap();
+    assert_eq!(replayed.id, proposal.id);
+    assert_eq!(replayed.fund_id, Some(fund.id));
+    drop(conn);
+
+    let local = operations::get_proposal_by_id(&pool, local.id).await.unwrap();
+    assert!(!local.synced);
+    assert!(operations::sync_proposal_veto(&pool, &governance, chain_id + 1, 3).await.unwrap().is_none());
+    // Ids are only meaningful within the governance they were created in
+    assert!(operations::get_proposal_id_by_chain_id(&pool, "0xdead", chain_id).await.unwrap().is_none());
+    assert!(operations::sync_proposal_veto(&pool, "0xdead", chain_id, 3).await.unwrap().is_none());
+
+    let local_id = operations::get_proposal_id_by_chain_id(&pool, &governance, chain_id)
+        .await
+        .unwrap()
+        .expect("Proposal should resolve by chain id");
+    assert_eq!(local_id, proposal.id);
+
+    let voted_at = Utc::now().into();
+    operations::sync_vote(&pool, local_id, "0x3", true, voted_at, 2).await.unwrap();
+    // Replayed or changed votes overwrite rather than duplicate
+    operations::sync_vote(&pool, local_id, "0x3", false, voted_at, 3).await.unwrap();
+    operations::sync_veto_vote(&pool, local_id, "0x3", voted_at, 4).await.unwrap();
+    operations::sync_veto_vote(&pool, local_id, "0x4", voted_at, 4).await.unwrap();
+
+    let votes = operations::get_proposal_votes(&pool, local_id).await.unwrap();
+    assert_eq!(votes.len(), 2);
+    assert!(votes.iter().all(|v| v.on_chain && v.is_veto && !v.vote_type));
+}
+
+#[tokio::test]
+async fn test_position_lifecycle_sync() {
+    let pool = setup_test_db().await;
+    let fund = crate::test_helpers::create_test_fund(&pool, "Position Fund").await.unwrap();
+    let asset = crate::test_helpers::create_test_asset(&pool).await.unwrap();
+
+    let fund_id = operations::get_fund_id_by_executor(&pool, &fund.executor_address).await.unwrap();
+    assert_eq!(fund_id, Some(fund.id));
+
+    let opened_at = Utc::now().into();
+    let position = operations::sync_position_opened(&pool, fund.id, asset.id, 0, 1000, 50, true, opened_at, 1)
+        .await
+        .unwrap();
+    assert_eq!(position.status, POSITION_OPEN);
+    assert_eq!(position.chain_position_id, Some(0));
+
+    // Replaying the open event keeps a single row
+    operations::sync_position_opened(&pool, fund.id, asset.id, 0, 1000, 50, true, opened_at, 1)
+        .await
+        .unwrap();
+
+    let modified = operations::sync_position_modified(&pool, 0, Some(2000), 60, 2).await.unwrap().unwrap();
+    assert_eq!((modified.size, modified.entry_price), (2000, 60));
+
+    let closed = operations::sync_position_closed(&pool, 0, POSITION_LIQUIDATED, 30, 0, Utc::now().into(), 3)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(closed.status, POSITION_LIQUIDATED);
+    assert_eq!(closed.exit_price, Some(30));
+    assert!(closed.closed_at.is_some());
+
+    // Closed positions are no longer modified
+    assert!(operations::sync_position_modified(&pool, 0, None, 70, 4).await.unwrap().is_none());
+    assert!(operations::sync_position_closed(&pool, 9, POSITION_CLOSED, 1, 0, Utc::now().into(), 4).await.unwrap().is_none());
+
+    let open = operations::get_fund_positions(&pool, fund.id, Some(POSITION_OPEN)).await.unwrap();
+    assert!(open.is_empty());
+    let history = operations::get_fund_positions(&pool, fund.id, None).await.unwrap();
+    assert_eq!(history.len(), 1);
+}
+
+#[tokio::test]
+async fn test_position_share_allocation_and_transfer() {
+    let pool = setup_test_db().await;
+    let fund = crate::test_helpers::create_test_fund(&pool, "Share Fund").await.unwrap();
+    let asset = crate::test_helpers::create_test_asset(&pool).await.unwrap();
+    let now = Utc::now().into();
+
+    operations::sync_position_opened(&pool, fund.id, asset.id, 1, 1000, 50, true, now, 1).await.unwrap();
+    operations::sync_share_allocation(&pool, 1, "0xa", 600_000, now, 2).await.unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    operations::sync_share_transfer(&mut conn, 1, "0xa", "0xb", 200_000, now, 3).await.unwrap();
+    let overdraft = operations::sync_share_transfer(&mut conn, 1, "0xb", "0xa", 300_000, now, 4).await;
+    assert!(matches!(overdraft, Err(AppError::InsufficientShares { .. })));
+    drop(conn);
+
+    let sender = operations::get_member_position_shares(&pool, fund.id, "0xa").await.unwrap();
+    assert_eq!(sender.len(), 1);
+    assert_eq!(sender[0].shares, 400_000);
+    assert_eq!(sender[0].status, POSITION_OPEN);
+
+    let receiver = operations::get_member_position_shares(&pool, fund.id, "0xb").await.unwrap();
+    assert_eq!(receiver[0].shares, 200_000);
+}
+
+#[tokio::test]
+async fn test_rollback_reverts_state_above_version() {
+    let pool = setup_test_db().await;
+    let asset = crate::test_helpers::create_test_asset(&pool).await.unwrap();
+    let fund = crate::test_helpers::create_test_fund(&pool, "Rollback Fund").await.unwrap();
+    let zero = "0x0";
+
+    let mut conn = pool.acquire().await.unwrap();
+    operations::update_balances(&mut conn, "TEST", zero, "0xa", 1000, 10).await.unwrap();
+    operations::advance_event_cursor(&mut *conn, "asset_transfer_events", 1, 10).await.unwrap();
+    operations::sync_member_state(&mut *conn, fund.id, "0xm", 50, "active".to_string(), 10).await.unwrap();
+
+    // Everything below happens above version 10 and must be undone
+    operations::update_balances(&mut conn, "TEST", "0xa", "0xb", 400, 20).await.unwrap();
+    operations::advance_event_cursor(&mut *conn, "asset_transfer_events", 2, 20).await.unwrap();
+    operations::sync_member_removal(&mut conn, fund.id, "0xm", 20).await.unwrap();
+    assert_eq!(operations::get_applied_ledger_version(&pool).await.unwrap(), Some(20));
+
+    let summary = operations::rollback_to_version(&mut conn, 10).await.unwrap();
+    assert!(summary.reverted_changes > 0);
+    drop(conn);
+
+    let balances = operations::get_asset_balances(&pool, asset.id).await.unwrap();
+    assert_eq!(balances.len(), 1);
+    assert_eq!(balances[0].holder_address, "0xa");
+    assert_eq!(balances[0].amount, 1000);
+
+    let asset = operations::get_asset_by_id(&pool, asset.id).await.unwrap();
+    assert_eq!(asset.total_supply, 1000);
+
+    assert_eq!(operations::get_event_cursor(&pool, "asset_transfer_events").await.unwrap(), 1);
+
+    let members = operations::get_fund_members(&pool, fund.id).await.unwrap();
+    assert!(members.iter().any(|m| m.member_address == "0xm" && m.share == 50));
+    assert_eq!(operations::get_applied_ledger_version(&pool).await.unwrap(), Some(10));
+}
+
+#[tokio::test]
+async fn test_sync_checkpoint_saved_and_rolled_back() {
+    let pool = setup_test_db().await;
+    assert!(operations::get_sync_checkpoint(&pool, "synchronizer").await.unwrap().is_none());
+
+    operations::save_sync_checkpoint(&pool, "synchronizer", 90, 100).await.unwrap();
+    operations::save_sync_checkpoint(&pool, "synchronizer", 190, 200).await.unwrap();
+
+    let checkpoint = operations::get_sync_checkpoint(&pool, "synchronizer").await.unwrap().unwrap();
+    assert_eq!((checkpoint.ledger_version, checkpoint.ledger_tip), (190, 200));
+
+    // A rollback below the checkpoint makes the next pass re-read from there
+    let mut conn = pool.acquire().await.unwrap();
+    operations::rollback_to_version(&mut conn, 150).await.unwrap();
+    drop(conn);
+
+    let checkpoint = operations::get_sync_checkpoint(&pool, "synchronizer").await.unwrap().unwrap();
+    assert_eq!(checkpoint.ledger_version, 150);
+}
+
+#[tokio::test]
+async fn test_proposal_execution_attempts() {
+    let pool = setup_test_db().await;
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    let local = crate::test_helpers::create_test_proposal(&pool, fund.id, "Local Proposal").await.unwrap();
+    let proposal = crate::test_helpers::create_chain_proposal(&pool, fund.id, 0).await.unwrap();
+    let governance = crate::test_helpers::governance_address();
+
+    // Only passed proposals created on chain are picked up
+    operations::finalize_proposal(&pool, local.id, PROPOSAL_PASSED).await.unwrap();
+    assert!(operations::get_executable_proposals(&pool, &governance, 2).await.unwrap().is_empty());
+    operations::finalize_proposal(&pool, proposal.id, PROPOSAL_PASSED).await.unwrap();
+    assert_eq!(operations::get_executable_proposals(&pool, &governance, 2).await.unwrap().len(), 1);
+
+    // One attempt in flight at a time
+    let first = operations::create_proposal_execution(&pool, proposal.id, "0x1").await.unwrap().unwrap();
+    assert_eq!(first.attempt, 1);
+    assert_eq!(first.status, EXECUTION_PENDING);
+    assert!(operations::create_proposal_execution(&pool, proposal.id, "0x1").await.unwrap().is_none());
+    assert!(operations::get_executable_proposals(&pool, &governance, 2).await.unwrap().is_empty());
+
+    operations::set_execution_txn_hash(&pool, first.id, "0xabc").await.unwrap();
+    let failed = operations::complete_proposal_execution(
+        &pool,
+        first.id,
+        EXECUTION_FAILED,
+        Some(7),
+        Some("Move abort"),
+        None,
+    ).await.unwrap();
+    assert_eq!(failed.txn_hash.as_deref(), Some("0xabc"));
+    assert_eq!(failed.gas_used, Some(7));
+
+    // Failed attempts are retried until they run out
+    assert_eq!(operations::get_executable_proposals(&pool, &governance, 2).await.unwrap().len(), 1);
+    let second = operations::create_proposal_execution(&pool, proposal.id, "0x1").await.unwrap().unwrap();
+    assert_eq!(second.attempt, 2);
+    operations::complete_proposal_execution(&pool, second.id, EXECUTION_FAILED, None, None, Some("timeout"))
+        .await
+        .unwrap();
+    assert!(operations::get_executable_proposals(&pool, &governance, 2).await.unwrap().is_empty());
+
+    let executions = operations::get_proposal_executions(&pool, proposal.id).await.unwrap();
+    assert_eq!(executions.len(), 2);
+    assert!(operations::get_pending_executions(&pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_proposal_fund_membership_lookup() {
+    let pool = setup_test_db().await;
+    let fund = crate::test_helpers::create_test_fund(&pool, "Test Fund").await.unwrap();
+    crate::test_helpers::create_test_member(&pool, fund.id, 100).await.unwrap();
+    let proposal = crate::test_helpers::create_test_proposal(&pool, fund.id, "Test Proposal").await.unwrap();
+
+    assert_eq!(operations::get_proposal_fund_id(&pool, proposal.id).await.unwrap(), Some(fund.id));
+    assert_eq!(operations::get_proposal_fund_id(&pool, 999).await.unwrap(), None);
+
+    let members = operations::get_active_member_addresses(&pool, fund.id).await.unwrap();
+    assert_eq!(members, vec!["0x3".to_string()]);
+}
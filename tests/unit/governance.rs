@@ -0,0 +1,115 @@
+use chrono::{Duration, Utc};
+use backend::{
+    config::GovernanceConfig,
+    db::schema::*,
+    governance::{Outcome, Tally},
+};
+
+fn proposal(end_offset: Duration) -> Proposal {
+    let now = Utc::now();
+    Proposal {
+        id: 1,
+        fund_id: Some(1),
+        title: "Test Proposal".to_string(),
+        description: "Test Description".to_string(),
+        end_time: (now + end_offset).into(),
+        status: PROPOSAL_ACTIVE.to_string(),
+        executed: false,
+        vetoed: false,
+        chain_id: 0,
+        chain_address: None,
+        synced: false,
+        proposer_address: None,
+        created_at: now.into(),
+        updated_at: now.into(),
+    }
+}
+
+fn member(address: &str, share: i64, status: &str) -> FundMember {
+    let now = Utc::now();
+    FundMember {
+        id: 0,
+        fund_id: 1,
+        member_address: address.to_string(),
+        share,
+        status: status.to_string(),
+        created_at: now.into(),
+        updated_at: now.into(),
+    }
+}
+
+fn vote(voter: &str, vote_type: bool) -> Vote {
+    let now = Utc::now();
+    Vote {
+        id: 0,
+        proposal_id: 1,
+        voter_address: voter.to_string(),
+        vote_type,
+        voted_at: Some(now.into()),
+        is_veto: false,
+        on_chain: false,
+        created_at: now.into(),
+        updated_at: now.into(),
+    }
+}
+
+#[test]
+fn test_tally_weighs_votes_by_active_share() {
+    let members = vec![
+        member("0x3", 6000, MEMBER_ACTIVE),
+        // Addresses match whatever form they were stored in
+        member("0x0000000000000000000000000000000000000000000000000000000000000004", 4000, MEMBER_ACTIVE),
+        member("0x5", 5000, "inactive"),
+    ];
+    let votes = vec![vote("0x3", true), vote("0x4", false), vote("0x5", true)];
+
+    let tally = Tally::compute(&GovernanceConfig::default(), &proposal(Duration::days(1)), &members, &votes);
+
+    assert_eq!(tally.eligible_weight, 10000);
+    assert_eq!(tally.yes_weight, 6000);
+    assert_eq!(tally.no_weight, 4000);
+    assert_eq!(tally.counted_votes, 2);
+    assert_eq!(tally.ignored_votes, 1);
+    assert!(!tally.closed);
+    assert_eq!(tally.projected, Outcome::Passed);
+    assert!(tally.emergency_veto_allowed);
+}
+
+#[test]
+fn test_tally_thresholds() {
+    let members = vec![member("0x3", 5000, MEMBER_ACTIVE), member("0x4", 5000, MEMBER_ACTIVE)];
+    let votes = vec![vote("0x3", true), vote("0x4", false)];
+
+    // An even split misses a 51% quorum
+    let tally = Tally::compute(&GovernanceConfig::default(), &proposal(Duration::days(-1)), &members, &votes);
+    assert!(tally.closed);
+    assert_eq!(tally.projected, Outcome::Failed);
+
+    // No votes at all never pass
+    let tally = Tally::compute(&GovernanceConfig::default(), &proposal(Duration::days(-1)), &members, &[]);
+    assert_eq!(tally.projected, Outcome::Failed);
+
+    // A configured veto threshold above the chain's minimum applies
+    let config = GovernanceConfig {
+        veto_threshold: 60,
+        ..GovernanceConfig::default()
+    };
+    let tally = Tally::compute(&config, &proposal(Duration::days(1)), &members, &votes);
+    assert!(!tally.emergency_veto_allowed);
+}
+
+#[test]
+fn test_tally_counts_each_voter_once() {
+    let members = vec![member("0x3", 6000, MEMBER_ACTIVE), member("0x4", 4000, MEMBER_ACTIVE)];
+    // A local vote, then the indexed one in long form, which overrides it
+    let mut indexed = vote("0x0000000000000000000000000000000000000000000000000000000000000003", false);
+    indexed.on_chain = true;
+    let votes = vec![vote("0x3", true), indexed, vote("0x4", true)];
+
+    let tally = Tally::compute(&GovernanceConfig::default(), &proposal(Duration::days(1)), &members, &votes);
+
+    assert_eq!(tally.yes_weight, 4000);
+    assert_eq!(tally.no_weight, 6000);
+    assert_eq!(tally.counted_votes, 2);
+    assert_eq!(tally.ignored_votes, 0);
+}
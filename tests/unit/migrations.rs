@@ -0,0 +1,76 @@
+use super::*;
+use backend::db::migrations::{self, Migration};
+
+const TEST_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_widgets",
+        sql: "CREATE TABLE widgets (id INTEGER PRIMARY KEY);",
+    },
+    Migration {
+        version: 2,
+        name: "add_widget_name",
+        sql: "ALTER TABLE widgets ADD COLUMN name TEXT NOT NULL DEFAULT '';",
+    },
+];
+
+#[tokio::test]
+async fn test_migrations_apply_in_order() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+
+    migrations::run(&pool, TEST_MIGRATIONS)
+        .await
+        .expect("Failed to run migrations");
+
+    let applied = migrations::applied_migrations(&pool).await.unwrap();
+    assert_eq!(applied.len(), 2);
+    assert_eq!(applied[0].version, 1);
+    assert_eq!(applied[1].version, 2);
+    assert_eq!(applied[1].checksum, TEST_MIGRATIONS[1].checksum());
+}
+
+#[tokio::test]
+async fn test_migrations_are_idempotent() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+
+    migrations::run(&pool, &TEST_MIGRATIONS[..1]).await.unwrap();
+    migrations::run(&pool, TEST_MIGRATIONS).await.unwrap();
+    migrations::run(&pool, TEST_MIGRATIONS).await.unwrap();
+
+    let applied = migrations::applied_migrations(&pool).await.unwrap();
+    assert_eq!(applied.len(), 2);
+}
+
+#[tokio::test]
+async fn test_refuses_newer_schema() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+
+    migrations::run(&pool, TEST_MIGRATIONS).await.unwrap();
+
+    // An older binary only knows about the first migration
+    let result = migrations::run(&pool, &TEST_MIGRATIONS[..1]).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_refuses_checksum_mismatch() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+
+    migrations::run(&pool, &TEST_MIGRATIONS[..1]).await.unwrap();
+
+    let edited = [Migration {
+        version: 1,
+        name: "create_widgets",
+        sql: "CREATE TABLE widgets (id INTEGER PRIMARY KEY, extra TEXT);",
+    }];
+    let result = migrations::run(&pool, &edited).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_bundled_migrations_reach_latest_version() {
+    let pool = setup_test_db().await;
+
+    let applied = migrations::applied_migrations(&pool).await.unwrap();
+    assert_eq!(applied.last().map(|m| m.version), Some(migrations::latest_version()));
+}
@@ -1,19 +1,15 @@
 pub mod client;
+pub mod config;
 pub mod db;
+pub mod governance;
+pub mod migrations;
 pub mod models;
+pub mod move_errors;
+pub mod move_events;
+pub mod sequence_numbers;
+pub mod transactions;
 
-use backend::{
-    AppState,
-    db::{operations, schema::*},
-    error::Result,
-    Client,
-};
+use aptos_sdk::types::account_address::AccountAddress;
+use backend::Client;
 use sqlx::sqlite::SqlitePool;
-use crate::{setup_test_db, AptosRestClientInterface};
-use mockall::Mock;
-use aptos_sdk::{
-    types::{
-        account_address::AccountAddress,
-        transaction::{SignedTransaction, TransactionPayload},
-    },
-}; 
\ No newline at end of file
+use crate::setup_test_db;
@@ -1,4 +1,3 @@
-use super::*;
 use chrono::Utc;
 use backend::db::schema::*;
 
@@ -9,6 +8,8 @@ fn test_fund_model() {
         id: 1,
         name: "Test Fund".to_string(),
         executor_address: "0x1234".to_string(),
+        version: 0,
+        status: "active".to_string(),
         created_at: now.into(),
         updated_at: now.into(),
     };
@@ -26,6 +27,7 @@ fn test_fund_member_model() {
         fund_id: 1,
         member_address: "0x1234".to_string(),
         share: 5000,
+        status: MEMBER_ACTIVE.to_string(),
         created_at: now.into(),
         updated_at: now.into(),
     };
@@ -41,12 +43,15 @@ fn test_proposal_model() {
     let now = Utc::now();
     let proposal = Proposal {
         id: 1,
+        fund_id: Some(1),
         title: "Test Proposal".to_string(),
         description: "Test Description".to_string(),
         end_time: now.into(),
+        status: "active".to_string(),
         executed: false,
         vetoed: false,
         chain_id: 0,
+        chain_address: None,
         synced: false,
         proposer_address: None,
         created_at: now.into(),
@@ -0,0 +1,90 @@
+use aptos_sdk::types::account_address::AccountAddress;
+use backend::{
+    config::ModuleConfig,
+    error::AppError,
+    move_errors::{lookup, MoveAbort},
+    transactions::Simulation,
+};
+use actix_web::ResponseError;
+
+#[test]
+fn test_lookup_named_constants() {
+    assert_eq!(lookup("governance", 5).unwrap().0, "EALREADY_VOTED");
+    assert_eq!(lookup("asset", 7).unwrap().0, "EINSUFFICIENT_BALANCE");
+    assert!(lookup("governance", 99).is_none());
+    assert!(lookup("unknown", 1).is_none());
+}
+
+#[test]
+fn test_decode_windfall_abort() {
+    let modules = ModuleConfig::default();
+    let status = format!(
+        "Move abort in {}::governance: 0x30005",
+        modules.address.to_hex_literal()
+    );
+
+    let abort = MoveAbort::from_vm_status(&status, &modules).unwrap();
+    assert_eq!(abort.abort_code, 0x30005);
+    assert_eq!(abort.category, Some("INVALID_STATE"));
+    assert_eq!(abort.name.as_deref(), Some("EALREADY_VOTED"));
+    assert_eq!(abort.reason, "The sender has already voted on this proposal");
+
+    // Decimal codes and published error maps decode the same way
+    let status = format!(
+        "Move abort in {}::asset: EINSUFFICIENT_BALANCE(0x10007): not enough",
+        modules.address.to_hex_literal()
+    );
+    let abort = MoveAbort::from_vm_status(&status, &modules).unwrap();
+    assert_eq!(abort.name.as_deref(), Some("EINSUFFICIENT_BALANCE"));
+    assert_eq!(abort.category, Some("INVALID_ARGUMENT"));
+
+    let status = format!("Move abort in {}::asset: 65543", modules.address.to_hex_literal());
+    let abort = MoveAbort::from_vm_status(&status, &modules).unwrap();
+    assert_eq!(abort.name.as_deref(), Some("EINSUFFICIENT_BALANCE"));
+}
+
+#[test]
+fn test_decode_abort_outside_windfall() {
+    let mut modules = ModuleConfig::default();
+    modules.address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+
+    // Same module name and code, but not where the package is published
+    let abort = MoveAbort::from_vm_status("Move abort in 0x1::governance: 0x30005", &modules).unwrap();
+    assert_eq!(abort.name, None);
+    assert_eq!(abort.reason, "0x1::governance aborted with code 196613");
+
+    let abort = MoveAbort::from_vm_status(
+        "Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins",
+        &modules,
+    ).unwrap();
+    assert_eq!(abort.name.as_deref(), Some("EINSUFFICIENT_BALANCE"));
+    assert_eq!(abort.reason, "0x1::coin aborted with EINSUFFICIENT_BALANCE");
+
+    assert!(MoveAbort::from_vm_status("Out of gas", &modules).is_none());
+    assert!(MoveAbort::from_vm_status("Executed successfully", &modules).is_none());
+}
+
+#[test]
+fn test_simulation_reports_fee_and_failure() {
+    let modules = ModuleConfig::default();
+
+    let simulation = Simulation::new(true, "Executed successfully".to_string(), 12, 100, 20_000, &modules);
+    assert_eq!(simulation.estimated_fee, 1200);
+    assert!(simulation.abort.is_none());
+    assert!(simulation.failure_reason().is_none());
+
+    let status = format!(
+        "Move abort in {}::governance: 0x30005",
+        modules.address.to_hex_literal()
+    );
+    let simulation = Simulation::new(false, status, 8, 100, 20_000, &modules);
+    let reason = simulation.failure_reason().unwrap();
+    assert_eq!(reason, "The sender has already voted on this proposal (EALREADY_VOTED)");
+
+    let error = AppError::SimulationFailed(reason);
+    assert_eq!(error.code(), "simulation_failed");
+    assert_eq!(error.status_code().as_u16(), 422);
+
+    let simulation = Simulation::new(false, "Out of gas".to_string(), 20_000, 100, 20_000, &modules);
+    assert_eq!(simulation.failure_reason().as_deref(), Some("Out of gas"));
+}
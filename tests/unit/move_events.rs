@@ -0,0 +1,86 @@
+use backend::move_events::{
+    decode_payload, parse_event_type, position_effect, DecodeError, EntryCall,
+    PositionClosedEvent, PositionEffect, WindfallEvent,
+};
+use serde_json::json;
+
+#[test]
+fn test_parse_event_type() {
+    let (module, name) = parse_event_type("0x1::governance::VoteEvent").unwrap();
+    assert_eq!(module, "governance");
+    assert_eq!(name, "VoteEvent");
+
+    assert!(parse_event_type("VoteEvent").is_err());
+}
+
+#[test]
+fn test_decode_transfer_event_with_string_u64s() {
+    let data = json!({
+        "symbol": "TEST",
+        "from": "0xa",
+        "to": "0xb",
+        "amount": "1000",
+        "transfer_time": "1700000000000000",
+    });
+
+    let event = decode_payload("0x1::asset::TransferEvent", &data).unwrap();
+    assert_eq!(event.timestamp(), 1_700_000_000_000_000);
+    match event {
+        WindfallEvent::Transfer(transfer) => {
+            assert_eq!(transfer.symbol, "TEST");
+            assert_eq!(transfer.amount, 1000);
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn test_decode_vote_event() {
+    let data = json!({
+        "proposal_id": "7",
+        "voter": "0xa",
+        "vote": false,
+        "timestamp": "42",
+    });
+
+    let event = decode_payload("0x1::governance::VoteEvent", &data).unwrap();
+    assert!(matches!(event, WindfallEvent::Vote(ref v) if v.proposal_id == 7 && !v.vote));
+}
+
+#[test]
+fn test_decode_failures() {
+    let unknown = decode_payload("0x1::asset::UnknownEvent", &json!({}));
+    assert!(matches!(unknown, Err(DecodeError::UnknownType(_))));
+
+    let malformed = decode_payload("0x1::asset::MintEvent", &json!({ "symbol": "TEST" }));
+    assert!(matches!(malformed, Err(DecodeError::Payload { .. })));
+}
+
+#[test]
+fn test_position_effect_from_entry_function() {
+    let closed = PositionClosedEvent {
+        position_id: 3,
+        exit_price: 120,
+        pnl: 0,
+        timestamp: 42,
+    };
+    let call = |function: &str, arguments: Vec<serde_json::Value>| EntryCall {
+        sender: "0xa".to_string(),
+        module: "position".to_string(),
+        function: function.to_string(),
+        arguments,
+    };
+
+    let modify = call("modify_position", vec![json!("3"), json!("500"), json!("120")]);
+    assert_eq!(
+        position_effect(&closed, Some(&modify)),
+        PositionEffect::Modified { size: Some(500), entry_price: 120 }
+    );
+
+    let liquidate = call("liquidate_position", vec![json!("3"), json!("120")]);
+    assert_eq!(position_effect(&closed, Some(&liquidate)), PositionEffect::Liquidated);
+
+    let close = call("close_position", vec![json!("3"), json!("120")]);
+    assert_eq!(position_effect(&closed, Some(&close)), PositionEffect::Closed);
+    assert_eq!(position_effect(&closed, None), PositionEffect::Closed);
+}
@@ -0,0 +1,101 @@
+use aptos_sdk::types::account_address::AccountAddress;
+use backend::{error::AppError, utils::SequenceNumbers};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+fn far_future() -> u64 {
+    chrono::Utc::now().timestamp() as u64 + 600
+}
+
+#[tokio::test]
+async fn test_reserves_consecutive_numbers_locally() {
+    let numbers = SequenceNumbers::new();
+    let address = AccountAddress::from_hex_literal("0x3").unwrap();
+    let fetches = &AtomicUsize::new(0);
+    let on_chain = move || async move {
+        fetches.fetch_add(1, Ordering::SeqCst);
+        Ok(7)
+    };
+
+    assert_eq!(numbers.reserve(address, far_future(), on_chain).await.unwrap(), 7);
+    assert_eq!(numbers.reserve(address, far_future(), on_chain).await.unwrap(), 8);
+    assert_eq!(numbers.reserve(address, far_future(), on_chain).await.unwrap(), 9);
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    assert_eq!(numbers.in_flight(address).await, vec![7, 8, 9]);
+
+    numbers.complete(address, 7).await;
+    assert_eq!(numbers.in_flight(address).await, vec![8, 9]);
+
+    // Only the latest number can be handed out again
+    numbers.release(address, 8).await;
+    numbers.release(address, 9).await;
+    assert_eq!(numbers.reserve(address, far_future(), on_chain).await.unwrap(), 9);
+
+    // Other accounts are counted separately
+    let other = AccountAddress::from_hex_literal("0x4").unwrap();
+    assert_eq!(numbers.reserve(other, far_future(), || async { Ok(0) }).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_concurrent_reservations_never_collide() {
+    let numbers = Arc::new(SequenceNumbers::new());
+    let address = AccountAddress::from_hex_literal("0x3").unwrap();
+
+    let tasks: Vec<_> = (0..20)
+        .map(|_| {
+            let numbers = numbers.clone();
+            tokio::spawn(async move {
+                numbers.reserve(address, far_future(), || async { Ok(100) }).await.unwrap()
+            })
+        })
+        .collect();
+
+    let mut reserved = Vec::new();
+    for task in tasks {
+        reserved.push(task.await.unwrap());
+    }
+    reserved.sort();
+    assert_eq!(reserved, (100..120).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_resyncs_after_rejection_and_expired_gap() {
+    let numbers = SequenceNumbers::new();
+    let address = AccountAddress::from_hex_literal("0x3").unwrap();
+
+    assert_eq!(numbers.reserve(address, far_future(), || async { Ok(5) }).await.unwrap(), 5);
+    assert_eq!(numbers.reserve(address, far_future(), || async { Ok(5) }).await.unwrap(), 6);
+
+    // The node says another signer used the account meanwhile
+    numbers.resync(address).await;
+    assert_eq!(numbers.reserve(address, far_future(), || async { Ok(10) }).await.unwrap(), 10);
+    assert_eq!(numbers.in_flight(address).await, vec![10]);
+
+    // 11 expires without committing, so it is handed out again
+    numbers.complete(address, 10).await;
+    assert_eq!(numbers.reserve(address, 0, || async { Ok(11) }).await.unwrap(), 11);
+    assert_eq!(numbers.reserve(address, 0, || async { Ok(11) }).await.unwrap(), 11);
+    assert_eq!(numbers.in_flight(address).await, vec![11]);
+
+    // Fetch errors are passed on
+    numbers.resync(address).await;
+    let result = numbers
+        .reserve(address, far_future(), || async { Err(AppError::transient("node down")) })
+        .await;
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_sequence_number_rejections_are_conflicts() {
+    use actix_web::ResponseError;
+
+    let error = AppError::SequenceNumber("SEQUENCE_NUMBER_TOO_OLD".to_string());
+    assert_eq!(error.code(), "sequence_number_rejected");
+    assert_eq!(error.status_code().as_u16(), 409);
+    assert!(!error.is_retryable());
+
+    assert!(backend::error::is_sequence_number_status("Validation Code: SEQUENCE_NUMBER_TOO_NEW"));
+    assert!(!backend::error::is_sequence_number_status("Move abort in 0x1::governance: 0x30005"));
+}
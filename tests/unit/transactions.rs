@@ -0,0 +1,105 @@
+use aptos_sdk::types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::TransactionPayload,
+};
+use backend::{
+    config::ModuleConfig,
+    transactions::{EntryFunctionCall, MoveArg, TransactionParams, DEFAULT_MAX_GAS_AMOUNT},
+};
+use std::time::Duration;
+
+#[test]
+fn test_entry_function_arguments_are_bcs_encoded() {
+    let call = EntryFunctionCall::Invest {
+        fund_addr: AccountAddress::from_hex_literal("0x2").unwrap(),
+        target: AccountAddress::from_hex_literal("0x3").unwrap(),
+        amount: 500,
+    };
+
+    let (module, function, args) = call.entry_function().unwrap();
+    assert_eq!((module, function), ("asset", "invest"));
+    assert_eq!(args.len(), 3);
+    assert_eq!(args[0], MoveArg::Address(AccountAddress::from_hex_literal("0x2").unwrap()));
+    assert_eq!(args[0].to_bcs().unwrap(), AccountAddress::from_hex_literal("0x2").unwrap().to_vec());
+    assert_eq!(args[2].to_bcs().unwrap(), 500u64.to_le_bytes().to_vec());
+
+    // Strings are length-prefixed
+    let call = EntryFunctionCall::CreateActuatorProposal {
+        new_actuator: AccountAddress::from_hex_literal("0x4").unwrap(),
+        description: "abc".to_string(),
+    };
+    let (_, _, args) = call.entry_function().unwrap();
+    assert_eq!(args[1].to_bcs().unwrap(), vec![3, b'a', b'b', b'c']);
+
+    assert!(EntryFunctionCall::PauseAll.entry_function().unwrap().2.is_empty());
+
+    let call = EntryFunctionCall::CreateFund {
+        name: "Fund".to_string(),
+        description: "Test".to_string(),
+        executor: AccountAddress::from_hex_literal("0x1").unwrap(),
+        initial_members: vec![],
+        metadata_keys: vec!["strategy".to_string()],
+        metadata_values: vec![],
+    };
+    assert!(call.entry_function().is_err());
+}
+
+#[test]
+fn test_raw_transaction_uses_params_and_module_address() {
+    let mut modules = ModuleConfig::default();
+    let security_address = AccountAddress::from_hex_literal("0x5ec").unwrap();
+    modules.overrides.insert("security".to_string(), security_address);
+
+    let sender = AccountAddress::from_hex_literal("0x1").unwrap();
+    let params = TransactionParams::new(sender, 7, ChainId::new(2))
+        .with_gas_unit_price(150)
+        .with_ttl(Duration::from_secs(30));
+    assert_eq!(params.max_gas_amount, DEFAULT_MAX_GAS_AMOUNT);
+
+    let raw = EntryFunctionCall::PauseModule { module_id: 2 }
+        .raw_transaction(&modules, &params)
+        .unwrap();
+    assert_eq!(raw.sender(), sender);
+    assert_eq!(raw.sequence_number(), 7);
+    assert_eq!(raw.gas_unit_price(), 150);
+    assert_eq!(raw.chain_id(), ChainId::new(2));
+    assert_eq!(raw.expiration_timestamp_secs(), params.expiration_timestamp_secs);
+
+    let TransactionPayload::EntryFunction(entry_function) = raw.into_payload() else {
+        panic!("expected an entry function payload");
+    };
+    assert_eq!(*entry_function.module().address(), security_address);
+    assert_eq!(entry_function.module().name().as_str(), "security");
+    assert_eq!(entry_function.function().as_str(), "pause_module");
+    assert_eq!(entry_function.args(), &[vec![2u8]]);
+}
+
+#[test]
+fn test_calls_deserialize_from_json() {
+    let call: EntryFunctionCall = serde_json::from_value(serde_json::json!({
+        "function": "vote",
+        "proposal_id": 3,
+        "vote": true,
+    }))
+    .unwrap();
+
+    assert_eq!(call, EntryFunctionCall::Vote { proposal_id: 3, vote: true });
+}
+
+#[test]
+fn test_json_payload_for_wallets() {
+    let modules = ModuleConfig::default();
+    let payload = EntryFunctionCall::Vote { proposal_id: 3, vote: true }
+        .json_payload(&modules)
+        .unwrap();
+
+    assert_eq!(payload.payload_type, "entry_function_payload");
+    assert_eq!(
+        payload.function,
+        format!("{}::governance::vote", modules.address.to_hex_literal())
+    );
+    assert!(payload.type_arguments.is_empty());
+    // u64 arguments travel as strings
+    assert_eq!(payload.arguments, vec![serde_json::json!("3"), serde_json::json!(true)]);
+}